@@ -1,21 +1,19 @@
 use std::{
     error::Error,
     fs::{self, File},
-    io::{Cursor, Write, stdout},
+    io::{Cursor, Write},
     path::Path,
     process::{Command, Stdio},
+    time::Duration,
 };
 
 use clap::error::Result;
-use crossterm::{
-    terminal::{disable_raw_mode, enable_raw_mode},
-    tty::IsTty,
-};
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
 use image::{DynamicImage, ImageFormat};
-use markdownify::ConvertOptions;
+use indicatif::{ProgressBar, ProgressStyle};
 use rasteroid::{
     InlineEncoder,
-    image_extended::{InlineImage, ZoomPanViewport},
+    image_extended::{self, InlineImage, ZoomPanViewport},
     term_misc,
 };
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
@@ -23,8 +21,10 @@ use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use crate::{
     config::McatConfig,
     converter::{self},
+    fetch_manager,
     image_viewer::{clear_screen, run_interactive_viewer, show_help_prompt},
-    markdown_viewer,
+    markdown_viewer::{self, themes::CustomTheme},
+    text_viewer,
 };
 
 pub enum CatType {
@@ -38,6 +38,9 @@ pub enum CatType {
     Interactive,
 }
 
+/// images encoding into more than this show a progress bar while they're sent
+const TRANSFER_PROGRESS_MIN_BYTES: usize = 2_000_000;
+
 pub fn get_album(path: &Path) -> Option<Vec<DynamicImage>> {
     let ext = path
         .extension()
@@ -95,6 +98,11 @@ pub fn cat(
     if !path.exists() {
         return Err(format!("invalid path: {}", path.display()).into());
     }
+    if opts.report
+        && let Ok(meta) = markdownify::metadata::metadata(path)
+    {
+        report_metadata(path, &meta, opts.report_json);
+    }
 
     let (result, from, to) = load(path, out, opts)?;
     let (string_result, image_result) = match result {
@@ -103,6 +111,13 @@ pub fn cat(
         LoadResult::Handled(cat_type) => return Ok(cat_type),
     };
 
+    if let (Some(n), Some(md)) = (opts.extract_code, &string_result) {
+        let code = markdown_viewer::code_block(md, n, opts)
+            .ok_or_else(|| format!("{} has no code block {n}", path.display()))?;
+        out.write_all(code.as_bytes())?;
+        return Ok(CatType::Markdown);
+    }
+
     // converting
     match (from.as_ref(), to.as_ref()) {
         ("md", "md") => {
@@ -110,44 +125,44 @@ pub fn cat(
             Ok(CatType::Markdown)
         }
         ("md", "html") => {
-            let html = markdown_viewer::md_to_html(&string_result.unwrap(), if opts.style_html {Some(opts.theme.as_ref())} else {None});
+            let html = markdown_viewer::md_to_html(&string_result.unwrap(), if opts.style_html {Some(opts.theme.as_ref())} else {None}, Some(opts.code_theme()), !opts.no_emoji);
             out.write_all(html.as_bytes())?;
             Ok(CatType::Html)
         },
         ("md", "image") => {
-            let html = markdown_viewer::md_to_html(&string_result.unwrap(), Some(opts.theme.as_ref()));
-            let image = converter::html_to_image(&html)?;
+            let html = markdown_viewer::md_to_html(&string_result.unwrap(), Some(opts.theme.as_ref()), Some(opts.code_theme()), !opts.no_emoji);
+            let image = converter::html_to_image(&html, path.parent())?;
             out.write_all(&image)?;
             Ok(CatType::Image)
         },
         ("md", "inline") => {
-            let html = markdown_viewer::md_to_html(&string_result.unwrap(), Some(opts.theme.as_ref()));
-            let image = converter::html_to_image(&html)?;
+            let html = markdown_viewer::md_to_html(&string_result.unwrap(), Some(opts.theme.as_ref()), Some(opts.code_theme()), !opts.no_emoji);
+            let image = converter::html_to_image(&html, path.parent())?;
             let dyn_img = image::load_from_memory(&image)?;
             print_image(out, dyn_img, opts)?;
             Ok(CatType::InlineImage)
         },
         ("md", "interactive") => {
-            let html = markdown_viewer::md_to_html(&string_result.unwrap(), Some(opts.theme.as_ref()));
-            let img_bytes = converter::html_to_image(&html)?;
+            let html = markdown_viewer::md_to_html(&string_result.unwrap(), Some(opts.theme.as_ref()), Some(opts.code_theme()), !opts.no_emoji);
+            let img_bytes = converter::html_to_image(&html, path.parent())?;
             let img = image::load_from_memory(&img_bytes)?;
             interact_with_image(vec![img], opts, out)?;
             Ok(CatType::Interactive)
         },
         ("html", "image") => {
-            let image = converter::html_to_image(&string_result.unwrap())?;
+            let image = converter::html_to_image(&string_result.unwrap(), path.parent())?;
             out.write_all(&image)?;
             Ok(CatType::Image)
         },
         ("html", "inline") => {
-            let image = converter::html_to_image(&string_result.unwrap())?;
+            let image = converter::html_to_image(&string_result.unwrap(), path.parent())?;
             let dyn_img = image::load_from_memory(&image)?;
             print_image(out, dyn_img, opts)?;
             Ok(CatType::InlineImage)
         },
         ("html", "interactive") => {
             let html = &string_result.unwrap();
-            let img_bytes = converter::html_to_image(&html)?;
+            let img_bytes = converter::html_to_image(html, path.parent())?;
             let img = image::load_from_memory(&img_bytes)?;
             interact_with_image(vec![img], opts, out)?;
             Ok(CatType::Interactive)
@@ -170,26 +185,35 @@ pub fn cat(
             if from == "html" {
                 res = format!("```html\n{res}\n```");
             }
-            let is_tty = stdout().is_tty();
+            let is_tty = opts.is_tty();
             let use_color = opts.color.should_use(is_tty);
+            // mcat's own pager, it wraps the markdown again when the terminal is resized
+            if use_color && is_tty && opts.pager_command() == "builtin" {
+                let content = markdown_viewer::md_to_ansi(&res, &opts, Some(path));
+                let long = content.lines().count() > term_misc::get_wininfo().sc_height as usize;
+                if opts.paging.should_use(long) {
+                    text_viewer::view(&res, opts, Some(path))?;
+                    return Ok(CatType::Pretty);
+                }
+            }
             let content = match use_color {
                 true => markdown_viewer::md_to_ansi(&res, &opts, Some(path)),
+                false if markdown_viewer::stats::show_footer(&opts.stats, false) => {
+                    let stats = markdown_viewer::md_stats(&res, opts);
+                    format!("{}\n\n{}\n", res.trim_end(), stats.footer())
+                }
                 false => res,
             };
-            let use_pager = opts.paging.should_use(is_tty && content.lines().count() > term_misc::get_wininfo().sc_height as usize);
-            if use_pager {
-                if let Some(pager) = Pager::new(opts.pager.as_ref()) {
-                    if pager.page(&content).is_err() {
-                        out.write_all(content.as_bytes())?;
-                    }
-                } else {
-                    out.write_all(content.as_bytes())?;
-                }
-                Ok(CatType::Pretty)
-            } else {
-                out.write_all(content.as_bytes())?;
-                return Ok(CatType::Markdown)
-            }
+            print_text(out, content, opts, use_color)
+        },
+        ("diff", _) => {
+            let diff = string_result.unwrap();
+            let use_color = opts.color.should_use(opts.is_tty());
+            let content = match use_color {
+                true => markdown_viewer::diff::diff_to_ansi(&diff, &CustomTheme::from(opts.theme.as_ref())),
+                false => diff,
+            };
+            print_text(out, content, opts, use_color)
         },
         ("image", _) => {
             // default for image
@@ -203,6 +227,31 @@ pub fn cat(
     }
 }
 
+/// the `--grep`ed text, through the pager when it's longer than the terminal
+fn print_text(
+    out: &mut impl Write,
+    content: String,
+    opts: &McatConfig,
+    use_color: bool,
+) -> Result<CatType, Box<dyn std::error::Error>> {
+    let content = match &opts.grep {
+        Some(re) => text_viewer::grep(&content, re, opts.grep_context, use_color),
+        None => content,
+    };
+    let use_pager = opts.paging.should_use(
+        opts.is_tty() && content.lines().count() > term_misc::get_wininfo().sc_height as usize,
+    );
+    if !use_pager {
+        out.write_all(content.as_bytes())?;
+        return Ok(CatType::Markdown);
+    }
+    match Pager::new(&opts.pager_command()) {
+        Some(pager) if pager.page(&content).is_ok() => {}
+        _ => out.write_all(content.as_bytes())?,
+    }
+    Ok(CatType::Pretty)
+}
+
 pub enum LoadResult {
     Image(DynamicImage),
     Text(String),
@@ -220,8 +269,14 @@ pub fn load(
         .into_owned();
     let to = opts.output.as_deref().unwrap_or("unknown").to_owned();
 
+    // animated webp, only sixel plays it, the rest show its first frame
+    let animated_webp = ext.eq_ignore_ascii_case("webp")
+        && opts.inline_encoder == InlineEncoder::Sixel
+        && to != "image"
+        && converter::is_animated_webp(path);
+
     //video
-    if is_video(&ext) {
+    if is_video(&ext) || animated_webp {
         if to == "video" {
             let content = fs::read(path)?;
             out.write_all(&content)?;
@@ -229,29 +284,32 @@ pub fn load(
             let res = LoadResult::Handled(CatType::Video);
             return Ok((res, "video".to_owned(), to));
         }
-        converter::inline_a_video(
-            path.to_string_lossy(),
-            out,
-            &opts.inline_encoder,
-            opts.inline_options.width.as_deref(),
-            opts.inline_options.height.as_deref(),
-            opts.inline_options.center,
-            opts.silent,
-        )?;
+        converter::inline_a_video(path.to_string_lossy(), out, opts)?;
 
         let res = LoadResult::Handled(CatType::InlineVideo);
         return Ok((res, "video".to_owned(), to));
     }
 
-    // pdf to images
+    // iWork, shown as the preview it bundles
+    if converter::is_iwork(path) {
+        let preview = converter::iwork_preview(path)?;
+        return load(preview.path(), out, opts);
+    }
+
+    // pdf to images, all the pages (or --pages) stacked
     if matches!(ext.as_ref(), "pdf" | "tex" | "typ")
         && matches!(to.as_ref(), "inline" | "image" | "interactive")
-        && converter::get_pdf_command().is_ok()
     {
+        if !fetch_manager::is_poppler_installed() {
+            return Err(
+                "rendering pdf pages needs poppler, neither pdftocairo nor pdftoppm is in PATH"
+                    .into(),
+            );
+        }
         let (path, _tmpfile, _tmpfolder) = converter::get_pdf(path);
 
         // goes back to normal parsing if fails.
-        if let Ok(img_data) = converter::pdf_to_image(&path.to_string_lossy().to_owned(), 1) {
+        if let Ok(img_data) = converter::pdf_pages_to_image(&path, opts.pages.clone()) {
             match to.as_ref() {
                 "image" => {
                     let res = LoadResult::Handled(CatType::Image);
@@ -280,73 +338,244 @@ pub fn load(
         return Ok((res, "image".to_owned(), to));
     }
 
+    // multipage tiff, all the pages (or --pages) stacked
+    if matches!(ext.to_lowercase().as_ref(), "tif" | "tiff") {
+        let dyn_img =
+            converter::tiff_pages_to_image(path, opts.pages.clone(), !opts.no_auto_orient)?;
+
+        let res = LoadResult::Image(dyn_img);
+        return Ok((res, "image".to_owned(), to));
+    }
+
+    //heic / heif / avif / jxl / camera raw
+    if converter::is_extended_image(&ext) {
+        let dyn_img = converter::open_extended_image(path)?;
+
+        let res = LoadResult::Image(dyn_img);
+        return Ok((res, "image".to_owned(), to));
+    }
+
     //image
     if ImageFormat::from_extension(&ext).is_some() {
         let buf = fs::read(path)?;
-        let dyn_img = image::load_from_memory(&buf)?;
+        let dyn_img = image_extended::load_image(&buf, !opts.no_auto_orient)?;
 
         let res = LoadResult::Image(dyn_img);
         return Ok((res, "image".to_owned(), to));
     }
 
+    // unified diffs, colored on the terminal and highlighted as a code block anywhere else
+    if opts.diff && path.is_file() {
+        let diff = clip_lines(&fs::read_to_string(path)?, opts);
+        return match to.as_ref() {
+            "html" | "image" | "inline" | "interactive" => {
+                let md = markdown_viewer::utils::fence_code(&diff, "diff");
+                Ok((LoadResult::Text(md), "md".to_owned(), to))
+            }
+            _ => Ok((LoadResult::Text(diff), "diff".to_owned(), to)),
+        };
+    }
+
+    // source files, fenced so the markdown viewer highlights them
+    if (opts.as_code || !markdownify::has_converter(&ext)) && path.is_file() {
+        let content = String::from_utf8(fs::read(path)?)
+            .ok()
+            .filter(|c| !c.contains('\0'))
+            .ok_or_else(|| format!("{} is a binary file", path.display()))?;
+        let first_line = content.lines().next().unwrap_or_default();
+        // unknown languages stay plain text, unless --as-code asked for a fence
+        let lang = markdown_viewer::utils::detect_source_lang(path, first_line)
+            .or_else(|| opts.as_code.then(|| ext.clone()));
+        if let Some(lang) = lang {
+            // clipped before fencing, the fence must stay whole
+            let content = clip_lines(&content, opts);
+            let md = match content.is_empty() {
+                true => content,
+                false => markdown_viewer::utils::fence_code(&content, &lang),
+            };
+            let res = LoadResult::Text(md);
+            return Ok((res, "md".to_owned(), to));
+        }
+    }
+
     // local file or dir
     match ext.as_ref() {
         "md" | "html" => {
             let r = fs::read_to_string(path)?;
 
-            let res = LoadResult::Text(r);
+            let res = LoadResult::Text(clip_lines(&r, opts));
             return Ok((res, ext, to));
         }
         _ => {
-            let screen_size = term_misc::get_wininfo();
-            let opts = ConvertOptions::new(path)
-                .with_screen_size((screen_size.sc_width, screen_size.sc_height));
-            let f = markdownify::convert(opts)?;
+            let f = markdownify::convert(opts.convert_options(path))?;
 
-            let res = LoadResult::Text(f);
+            let res = LoadResult::Text(clip_lines(&f, opts));
             return Ok((res, "md".to_owned(), to));
         }
     }
 }
 
+/// keeps only the `--lines` range of the text, markdown is clipped by its source lines too
+fn clip_lines(text: &str, opts: &McatConfig) -> String {
+    let Some(range) = &opts.lines else {
+        return text.to_owned();
+    };
+    let skip = range.start() - 1;
+    let take = range.end() - skip;
+    text.lines()
+        .skip(skip)
+        .take(take)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 fn print_image(
     out: &mut impl Write,
     dyn_img: DynamicImage,
     opts: &McatConfig,
 ) -> Result<(), Box<dyn Error>> {
     let resize_for_ascii = match opts.inline_encoder {
-        rasteroid::InlineEncoder::Ascii => true,
+        rasteroid::InlineEncoder::Ascii | rasteroid::InlineEncoder::HalfBlock => true,
         _ => false,
     };
 
+    let inline_options = &opts.inline_options;
+    let dyn_img = match (inline_options.rotate, inline_options.flip) {
+        (None, None) => dyn_img,
+        (rotate, flip) => dyn_img.rotate_flip(rotate.unwrap_or_default(), flip),
+    };
+    let dyn_img = match &inline_options.crop {
+        Some([x, y, w, h]) => dyn_img.crop_plus(x, y, w, h)?,
+        None => dyn_img,
+    };
     let dyn_img = apply_pan_zoom_once(dyn_img, &opts);
-    let (img, center, _, _) = dyn_img.resize_plus(
+    let (img, center, _, _) = dyn_img.resize_plus_max(
         opts.inline_options.width.as_deref(),
         opts.inline_options.height.as_deref(),
         resize_for_ascii,
         false,
+        opts.image_px_cap(),
     )?;
     if opts.report {
         rasteroid::term_misc::report_size(
             &opts.inline_options.width.as_deref().unwrap_or(""),
             &opts.inline_options.height.as_deref().unwrap_or(""),
         );
+        let (max_pixels, max_bytes) = rasteroid::image_extended::image_budget();
+        eprintln!("|max pixels: {max_pixels}, max bytes: {max_bytes}|");
     }
-    rasteroid::inline_an_image(
-        &img,
-        out,
-        if opts.inline_options.center {
-            Some(center)
-        } else {
-            None
-        },
-        None,
-        &opts.inline_encoder,
-    )?;
+
+    let offset = opts.inline_options.center.then_some(center);
+    if opts.inline_options.vcenter {
+        let (_, rows) = rasteroid::image_cells(&img, &opts.inline_encoder)?;
+        let top = term_misc::vcenter_image(rows);
+        out.write_all("\n".repeat(top as usize).as_bytes())?;
+    }
+
+    // big images take a while to reach a remote terminal, kitty can show how far along they are
+    if matches!(opts.inline_encoder, InlineEncoder::Kitty)
+        && img.len() > TRANSFER_PROGRESS_MIN_BYTES
+        && !opts.silent
+        && opts.is_tty()
+    {
+        let pb = ProgressBar::new(0);
+        pb.set_style(
+            ProgressStyle::default_bar()
+                .template(
+                    "{spinner:.green} Sending image [{bar:40.blue/white}] {bytes}/{total_bytes}",
+                )?
+                .progress_chars("█▓▒░"),
+        );
+        let result = rasteroid::kitty_encoder::encode_image_with_progress(
+            &img,
+            out,
+            offset,
+            None,
+            &mut |sent, total| {
+                pb.set_length(total as u64);
+                pb.set_position(sent as u64);
+                if sent == total {
+                    pb.finish_and_clear();
+                }
+            },
+        );
+        pb.finish_and_clear();
+        result?;
+        return Ok(());
+    }
+
+    // sixel and ascii encode slowly, so they're encoded into a buffer behind a spinner
+    let spinner = match opts.inline_encoder {
+        InlineEncoder::Sixel
+        | InlineEncoder::Ascii
+        | InlineEncoder::HalfBlock
+        | InlineEncoder::Braille
+            if !opts.silent && opts.is_tty() =>
+        {
+            let pb = ProgressBar::new_spinner();
+            pb.set_style(
+                ProgressStyle::default_spinner()
+                    .template("{spinner:.green} Encoding image...")?
+                    .tick_chars("⠁⠂⠄⡀⢀⠠⠐⠈ "),
+            );
+            pb.enable_steady_tick(Duration::from_millis(100));
+            Some(pb)
+        }
+        _ => None,
+    };
+    let mut buf = Vec::new();
+    let result = rasteroid::inline_an_image(&img, &mut buf, offset, None, &opts.inline_encoder);
+    if let Some(pb) = spinner {
+        pb.finish_and_clear();
+    }
+    result?;
+    out.write_all(&buf)?;
 
     Ok(())
 }
 
+/// prints the counts and properties a document has, in the style of `term_misc::report_size`,
+/// or as a json object per line with `--format json`
+fn report_metadata(path: &Path, meta: &markdownify::metadata::DocMeta, json: bool) {
+    if json {
+        let report = serde_json::json!({
+            "path": path,
+            "metadata": {
+                "pages": meta.pages,
+                "slides": meta.slides,
+                "sheets": meta.sheets,
+                "paragraphs": meta.paragraphs,
+                "title": meta.title,
+                "author": meta.author,
+                "created": meta.created,
+            },
+        });
+        eprintln!("{report}");
+        return;
+    }
+    let counts = [
+        ("pages", meta.pages),
+        ("slides", meta.slides),
+        ("sheets", meta.sheets),
+        ("paragraphs", meta.paragraphs),
+    ];
+    let properties = [
+        ("title", &meta.title),
+        ("author", &meta.author),
+        ("created", &meta.created),
+    ];
+    let parts: Vec<String> = counts
+        .iter()
+        .filter_map(|(name, v)| v.map(|v| format!("{name}: {v}")))
+        .chain(
+            properties
+                .iter()
+                .filter_map(|(name, v)| v.as_ref().map(|v| format!("{name}: {v}"))),
+        )
+        .collect();
+    eprintln!("|{}|", parts.join(", "));
+}
+
 fn apply_pan_zoom_once(img: DynamicImage, opts: &McatConfig) -> DynamicImage {
     let zoom = opts.inline_options.zoom.unwrap_or(1);
     let x = opts.inline_options.x.unwrap_or_default();
@@ -356,8 +585,8 @@ fn apply_pan_zoom_once(img: DynamicImage, opts: &McatConfig) -> DynamicImage {
     }
 
     let tinfo = term_misc::get_wininfo();
-    let container_width = tinfo.spx_width as u32;
-    let container_height = tinfo.spx_height as u32;
+    let container_width = tinfo.spx_width() as u32;
+    let container_height = tinfo.spx_height() as u32;
     let image_width = img.width();
     let image_height = img.height();
 
@@ -378,13 +607,13 @@ fn interact_with_image(
 
     let mut img = &images[0];
     let tinfo = term_misc::get_wininfo();
-    let container_width = tinfo.spx_width as u32;
-    let container_height = tinfo.spx_height as u32;
+    let container_width = tinfo.spx_width() as u32;
+    let container_height = tinfo.spx_height() as u32;
     let image_width = img.width();
     let image_height = img.height();
 
     let resize_for_ascii = match opts.inline_encoder {
-        rasteroid::InlineEncoder::Ascii => true,
+        rasteroid::InlineEncoder::Ascii | rasteroid::InlineEncoder::HalfBlock => true,
         _ => false,
     };
 
@@ -395,7 +624,7 @@ fn interact_with_image(
     let height = (tinfo.sc_height - 3).min(height_cells as u16);
     let should_disable_raw_mode = match opts.inline_encoder {
         InlineEncoder::Kitty => tinfo.is_tmux,
-        InlineEncoder::Ascii => true,
+        InlineEncoder::Ascii | InlineEncoder::HalfBlock | InlineEncoder::Braille => true,
         InlineEncoder::Iterm | InlineEncoder::Sixel => false,
     };
     let mut current_index = 0;
@@ -417,11 +646,12 @@ fn interact_with_image(
             }
             let new_img = vp.apply_to_image(&img);
             let (img, center, _, _) = new_img
-                .resize_plus(
+                .resize_plus_max(
                     opts.inline_options.width.as_deref(),
                     Some(&format!("{height}c")),
                     resize_for_ascii,
                     false,
+                    opts.image_px_cap(),
                 )
                 .ok()?;
             if should_disable_raw_mode {
@@ -465,10 +695,43 @@ fn interact_with_image(
 pub fn is_video(ext: &str) -> bool {
     matches!(
         ext,
-        "mp4" | "mov" | "avi" | "mkv" | "webm" | "wmv" | "flv" | "m4v" | "ts" | "gif"
+        "mp4"
+            | "mov"
+            | "avi"
+            | "mkv"
+            | "webm"
+            | "wmv"
+            | "flv"
+            | "m4v"
+            | "ts"
+            | "gif"
+            | "mpg"
+            | "mpeg"
+            | "ogv"
+            | "3gp"
+            | "3g2"
+            | "mts"
+            | "m2ts"
+            | "vob"
+            | "f4v"
+            | "asf"
     )
 }
 
+pub fn is_image(ext: &str) -> bool {
+    matches!(ext, "svg" | "heic" | "heif" | "avif" | "jxl" | "webp")
+        || converter::is_raw(ext)
+        || ImageFormat::from_extension(ext).is_some()
+}
+
+/// whether a less argument already passes colors through (`-R`, `-r`, or combined like `-FRX`)
+fn is_raw_flag(arg: &str) -> bool {
+    match arg.strip_prefix("--") {
+        Some(long) => long.eq_ignore_ascii_case("raw-control-chars"),
+        None => arg.starts_with('-') && arg.contains(['r', 'R']),
+    }
+}
+
 pub struct Pager {
     command: String,
     args: Vec<String>,
@@ -481,7 +744,12 @@ impl Pager {
         return Some((cmd.clone(), args.to_vec()));
     }
     pub fn new(def_command: &str) -> Option<Self> {
-        let (command, args) = Pager::command_and_args_from_string(def_command)?;
+        let (command, mut args) = Pager::command_and_args_from_string(def_command)?;
+        // a bare less (like a plain `PAGER=less`) shows the escapes instead of the colors
+        let is_less = Path::new(&command).file_stem().is_some_and(|s| s == "less");
+        if is_less && !args.iter().any(|a| is_raw_flag(a)) {
+            args.push("-R".into());
+        }
         if which::which(&command).is_ok() {
             return Some(Self { command, args });
         }
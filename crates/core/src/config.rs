@@ -1,11 +1,30 @@
-use std::{collections::HashMap, env};
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    env,
+    io::stdout,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
 
 use clap::ArgMatches;
-use rasteroid::{InlineEncoder, term_misc};
+use crossterm::tty::IsTty;
+use image::ImageFormat;
+use markdownify::ConvertOptions;
+use rasteroid::{
+    InlineEncoder,
+    image_extended::{DEFAULT_MAX_IMAGE_BYTES, DEFAULT_MAX_IMAGE_PIXELS, Flip},
+    term_misc,
+};
+use regex::Regex;
+
+use crate::markdown_viewer::themes::{CustomTheme, THEMES};
 
 #[derive(Debug, Clone)]
 pub struct InlineOptions {
     pub center: bool,
+    /// centered vertically too, when the image is shorter than the terminal
+    pub vcenter: bool,
     pub width: Option<String>,
     pub height: Option<String>,
     pub spx: String,
@@ -14,6 +33,9 @@ pub struct InlineOptions {
     pub zoom: Option<usize>,
     pub x: Option<i32>,
     pub y: Option<i32>,
+    pub crop: Option<[String; 4]>,
+    pub rotate: Option<u32>,
+    pub flip: Option<Flip>,
     pub inline: bool,
 }
 
@@ -21,6 +43,7 @@ impl Default for InlineOptions {
     fn default() -> Self {
         InlineOptions {
             center: true,
+            vcenter: false,
             width: Some("80%".into()),
             height: Some("80%".into()),
             spx: "1920x1080".into(),
@@ -29,6 +52,9 @@ impl Default for InlineOptions {
             zoom: None,
             x: None,
             y: None,
+            crop: None,
+            rotate: None,
+            flip: None,
             inline: false,
         }
     }
@@ -36,17 +62,22 @@ impl Default for InlineOptions {
 
 impl InlineOptions {
     pub fn extend_from_string(&mut self, s: &str) -> &mut Self {
-        let map: HashMap<_, _> = s
-            .split(',')
-            .filter_map(|pair| {
-                let mut split = pair.splitn(2, '=');
-                let key = split.next()?.trim();
-                let value = split.next()?.trim();
-                Some((key, value))
-            })
-            .collect();
+        // parts without a key belong to the value before them, e.g. crop=0,0,50%,50%
+        let mut pairs: Vec<(&str, String)> = Vec::new();
+        for part in s.split(',') {
+            match part.split_once('=') {
+                Some((key, value)) => pairs.push((key.trim(), value.trim().to_string())),
+                None => {
+                    if let Some((_, value)) = pairs.last_mut() {
+                        value.push(',');
+                        value.push_str(part.trim());
+                    }
+                }
+            }
+        }
+        let map: HashMap<_, _> = pairs.into_iter().collect();
 
-        let get = |key: &str| map.get(key).copied();
+        let get = |key: &str| map.get(key).map(|v| v.as_str());
         let get_size = |key: &str, default: &Option<String>| match map.get(key) {
             Some(v) => {
                 if v.eq_ignore_ascii_case("none") {
@@ -70,9 +101,21 @@ impl InlineOptions {
         self.zoom = get("zoom").and_then(|v| v.parse().ok()).or(self.zoom);
         self.x = get("x").and_then(|v| v.parse().ok()).or(self.x);
         self.y = get("y").and_then(|v| v.parse().ok()).or(self.y);
+        if let Some(crop) = get("crop") {
+            let parts: Vec<String> = crop.split(',').map(|p| p.trim().to_string()).collect();
+            self.crop = parts.try_into().ok().or(self.crop.take());
+        }
+        self.rotate = get("rotate")
+            .and_then(|v| v.parse().ok())
+            .filter(|r| matches!(r, 0 | 90 | 180 | 270))
+            .or(self.rotate);
+        self.flip = get("flip").and_then(Flip::parse).or(self.flip);
         self.center = get("center")
             .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
             .unwrap_or(self.center);
+        self.vcenter = get("vcenter")
+            .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+            .unwrap_or(self.vcenter);
         self.inline = get("inline")
             .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
             .unwrap_or(self.inline);
@@ -88,6 +131,11 @@ pub struct LsixOptions {
     pub max_width: String,
     pub height: String,
     pub max_items_per_row: usize,
+    pub columns: usize,
+    pub sort: SortBy,
+    /// flips the `sort` order, directories stay first
+    pub reverse: bool,
+    pub dirs_first: bool,
 }
 
 impl Default for LsixOptions {
@@ -99,6 +147,10 @@ impl Default for LsixOptions {
             max_width: "16c".into(),
             height: "2c".into(),
             max_items_per_row: 20,
+            columns: 6,
+            sort: SortBy::Natural,
+            reverse: false,
+            dirs_first: true,
         }
     }
 }
@@ -135,10 +187,147 @@ impl LsixOptions {
         self.max_items_per_row = get("items_per_row")
             .and_then(|v| v.parse().ok())
             .unwrap_or(self.max_items_per_row);
+        self.columns = get("columns")
+            .and_then(|v| v.parse().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(self.columns);
+        self.sort = get("sort").and_then(parse_sort).unwrap_or(self.sort);
+        self.reverse = get("reverse")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(self.reverse);
+        self.dirs_first = get("dirs_first")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(self.dirs_first);
         self
     }
 }
 
+/// how directory listings are ordered
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SortBy {
+    /// character by character, `img10` comes before `img2`
+    Name,
+    /// runs of digits are compared by their value, `img2` comes before `img10`
+    Natural,
+    /// newest first
+    Mtime,
+    /// biggest first
+    Size,
+}
+
+impl SortBy {
+    /// compares two file names, case insensitive.
+    /// names have no time or size, those compare like `Natural`
+    pub fn cmp_names(self, a: &str, b: &str) -> Ordering {
+        let (la, lb) = (a.to_lowercase(), b.to_lowercase());
+        let ord = match self {
+            SortBy::Name => la.cmp(&lb),
+            SortBy::Natural | SortBy::Mtime | SortBy::Size => natural_cmp(&la, &lb),
+        };
+        ord.then_with(|| a.cmp(b))
+    }
+
+    /// compares two paths component by component, so `a/b` stays next to `a`
+    pub fn cmp_paths(self, a: &Path, b: &Path) -> Ordering {
+        let names = |p: &Path| {
+            p.components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+        };
+        let (a, b) = (names(a), names(b));
+        a.iter()
+            .zip(&b)
+            .map(|(a, b)| self.cmp_names(a, b))
+            .find(|ord| ord.is_ne())
+            .unwrap_or_else(|| a.len().cmp(&b.len()))
+    }
+}
+
+/// compares strings with the digit runs in them as numbers, leading zeros aside
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a.chars().peekable(), b.chars().peekable());
+    loop {
+        match (a.peek().copied(), b.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let digits = |it: &mut std::iter::Peekable<std::str::Chars>| {
+                    let mut run = String::new();
+                    while let Some(c) = it.next_if(|c| c.is_ascii_digit()) {
+                        run.push(c);
+                    }
+                    run
+                };
+                let (x, y) = (digits(&mut a), digits(&mut b));
+                let (tx, ty) = (x.trim_start_matches('0'), y.trim_start_matches('0'));
+                let ord = tx.len().cmp(&ty.len()).then_with(|| tx.cmp(ty));
+                if ord.is_ne() {
+                    return ord;
+                }
+            }
+            (Some(x), Some(y)) => {
+                if x != y {
+                    return x.cmp(&y);
+                }
+                a.next();
+                b.next();
+            }
+        }
+    }
+}
+
+/// the part of a video that plays, in seconds, and how
+#[derive(Debug, Clone)]
+pub struct VideoOptions {
+    pub start: Option<f32>,
+    pub end: Option<f32>,
+    /// 2.0 plays twice as fast
+    pub speed: f32,
+    /// repeats until interrupted, `--no-loop` plays once
+    pub looped: bool,
+}
+
+impl Default for VideoOptions {
+    fn default() -> Self {
+        VideoOptions {
+            start: None,
+            end: None,
+            speed: 1.0,
+            looped: true,
+        }
+    }
+}
+
+impl VideoOptions {
+    pub fn is_trimmed(&self) -> bool {
+        self.start.is_some() || self.end.is_some()
+    }
+
+    /// ffmpeg input args for the trim, the seek goes before the input so it doesn't decode what it skips
+    pub fn ffmpeg_args(&self) -> Result<Vec<String>, String> {
+        let start = self.start.unwrap_or(0.0);
+        let mut args = Vec::new();
+        if start > 0.0 {
+            args.extend(["-ss".to_owned(), start.to_string()]);
+        }
+        if let Some(end) = self.end {
+            if end <= start {
+                return Err(format!(
+                    "the video ends at {end}s, before it starts at {start}s"
+                ));
+            }
+            args.extend(["-t".to_owned(), (end - start).to_string()]);
+        }
+        Ok(args)
+    }
+
+    /// if a frame at `timestamp` is inside of the trim
+    pub fn contains(&self, timestamp: f32) -> bool {
+        timestamp >= self.start.unwrap_or(0.0) && self.end.is_none_or(|end| timestamp < end)
+    }
+}
+
 #[derive(Clone)]
 pub struct McatConfig {
     pub input: Vec<String>,
@@ -147,19 +336,69 @@ pub struct McatConfig {
     pub inline_encoder: InlineEncoder,
     pub ls_options: LsixOptions,
     pub inline_options: InlineOptions,
+    pub video_options: VideoOptions,
     pub is_tmux: bool,
     pub silent: bool,
     pub hidden: bool,
+    pub recursive: bool,
+    pub no_preview: bool,
+    pub contact_sheet: bool,
     pub report: bool,
+    /// `--format json`, reports are printed as json
+    pub report_json: bool,
     pub no_linenumbers: bool,
+    /// code blocks longer than this have line numbers
+    pub linenumbers_min: usize,
+    pub no_emoji: bool,
+    pub collapse_details: bool,
+    pub show_front_matter: bool,
+    /// a table of contents down to this heading level
+    pub toc: Option<u8>,
+    /// the word count footer, `Auto` only shows it on colored output
+    pub stats: AlwaysOrNever,
+    /// icons from a nerd font, plain unicode without
+    pub nerd_fonts: bool,
+    pub as_code: bool,
+    /// the input is a unified diff, added and removed lines are colored
+    pub diff: bool,
+    /// print this code block (1-based) as is, instead of the document
+    pub extract_code: Option<usize>,
+    pub copy: bool,
+    pub out_file: Option<PathBuf>,
+    pub convert_to: Option<ImageFormat>,
+    pub quality: u8,
     pub md_image_render: MdImageRender,
+    pub table_mode: TableMode,
+    pub tab_width: usize,
     pub horizontal_image_stacking: bool,
+    /// the images are frames of an animation, `fps` per second
+    pub as_frames: bool,
+    pub fps: f32,
     pub style_html: bool,
     pub theme: String,
+    /// highlighting of code, the main theme when unset
+    pub code_theme: Option<String>,
+    /// more `.sublime-syntax` files to highlight code with
+    pub syntax_dir: Option<PathBuf>,
     pub fn_and_leave: Option<FnAndLeave>,
-    pub pager: String,
+    pub pager: Option<String>,
     pub color: AlwaysOrNever,
     pub paging: AlwaysOrNever,
+    pub max_image_px: Option<(u32, u32)>,
+    pub max_image_pixels: u64,
+    pub max_image_bytes: u64,
+    pub no_auto_orient: bool,
+    pub pages: Option<RangeInclusive<usize>>,
+    pub lines: Option<RangeInclusive<usize>>,
+    pub grep: Option<Regex>,
+    pub grep_context: usize,
+    pub sheet: Option<String>,
+    pub csv_delimiter: Option<u8>,
+    pub headers: Vec<(String, String)>,
+    pub cookie: Option<String>,
+    pub notes: bool,
+    pub ocr: bool,
+    pub keep_headers: bool,
     encoder_force: String,
 }
 
@@ -173,9 +412,10 @@ pub enum AlwaysOrNever {
 impl AlwaysOrNever {
     pub fn from_string(s: &str) -> AlwaysOrNever {
         match s.to_lowercase().as_ref() {
-            "always" => return AlwaysOrNever::Always,
-            "never" => return AlwaysOrNever::Never,
-            _ => return AlwaysOrNever::Always,
+            "always" => AlwaysOrNever::Always,
+            "never" => AlwaysOrNever::Never,
+            "auto" => AlwaysOrNever::Auto,
+            _ => AlwaysOrNever::Always,
         }
     }
     pub fn should_use(&self, other: bool) -> bool {
@@ -193,8 +433,15 @@ pub enum FnAndLeave {
     DeleteImages,
     FetchChromium,
     FetchFfmpeg,
+    FetchYtdlp,
     FetchClean,
-    Report,
+    Report {
+        json: bool,
+    },
+    /// a markdown file to render instead of the built-in sample
+    ListThemes {
+        sample: Option<PathBuf>,
+    },
 }
 
 #[derive(Clone, Copy, PartialEq, Debug)]
@@ -205,6 +452,17 @@ pub enum MdImageRender {
     Auto,
 }
 
+/// what to do with markdown tables wider than the terminal
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TableMode {
+    /// wrapped with the rest of the text
+    Wrap,
+    /// every row is cut at the terminal edge
+    Truncate,
+    /// the widest columns are narrowed and their cells wrapped inside them
+    Shrink,
+}
+
 impl Default for McatConfig {
     fn default() -> Self {
         McatConfig {
@@ -215,24 +473,310 @@ impl Default for McatConfig {
             is_tmux: false,
             ls_options: LsixOptions::default(),
             inline_options: InlineOptions::default(),
+            video_options: VideoOptions::default(),
             silent: false,
             hidden: false,
+            recursive: false,
+            no_preview: false,
+            contact_sheet: false,
             report: false,
+            report_json: false,
             no_linenumbers: false,
+            linenumbers_min: 10,
+            no_emoji: false,
+            collapse_details: false,
+            show_front_matter: false,
+            stats: AlwaysOrNever::Never,
+            toc: None,
+            nerd_fonts: true,
+            as_code: false,
+            diff: false,
+            extract_code: None,
+            copy: false,
+            out_file: None,
+            convert_to: None,
+            quality: 90,
             md_image_render: MdImageRender::Auto,
+            table_mode: TableMode::Wrap,
+            tab_width: 4,
             horizontal_image_stacking: false,
+            as_frames: false,
+            fps: 10.0,
             style_html: false,
             theme: "dark".into(),
+            code_theme: None,
+            syntax_dir: None,
             fn_and_leave: None,
             encoder_force: String::new(),
-            pager: "less -r".into(),
+            pager: None,
             color: AlwaysOrNever::Auto,
             paging: AlwaysOrNever::Auto,
+            max_image_px: None,
+            max_image_pixels: DEFAULT_MAX_IMAGE_PIXELS,
+            max_image_bytes: DEFAULT_MAX_IMAGE_BYTES,
+            no_auto_orient: false,
+            pages: None,
+            lines: None,
+            grep: None,
+            grep_context: 2,
+            sheet: None,
+            csv_delimiter: None,
+            headers: Vec::new(),
+            cookie: None,
+            notes: false,
+            ocr: false,
+            keep_headers: false,
+        }
+    }
+}
+
+/// sixel encoding gets slow on big rasters, so it's capped unless asked otherwise
+const SIXEL_MAX_IMAGE_PX: (u32, u32) = (2000, 2000);
+
+/// parses `WxH` (or a single `N` for both) into pixels
+pub fn parse_max_image_px(s: &str) -> Result<(u32, u32), String> {
+    let err = || format!("invalid size '{s}', expected WxH (e.g. 1920x1080)");
+    let (w, h) = match s.split_once(['x', 'X']) {
+        Some((w, h)) => (w, h),
+        None => (s, s),
+    };
+    let w = w.trim().parse::<u32>().map_err(|_| err())?;
+    let h = h.trim().parse::<u32>().map_err(|_| err())?;
+    if w == 0 || h == 0 {
+        return Err(err());
+    }
+    Ok((w, h))
+}
+
+/// parses a count like `12000000`, `500k` or `8M` (powers of 1000), 0 means no limit
+pub fn parse_count(s: &str) -> Result<u64, String> {
+    let err = || format!("invalid number '{s}', expected N, Nk or NM (e.g. 8M)");
+    let lower = s.trim().to_lowercase();
+    let lower = lower.strip_suffix('b').unwrap_or(&lower);
+    let (num, mul) = match lower.char_indices().last() {
+        Some((i, 'k')) => (&lower[..i], 1_000.0),
+        Some((i, 'm')) => (&lower[..i], 1_000_000.0),
+        Some((i, 'g')) => (&lower[..i], 1_000_000_000.0),
+        _ => (lower, 1.0),
+    };
+    let num = num.trim().parse::<f64>().map_err(|_| err())?;
+    if num < 0.0 || !num.is_finite() {
+        return Err(err());
+    }
+    Ok((num * mul) as u64)
+}
+
+/// parses a 1-based page range like `3-7`, or a single page like `4`
+pub fn parse_pages(s: &str) -> Result<RangeInclusive<usize>, String> {
+    let err = || format!("invalid page range '{s}', expected N or N-M (e.g. 3-7)");
+    let (start, end) = match s.split_once('-') {
+        Some((start, end)) => (start, end),
+        None => (s, s),
+    };
+    let start = start.trim().parse::<usize>().map_err(|_| err())?;
+    let end = end.trim().parse::<usize>().map_err(|_| err())?;
+    if start == 0 || start > end {
+        return Err(err());
+    }
+    Ok(start..=end)
+}
+
+fn config_file_path() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|h| h.join(".config")))?;
+    Some(base.join("mcat").join("config.toml"))
+}
+
+fn parse_md_image(s: &str) -> Option<MdImageRender> {
+    match s.to_lowercase().as_str() {
+        "all" => Some(MdImageRender::All),
+        "small" => Some(MdImageRender::Small),
+        "none" => Some(MdImageRender::None),
+        "auto" => Some(MdImageRender::Auto),
+        _ => None,
+    }
+}
+
+fn parse_sort(s: &str) -> Option<SortBy> {
+    match s.to_lowercase().as_str() {
+        "name" => Some(SortBy::Name),
+        "natural" => Some(SortBy::Natural),
+        "mtime" => Some(SortBy::Mtime),
+        "size" => Some(SortBy::Size),
+        _ => None,
+    }
+}
+
+fn parse_table_mode(s: &str) -> Option<TableMode> {
+    match s.to_lowercase().as_str() {
+        "wrap" => Some(TableMode::Wrap),
+        "truncate" => Some(TableMode::Truncate),
+        "shrink" => Some(TableMode::Shrink),
+        _ => None,
+    }
+}
+
+/// parses a 1-based inclusive line range like `10:40`, either side can be left open (`10:`, `:40`)
+pub fn parse_lines(s: &str) -> Result<RangeInclusive<usize>, String> {
+    let err = || format!("invalid line range '{s}', expected N, N:M, N: or :M (e.g. 10:40)");
+    let (start, end) = match s.split_once(':') {
+        Some((start, end)) => (start.trim(), end.trim()),
+        None => (s.trim(), s.trim()),
+    };
+    let start = match start {
+        "" => 1,
+        v => v.parse::<usize>().map_err(|_| err())?,
+    };
+    let end = match end {
+        "" => usize::MAX,
+        v => v.parse::<usize>().map_err(|_| err())?,
+    };
+    if start == 0 || start > end {
+        return Err(err());
+    }
+    Ok(start..=end)
+}
+
+/// the formats `--convert-to` can encode into
+pub fn parse_image_format(s: &str) -> Result<ImageFormat, String> {
+    match s.to_lowercase().as_str() {
+        "png" => Ok(ImageFormat::Png),
+        "jpg" | "jpeg" => Ok(ImageFormat::Jpeg),
+        "webp" => Ok(ImageFormat::WebP),
+        "bmp" => Ok(ImageFormat::Bmp),
+        "gif" => Ok(ImageFormat::Gif),
+        _ => Err(format!(
+            "can't convert images to '{s}', expected png, jpg, webp, bmp or gif"
+        )),
+    }
+}
+
+pub fn parse_fps(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(fps) if fps.is_finite() && fps > 0.0 => Ok(fps),
+        _ => Err(format!(
+            "invalid frame rate '{s}', expected a number above 0"
+        )),
+    }
+}
+
+pub fn parse_speed(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(speed) if speed.is_finite() && speed > 0.0 => Ok(speed),
+        _ => Err(format!(
+            "invalid speed '{s}', expected a number above 0 (e.g. 0.5, 2)"
+        )),
+    }
+}
+
+/// seconds (`90`, `1.5`), or `mm:ss` / `hh:mm:ss` with optional fractions (`1:02:03.5`)
+pub fn parse_timestamp(s: &str) -> Result<f32, String> {
+    let err = || format!("invalid time '{s}', expected seconds or hh:mm:ss (e.g. 90, 1:30)");
+    let parts: Vec<&str> = s.trim().split(':').collect();
+    if parts.len() > 3 {
+        return Err(err());
+    }
+    let mut secs = 0.0;
+    for (i, part) in parts.iter().enumerate() {
+        let v = part.parse::<f32>().map_err(|_| err())?;
+        // only the seconds have a fraction, the minutes and seconds are under 60
+        let last = i == parts.len() - 1;
+        if !v.is_finite() || v < 0.0 || (!last && v.fract() != 0.0) || (i > 0 && v >= 60.0) {
+            return Err(err());
         }
+        secs = secs * 60.0 + v;
+    }
+    Ok(secs)
+}
+
+pub fn parse_grep(s: &str) -> Result<Regex, String> {
+    Regex::new(s).map_err(|e| format!("invalid pattern: {e}"))
+}
+
+/// parses a `Name: Value` http header
+pub fn parse_header(s: &str) -> Result<(String, String), String> {
+    match s.split_once(':') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.trim().to_string()))
+        }
+        _ => Err(format!("invalid header '{s}', expected 'Name: Value'")),
+    }
+}
+
+/// parses a single byte delimiter, `\t` and `tab` mean a tab
+pub fn parse_csv_delimiter(s: &str) -> Result<u8, String> {
+    match s {
+        "\\t" | "tab" => Ok(b'\t'),
+        _ if s.len() == 1 && s.is_ascii() => Ok(s.as_bytes()[0]),
+        _ => Err(format!(
+            "invalid delimiter '{s}', expected a single character"
+        )),
+    }
+}
+
+/// a built-in theme name, or a toml theme file that loads
+pub fn parse_theme(s: &str) -> Result<String, String> {
+    if s.ends_with(".toml") {
+        return CustomTheme::from_toml_path(Path::new(s))
+            .map(|_| s.to_owned())
+            .map_err(|e| format!("failed loading theme '{s}': {e}"));
+    }
+    match THEMES.contains(&s) {
+        true => Ok(s.to_owned()),
+        false => Err(format!(
+            "unknown theme '{s}', expected a .toml file or one of: {}",
+            THEMES.join(", ")
+        )),
     }
 }
 
 impl McatConfig {
+    /// if the output ends up in a terminal, and not in a file or the clipboard
+    pub fn is_tty(&self) -> bool {
+        !self.copy && self.out_file.is_none() && stdout().is_tty()
+    }
+
+    /// the theme code is highlighted with
+    pub fn code_theme(&self) -> &str {
+        self.code_theme.as_deref().unwrap_or(&self.theme)
+    }
+
+    /// the pager command: `--pager`, `MCAT_PAGER` or the config file, then `$PAGER`, then less.
+    /// `builtin` is the text viewer of mcat itself
+    pub fn pager_command(&self) -> String {
+        self.pager
+            .clone()
+            .or_else(|| env::var("PAGER").ok().filter(|p| !p.trim().is_empty()))
+            .unwrap_or_else(|| "less -R".into())
+    }
+
+    /// the pixel cap for encoded images, if any
+    pub fn image_px_cap(&self) -> Option<(u32, u32)> {
+        match (self.max_image_px, &self.inline_encoder) {
+            (Some(max_px), _) => Some(max_px),
+            (None, InlineEncoder::Sixel) => Some(SIXEL_MAX_IMAGE_PX),
+            (None, _) => None,
+        }
+    }
+
+    /// the options documents are converted into markdown with
+    pub fn convert_options<'a>(&self, path: &'a Path) -> ConvertOptions<'a> {
+        let screen_size = term_misc::get_wininfo();
+        let mut opts = ConvertOptions::new(path)
+            .with_screen_size((screen_size.sc_width, screen_size.sc_height));
+        opts.pages = self.pages.clone();
+        opts.csv_delimiter = self.csv_delimiter;
+        opts.include_notes = self.notes;
+        opts.ocr = self.ocr;
+        opts.keep_headers = self.keep_headers;
+        // picked by name, a number falls back to the position (1-based)
+        opts.sheet = self.sheet.clone();
+        opts.sheet_index = self.sheet.as_deref().and_then(|s| s.parse::<usize>().ok());
+        opts
+    }
+
     pub fn extend_from_args(&mut self, opts: &ArgMatches) -> &mut Self {
         self.input = opts
             .get_many::<String>("input")
@@ -246,17 +790,22 @@ impl McatConfig {
         let mut iterm = opts.get_flag("iterm");
         let mut sixel = opts.get_flag("sixel");
         let mut ascii = opts.get_flag("ascii");
+        let mut halfblock = opts.get_flag("halfblock");
+        let mut braille = opts.get_flag("braille");
         match self.encoder_force.as_ref() {
             "kitty" => kitty = true,
             "iterm" => iterm = true,
             "sixel" => sixel = true,
             "ascii" => ascii = true,
+            "halfblock" => halfblock = true,
+            "braille" => braille = true,
             _ => {}
         }
         let mut env = term_misc::EnvIdentifiers::new();
         self.is_tmux = env.is_tmux();
-        self.inline_encoder =
-            rasteroid::InlineEncoder::auto_detect(kitty, iterm, sixel, ascii, &mut env);
+        self.inline_encoder = rasteroid::InlineEncoder::auto_detect(
+            kitty, iterm, sixel, ascii, halfblock, braille, &mut env,
+        );
 
         // fn and leave
         if let Some(shell) = opts.get_one::<String>("generate-completions") {
@@ -275,16 +824,32 @@ impl McatConfig {
             self.fn_and_leave = Some(FnAndLeave::FetchFfmpeg);
             return self;
         }
+        if opts.get_flag("fetch-ytdlp") {
+            self.fn_and_leave = Some(FnAndLeave::FetchYtdlp);
+            return self;
+        }
         if opts.get_flag("fetch-clean") {
             self.fn_and_leave = Some(FnAndLeave::FetchClean);
             return self;
         }
+        // before the report, which shows them
+        if let Some(max_pixels) = opts.get_one::<u64>("max-image-pixels") {
+            self.max_image_pixels = *max_pixels;
+        }
+        if let Some(max_bytes) = opts.get_one::<u64>("max-image-bytes") {
+            self.max_image_bytes = *max_bytes;
+        }
         self.report = opts
             .get_one::<bool>("report")
             .copied()
             .unwrap_or(self.report);
+        self.report_json = opts
+            .get_one::<String>("format")
+            .is_some_and(|f| f == "json");
         if self.report && self.input.is_empty() {
-            self.fn_and_leave = Some(FnAndLeave::Report);
+            self.fn_and_leave = Some(FnAndLeave::Report {
+                json: self.report_json,
+            });
             return self;
         }
 
@@ -292,6 +857,15 @@ impl McatConfig {
         if let Some(ls_options) = opts.get_one::<String>("ls-options") {
             self.ls_options.extend_from_string(&ls_options);
         }
+        if let Some(sort) = opts.get_one::<String>("sort") {
+            self.ls_options.sort = parse_sort(sort).unwrap_or(self.ls_options.sort);
+        }
+        if opts.get_flag("reverse") {
+            self.ls_options.reverse = true;
+        }
+        if opts.get_flag("no-dirs-first") {
+            self.ls_options.dirs_first = false;
+        }
         if let Some(inline_options) = opts.get_one::<String>("inline-options") {
             self.inline_options.extend_from_string(&inline_options);
         }
@@ -301,9 +875,48 @@ impl McatConfig {
         if opts.get_flag("hidden") {
             self.hidden = true;
         }
+        if opts.get_flag("recursive") {
+            self.recursive = true;
+        }
+        if opts.get_flag("no-preview") {
+            self.no_preview = true;
+        }
+        if opts.get_flag("contact-sheet") {
+            self.contact_sheet = true;
+        }
         if opts.get_flag("no-linenumbers") {
             self.no_linenumbers = true;
         }
+        if let Some(min) = opts.get_one::<usize>("linenumbers-min") {
+            self.linenumbers_min = *min;
+        }
+        if opts.get_flag("no-emoji") {
+            self.no_emoji = true;
+        }
+        if opts.get_flag("collapse-details") {
+            self.collapse_details = true;
+        }
+        if opts.get_flag("show-frontmatter") {
+            self.show_front_matter = true;
+        }
+        if opts.get_flag("toc") {
+            self.toc = Some(*opts.get_one::<u8>("toc-depth").unwrap_or(&6));
+        }
+        if opts.get_flag("stats") {
+            self.stats = AlwaysOrNever::Always;
+        }
+        if opts.get_flag("no-nerd-fonts") {
+            self.nerd_fonts = false;
+        }
+        if opts.get_flag("as-code") {
+            self.as_code = true;
+        }
+        if opts.get_flag("diff") {
+            self.diff = true;
+        }
+        if let Some(n) = opts.get_one::<u64>("extract-code") {
+            self.extract_code = Some(*n as usize);
+        }
         self.md_image_render = match opts.get_one::<String>("md-image") {
             Some(v) => match v.as_str() {
                 "all" => MdImageRender::All,
@@ -314,21 +927,54 @@ impl McatConfig {
             },
             None => self.md_image_render,
         };
+        if let Some(width) = opts.get_one::<usize>("tab-width") {
+            self.tab_width = *width;
+        }
+        if let Some(mode) = opts.get_one::<String>("table-mode") {
+            self.table_mode = parse_table_mode(mode).unwrap_or(self.table_mode);
+        }
         if opts.get_flag("fast") {
             self.md_image_render = MdImageRender::None
         }
         if opts.get_flag("horizontal") {
             self.horizontal_image_stacking = true;
         }
+        if opts.get_flag("as-frames") {
+            self.as_frames = true;
+        }
+        if let Some(fps) = opts.get_one::<f32>("fps") {
+            self.fps = *fps;
+        }
+        if let Some(start) = opts.get_one::<f32>("start") {
+            self.video_options.start = Some(*start);
+        }
+        if let Some(end) = opts.get_one::<f32>("end") {
+            self.video_options.end = Some(*end);
+        }
+        if let Some(duration) = opts.get_one::<f32>("duration") {
+            self.video_options.end = Some(self.video_options.start.unwrap_or(0.0) + duration);
+        }
+        if let Some(speed) = opts.get_one::<f32>("speed") {
+            self.video_options.speed = *speed;
+        }
+        if opts.get_flag("no-loop") {
+            self.video_options.looped = false;
+        }
         if opts.get_flag("style-html") {
             self.style_html = true;
         }
         if let Some(theme) = opts.get_one::<String>("theme") {
             self.theme = theme.clone();
         }
+        if let Some(theme) = opts.get_one::<String>("code-theme") {
+            self.code_theme = Some(theme.clone());
+        }
+        if let Some(dir) = opts.get_one::<PathBuf>("syntax-dir") {
+            self.syntax_dir = Some(dir.clone());
+        }
         // paging
         if let Some(pager) = opts.get_one::<String>("pager") {
-            self.pager = pager.clone();
+            self.pager = Some(pager.clone());
         }
         if let Some(paging) = opts.get_one::<String>("paging") {
             self.paging = AlwaysOrNever::from_string(paging);
@@ -339,6 +985,45 @@ impl McatConfig {
         if opts.get_flag("paging-never") {
             self.paging = AlwaysOrNever::Never
         }
+        if let Some(max_px) = opts.get_one::<(u32, u32)>("max-image-px") {
+            self.max_image_px = Some(*max_px);
+        }
+        if opts.get_flag("no-auto-orient") {
+            self.no_auto_orient = true;
+        }
+        if let Some(pages) = opts.get_one::<RangeInclusive<usize>>("pages") {
+            self.pages = Some(pages.clone());
+        }
+        if let Some(lines) = opts.get_one::<RangeInclusive<usize>>("lines") {
+            self.lines = Some(lines.clone());
+        }
+        if let Some(re) = opts.get_one::<Regex>("grep") {
+            self.grep = Some(re.clone());
+        }
+        if let Some(context) = opts.get_one::<usize>("grep-context") {
+            self.grep_context = *context;
+        }
+        if let Some(sheet) = opts.get_one::<String>("sheet") {
+            self.sheet = Some(sheet.clone());
+        }
+        if let Some(delimiter) = opts.get_one::<u8>("csv-delimiter") {
+            self.csv_delimiter = Some(*delimiter);
+        }
+        if let Some(headers) = opts.get_many::<(String, String)>("header") {
+            self.headers = headers.cloned().collect();
+        }
+        if let Some(cookie) = opts.get_one::<String>("cookie") {
+            self.cookie = Some(cookie.clone());
+        }
+        if opts.get_flag("notes") {
+            self.notes = true;
+        }
+        if opts.get_flag("ocr") {
+            self.ocr = true;
+        }
+        if opts.get_flag("keep-headers") {
+            self.keep_headers = true;
+        }
         // color
         if let Some(color) = opts.get_one::<String>("color") {
             self.color = AlwaysOrNever::from_string(color);
@@ -349,6 +1034,12 @@ impl McatConfig {
         if opts.get_flag("color-never") {
             self.color = AlwaysOrNever::Never
         }
+        if opts.get_flag("copy") {
+            self.copy = true;
+        }
+        if let Some(path) = opts.get_one::<PathBuf>("out-file") {
+            self.out_file = Some(path.clone());
+        }
 
         // output
         let inline = opts.get_flag("inline");
@@ -358,6 +1049,138 @@ impl McatConfig {
             opts.get_one::<String>("output").cloned()
         };
 
+        // converting images, explicitly or by the extension of --out-file
+        if let Some(quality) = opts.get_one::<u8>("quality") {
+            self.quality = *quality;
+        }
+        let from_out_file = self
+            .out_file
+            .as_ref()
+            .and_then(|p| p.extension())
+            .and_then(|ext| parse_image_format(&ext.to_string_lossy()).ok());
+        self.convert_to = opts.get_one::<ImageFormat>("convert-to").copied();
+        // the extension only picks the format, `-o image` is still what asks for an image
+        if self.output.as_deref() == Some("image") && self.convert_to.is_none() {
+            self.convert_to = from_out_file.filter(|format| *format != ImageFormat::Png);
+        }
+        if let Some(path) = &self.out_file
+            && from_out_file.is_some()
+            && self.output.is_none()
+            && self.convert_to.is_none()
+            && !self.silent
+        {
+            eprintln!(
+                "warning: {} looks like an image, but the output isn't one. add `-o image` to write an image",
+                path.display()
+            );
+        }
+        if self.convert_to.is_some() && self.output.is_none() {
+            self.output = Some("image".to_string());
+        }
+
+        // last, the gallery renders with the flags above (--no-nerd-fonts, --table-mode..)
+        if opts.get_flag("list-themes") {
+            let sample = opts.get_one::<PathBuf>("theme-sample").cloned();
+            self.fn_and_leave = Some(FnAndLeave::ListThemes { sample });
+        }
+
+        self
+    }
+
+    /// loads defaults from `$XDG_CONFIG_HOME/mcat/config.toml` (or `~/.config/mcat/config.toml`).
+    /// a missing file is fine, unknown keys and bad values are warned about and skipped.
+    pub fn extend_from_file(&mut self) -> &mut Self {
+        let path = match config_file_path() {
+            Some(p) if p.is_file() => p,
+            _ => return self,
+        };
+        let table = match std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|s| s.parse::<toml::Table>().map_err(|e| e.to_string()))
+        {
+            Ok(t) => t,
+            Err(e) => {
+                eprintln!("warning: ignoring {}: {}", path.display(), e);
+                return self;
+            }
+        };
+
+        let config_dir = path.parent().unwrap_or(Path::new("")).to_path_buf();
+        const ENCODERS: [&str; 6] = ["kitty", "iterm", "sixel", "ascii", "halfblock", "braille"];
+        const KEYS: [&str; 10] = [
+            "theme",
+            "code_theme",
+            "syntax_dir",
+            "pager",
+            "encoder",
+            "inline_options",
+            "ls_options",
+            "md_image",
+            "nerd_fonts",
+            "stats",
+        ];
+        for (key, value) in table {
+            if !KEYS.contains(&key.as_str()) {
+                eprintln!("warning: {}: unknown key `{key}`", path.display());
+                continue;
+            }
+            if key == "nerd_fonts" || key == "stats" {
+                match (key.as_str(), value.as_bool()) {
+                    ("nerd_fonts", Some(v)) => self.nerd_fonts = v,
+                    (_, Some(v)) => self.stats = stats_when(v),
+                    (_, None) => {
+                        eprintln!("warning: {}: `{key}` should be a bool", path.display())
+                    }
+                }
+                continue;
+            }
+            let v = match value.as_str() {
+                Some(v) => v,
+                None => {
+                    eprintln!("warning: {}: `{key}` should be a string", path.display());
+                    continue;
+                }
+            };
+            match key.as_str() {
+                "theme" | "code_theme" => {
+                    // a relative theme file is relative to the config file, not the cwd
+                    let theme = match v.ends_with(".toml") {
+                        true => config_dir.join(v).to_string_lossy().into_owned(),
+                        false => v.to_string(),
+                    };
+                    match (key.as_str(), parse_theme(&theme)) {
+                        ("theme", Ok(theme)) => self.theme = theme,
+                        (_, Ok(theme)) => self.code_theme = Some(theme),
+                        (_, Err(e)) => eprintln!("warning: {}: {e}", path.display()),
+                    }
+                }
+                "syntax_dir" => self.syntax_dir = Some(config_dir.join(v)),
+                "pager" => self.pager = Some(v.to_string()),
+                "encoder" => match ENCODERS.contains(&v.to_lowercase().as_str()) {
+                    true => self.encoder_force = v.to_lowercase(),
+                    false => eprintln!(
+                        "warning: {}: unknown encoder `{v}`, expected one of: {}",
+                        path.display(),
+                        ENCODERS.join(", ")
+                    ),
+                },
+                "inline_options" => {
+                    self.inline_options.extend_from_string(v);
+                }
+                "ls_options" => {
+                    self.ls_options.extend_from_string(v);
+                }
+                "md_image" => match parse_md_image(v) {
+                    Some(m) => self.md_image_render = m,
+                    None => eprintln!(
+                        "warning: {}: unknown md_image `{v}`, expected all, small, none or auto",
+                        path.display()
+                    ),
+                },
+                _ => {}
+            }
+        }
+
         self
     }
 
@@ -366,11 +1189,14 @@ impl McatConfig {
             self.encoder_force = v.to_lowercase();
         }
         if let Ok(v) = env::var("MCAT_PAGER") {
-            self.pager = v;
+            self.pager = Some(v);
         }
         if let Ok(v) = env::var("MCAT_THEME") {
             self.theme = v;
         }
+        if let Ok(v) = env::var("MCAT_CODE_THEME") {
+            self.code_theme = Some(v);
+        }
         if let Ok(v) = env::var("MCAT_INLINE_OPTS") {
             self.inline_options.extend_from_string(&v);
         }
@@ -383,16 +1209,42 @@ impl McatConfig {
         if let Ok(v) = env::var("MCAT_NO_LINENUMBERS") {
             self.no_linenumbers = v == "1" || v.eq_ignore_ascii_case("true");
         }
+        if let Ok(v) = env::var("MCAT_NO_NERD_FONTS") {
+            self.nerd_fonts = !(v == "1" || v.eq_ignore_ascii_case("true"));
+        }
+        if let Ok(v) = env::var("MCAT_STATS") {
+            self.stats = stats_when(v == "1" || v.eq_ignore_ascii_case("true"));
+        }
         if let Ok(v) = env::var("MCAT_MD_IMAGE") {
-            self.md_image_render = match v.to_lowercase().as_str() {
-                "all" => MdImageRender::All,
-                "small" => MdImageRender::Small,
-                "none" => MdImageRender::None,
-                "auto" => MdImageRender::Auto,
-                _ => self.md_image_render,
-            };
+            self.md_image_render = parse_md_image(&v).unwrap_or(self.md_image_render);
+        }
+        if let Ok(v) = env::var("MCAT_TABLE_MODE") {
+            self.table_mode = parse_table_mode(&v).unwrap_or(self.table_mode);
+        }
+        // https://bixense.com/clicolors and https://no-color.org, `--color` still wins
+        if env::var("CLICOLOR").is_ok_and(|v| v == "0") {
+            self.color = AlwaysOrNever::Never;
+        }
+        if env::var("CLICOLOR_FORCE").is_ok_and(|v| !v.is_empty() && v != "0") {
+            self.color = AlwaysOrNever::Always;
+        }
+        if no_color() {
+            self.color = AlwaysOrNever::Never;
         }
 
         self
     }
 }
+
+/// the footer turned on by the config or env is left out of plain output, unlike `--stats`
+fn stats_when(enabled: bool) -> AlwaysOrNever {
+    match enabled {
+        true => AlwaysOrNever::Auto,
+        false => AlwaysOrNever::Never,
+    }
+}
+
+/// if `NO_COLOR` is set to anything
+pub fn no_color() -> bool {
+    env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty())
+}
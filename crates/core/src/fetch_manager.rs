@@ -9,25 +9,77 @@ use ffmpeg_sidecar::{
     command::FfmpegCommand,
     download::{download_ffmpeg_package, ffmpeg_download_url, unpack_ffmpeg},
 };
+use sha2::{Digest, Sha256};
 use tokio::runtime::Builder;
 use zip::ZipArchive;
 
-pub fn is_chromium_installed() -> bool {
-    BrowserConfig::default().is_some()
+/// where a dependency was found, a system wide install always wins over the one mcat fetched
+pub enum Install {
+    System(PathBuf),
+    Managed(PathBuf),
 }
+
+impl Install {
+    pub fn path(&self) -> &Path {
+        match self {
+            Install::System(p) | Install::Managed(p) => p,
+        }
+    }
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Install::System(_) => "system",
+            Install::Managed(_) => "managed",
+        }
+    }
+}
+
 pub fn is_ffmpeg_installed() -> bool {
-    let cache_path = get_cache_path();
-    let mut ffmpeg_path = cache_path.join("ffmpeg").join("ffmpeg");
-    if cfg!(windows) {
-        ffmpeg_path.set_extension("exe");
+    find_ffmpeg().is_some()
+}
+
+pub fn find_ffmpeg() -> Option<Install> {
+    let name = if cfg!(windows) {
+        "ffmpeg.exe"
+    } else {
+        "ffmpeg"
+    };
+    if let Ok(path) = which::which(name) {
+        return Some(Install::System(path));
     }
-    if ffmpeg_path.exists() {
-        return true;
+
+    #[cfg(unix)]
+    let default_dirs = [
+        "/usr/local/bin",
+        "/usr/bin",
+        "/opt/homebrew/bin",
+        "/opt/local/bin",
+        "/snap/bin",
+    ];
+    #[cfg(windows)]
+    let default_dirs = [r"C:\ffmpeg\bin", r"C:\Program Files\ffmpeg\bin"];
+    for dir in default_dirs {
+        let path = Path::new(dir).join(name);
+        if path.is_file() {
+            return Some(Install::System(path));
+        }
     }
-    ffmpeg_sidecar::command::ffmpeg_is_installed()
+
+    let path = get_cache_path().join("ffmpeg").join(name);
+    path.exists().then_some(Install::Managed(path))
+}
+
+pub fn find_chromium() -> Option<Install> {
+    BrowserConfig::auto_detect_path()
 }
 
 pub fn fetch_chromium() -> Result<(), Box<dyn error::Error>> {
+    if let Some(Install::System(path)) = find_chromium() {
+        eprintln!(
+            "using the system chromium at {}, nothing to fetch",
+            path.display()
+        );
+        return Ok(());
+    }
     let cr = ChromeRevision::default();
     match cr {
         Some(cr) => cr.download(),
@@ -40,6 +92,13 @@ pub fn is_poppler_installed() -> bool {
 }
 
 pub fn fetch_ffmpeg() -> Result<(), Box<dyn error::Error>> {
+    if let Some(Install::System(path)) = find_ffmpeg() {
+        eprintln!(
+            "using the system ffmpeg at {}, nothing to fetch",
+            path.display()
+        );
+        return Ok(());
+    }
     let cache_path = get_cache_path();
     let des = cache_path.join("ffmpeg");
     if !des.exists() {
@@ -57,6 +116,118 @@ pub fn fetch_ffmpeg() -> Result<(), Box<dyn error::Error>> {
     Ok(())
 }
 
+fn ytdlp_cache_path() -> PathBuf {
+    let mut path = get_cache_path().join("yt-dlp").join("yt-dlp");
+    if cfg!(windows) {
+        path.set_extension("exe");
+    }
+    path
+}
+
+pub fn find_ytdlp() -> Option<Install> {
+    if let Ok(path) = which::which("yt-dlp") {
+        return Some(Install::System(path));
+    }
+    let path = ytdlp_cache_path();
+    path.exists().then_some(Install::Managed(path))
+}
+
+/// mermaid 9 renders synchronously, so a diagram is ready by the load event
+const MERMAID_VERSION: &str = "9.4.3";
+
+/// the mermaid.min.js diagrams are rendered with, downloaded into the cache on first use
+pub fn get_mermaid() -> Result<PathBuf, Box<dyn error::Error>> {
+    let path = get_cache_path()
+        .join("mermaid")
+        .join(format!("mermaid-{MERMAID_VERSION}.min.js"));
+    if path.is_file() {
+        return Ok(path);
+    }
+    let url = format!("https://cdn.jsdelivr.net/npm/mermaid@{MERMAID_VERSION}/dist/mermaid.min.js");
+    let rt = Builder::new_current_thread().enable_all().build()?;
+    let data = rt.block_on(async {
+        let res = reqwest::get(&url).await?;
+        if !res.status().is_success() {
+            return Err(format!("Failed to download mermaid: {}", res.status()).into());
+        }
+        Ok::<_, Box<dyn error::Error>>(res.bytes().await?)
+    })?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, &data)?;
+    Ok(path)
+}
+
+pub fn get_ytdlp() -> Option<PathBuf> {
+    find_ytdlp().map(|i| i.path().to_path_buf())
+}
+
+/// the yt-dlp release `--fetch-ytdlp` installs, its SHA2-256SUMS are checked before it's written
+const YTDLP_RELEASE: &str = "2025.10.22";
+
+pub fn fetch_ytdlp() -> Result<(), Box<dyn error::Error>> {
+    // the standalone builds, they don't need python
+    let asset = match (env::consts::OS, env::consts::ARCH) {
+        ("linux", "x86_64") => "yt-dlp_linux",
+        ("linux", "aarch64") => "yt-dlp_linux_aarch64",
+        ("macos", _) => "yt-dlp_macos",
+        ("windows", _) => "yt-dlp.exe",
+        _ => return Err("Platform isn't supported for fetching yt-dlp".into()),
+    };
+    let base = format!("https://github.com/yt-dlp/yt-dlp/releases/download/{YTDLP_RELEASE}");
+    let des = ytdlp_cache_path();
+    if let Some(parent) = des.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    eprintln!("downloading yt-dlp {YTDLP_RELEASE} into {}", des.display());
+
+    let rt = Builder::new_current_thread().enable_all().build()?;
+    let download = |url: String| async move {
+        let res = reqwest::get(&url).await?;
+        if !res.status().is_success() {
+            return Err(format!("Failed to download {url}: {}", res.status()).into());
+        }
+        Ok::<_, Box<dyn error::Error>>(res.bytes().await?)
+    };
+    let (sums, data) = rt.block_on(async {
+        let sums = download(format!("{base}/SHA2-256SUMS")).await?;
+        let data = download(format!("{base}/{asset}")).await?;
+        Ok::<_, Box<dyn error::Error>>((sums, data))
+    })?;
+
+    // lines of `<sha256>  <asset>`
+    let sums = String::from_utf8_lossy(&sums);
+    let expected = sums
+        .lines()
+        .filter_map(|line| line.split_once(char::is_whitespace))
+        .find(|(_, name)| name.trim_start().trim_start_matches('*') == asset)
+        .map(|(sum, _)| sum.to_lowercase())
+        .ok_or_else(|| format!("SHA2-256SUMS of yt-dlp {YTDLP_RELEASE} has no {asset}"))?;
+    let actual: String = Sha256::digest(&data)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    if actual != expected {
+        // don't leave an unverified yt-dlp behind, even from an earlier fetch
+        if des.exists() {
+            fs::remove_file(&des)?;
+        }
+        return Err(format!(
+            "{asset} doesn't match its checksum ({actual} != {expected}), not installing it"
+        )
+        .into());
+    }
+    fs::write(&des, &data)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&des, fs::Permissions::from_mode(0o755))?;
+    }
+    eprintln!("done!");
+    Ok(())
+}
+
 pub fn clean() -> Result<(), Box<dyn error::Error>> {
     let cache_path = get_cache_path();
     eprintln!("deleting: {}", cache_path.display());
@@ -76,18 +247,7 @@ pub fn get_cache_path() -> PathBuf {
 }
 
 pub fn get_ffmpeg() -> Option<FfmpegCommand> {
-    if ffmpeg_sidecar::command::ffmpeg_is_installed() {
-        return Some(FfmpegCommand::new());
-    }
-    let cache_path = get_cache_path();
-    let mut ffmpeg_path = cache_path.join("ffmpeg").join("ffmpeg");
-    if cfg!(windows) {
-        ffmpeg_path.set_extension("exe");
-    }
-    if ffmpeg_path.exists() {
-        return Some(FfmpegCommand::new_with_path(ffmpeg_path));
-    }
-    None
+    find_ffmpeg().map(|i| FfmpegCommand::new_with_path(i.path()))
 }
 
 pub struct BrowserConfig {
@@ -96,32 +256,32 @@ pub struct BrowserConfig {
 
 impl BrowserConfig {
     pub fn default() -> Option<Self> {
-        let path = BrowserConfig::auto_detect_path()?;
+        let path = BrowserConfig::auto_detect_path()?.path().to_path_buf();
         Some(BrowserConfig { path })
     }
 
-    fn auto_detect_path() -> Option<PathBuf> {
+    fn auto_detect_path() -> Option<Install> {
         if let Some(path) = get_by_env_var() {
-            return Some(path);
+            return Some(Install::System(path));
         }
 
         if let Some(path) = get_by_name() {
-            return Some(path);
+            return Some(Install::System(path));
         }
 
         #[cfg(windows)]
         if let Some(path) = get_by_registry() {
-            return Some(path);
+            return Some(Install::System(path));
         }
 
         if let Some(path) = get_by_path() {
-            return Some(path);
+            return Some(Install::System(path));
         }
 
         let cr = ChromeRevision::default()?;
         let p = cr.path();
         if p.exists() {
-            return Some(p);
+            return Some(Install::Managed(p));
         }
 
         None
@@ -0,0 +1,77 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static NERD_FONTS: AtomicBool = AtomicBool::new(true);
+
+/// use nerd font icons (the default) or their plain fallbacks
+pub fn set_nerd_fonts(enabled: bool) {
+    NERD_FONTS.store(enabled, Ordering::Relaxed);
+}
+
+pub fn nerd_fonts() -> bool {
+    NERD_FONTS.load(Ordering::Relaxed)
+}
+
+/// every icon mcat draws, so `--no-nerd-fonts` can swap them all
+/// for plain unicode that any terminal font has
+#[derive(Clone, Copy, Debug)]
+pub enum Glyph {
+    /// level 1-6
+    Heading(u8),
+    /// list depth, starting at 0
+    Bullet(usize),
+    TaskDone,
+    TaskPartial,
+    TaskImportant,
+    TaskTodo,
+    Link,
+    Image,
+    WikiLink,
+    AlertNote,
+    AlertTip,
+    AlertImportant,
+    AlertWarning,
+    AlertCaution,
+    Folder,
+    /// the icon of a file type or code language, they all fall back to the same dot
+    FileType(&'static str),
+}
+
+impl Glyph {
+    /// the nerd font icon, and the fallback
+    fn variants(self) -> (&'static str, &'static str) {
+        match self {
+            Glyph::Heading(1) => ("󰎤", "#"),
+            Glyph::Heading(2) => ("󰎧", "##"),
+            Glyph::Heading(3) => ("󰎬", "###"),
+            Glyph::Heading(4) => ("󰎮", "####"),
+            Glyph::Heading(5) => ("󰎰", "#####"),
+            Glyph::Heading(_) => ("󰎵", "######"),
+            Glyph::Bullet(depth) => {
+                let bullet = ["●", "○", "◆", "◇"][depth % 4];
+                (bullet, bullet)
+            }
+            Glyph::TaskDone => ("󰱒", "[x]"),
+            Glyph::TaskPartial => ("󰛲", "[-]"),
+            Glyph::TaskImportant => ("󰳤", "[!]"),
+            Glyph::TaskTodo => ("󰄱", "[ ]"),
+            Glyph::Link => ("\u{f0339}", "↗"),
+            Glyph::Image => ("\u{f0976}", "▣"),
+            Glyph::WikiLink => ("\u{f15d6}", "»"),
+            Glyph::AlertNote => ("\u{f05d6}", "ℹ"),
+            Glyph::AlertTip => ("\u{f400}", "✓"),
+            Glyph::AlertImportant => ("\u{f017e}", "!"),
+            Glyph::AlertWarning => ("\u{ea6c}", "▲"),
+            Glyph::AlertCaution => ("\u{f0ce6}", "✖"),
+            Glyph::Folder => ("\u{f024b}", "▸"),
+            Glyph::FileType(icon) => (icon, "•"),
+        }
+    }
+
+    pub fn get(self) -> &'static str {
+        let (nerd, plain) = self.variants();
+        match nerd_fonts() {
+            true => nerd,
+            false => plain,
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use std::{
-    io::Write,
+    fs::{self, File},
+    io::{Read, Write},
     path::{Path, PathBuf},
 };
 
@@ -12,20 +13,7 @@ pub enum InspectedBytes {
 
 impl InspectedBytes {
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
-        if let Some(ext) = detect_video(bytes) {
-            return Ok(write_with_ext(bytes, ext));
-        }
-
-        if image::guess_format(bytes).is_ok() {
-            // its does guess later again, so ext doesn't matter.
-            return Ok(write_with_ext(bytes, "png"));
-        }
-
-        if is_pdf(bytes) {
-            return Ok(write_with_ext(bytes, "pdf"));
-        }
-
-        if let Some(ext) = detect_zip_based_doc(bytes) {
+        if let Some(ext) = detect_format(bytes) {
             return Ok(write_with_ext(bytes, ext));
         }
 
@@ -47,6 +35,47 @@ impl InspectedBytes {
     }
 }
 
+/// sniffs the format out of the magic bytes, returning the extension it's known by.
+/// only binary formats are detected, text (and html) give `None`
+pub fn detect_format(bytes: &[u8]) -> Option<&'static str> {
+    if let Some(ext) = detect_video(bytes) {
+        return Some(ext);
+    }
+
+    if let Ok(format) = image::guess_format(bytes) {
+        return format.extensions_str().first().copied();
+    }
+
+    if is_pdf(bytes) {
+        return Some("pdf");
+    }
+
+    detect_zip_based_doc(bytes)
+}
+
+/// files without an extension are sniffed, and copied into a temp file with the detected one.
+/// `None` when the path has an extension or the format isn't known
+pub fn with_detected_ext(path: &Path) -> Result<Option<NamedTempFile>, Box<dyn std::error::Error>> {
+    if path.extension().is_some() {
+        return Ok(None);
+    }
+
+    let mut head = Vec::with_capacity(8192);
+    File::open(path)?.take(8192).read_to_end(&mut head)?;
+    let ext = match detect_format(&head) {
+        // the kind of zip is only known from its central directory, at the end
+        Some("zip") => detect_zip_based_doc(&fs::read(path)?),
+        ext => ext,
+    };
+    let Some(ext) = ext else {
+        return Ok(None);
+    };
+
+    let file = Builder::new().suffix(&format!(".{}", ext)).tempfile()?;
+    fs::copy(path, file.path())?;
+    Ok(Some(file))
+}
+
 fn write_with_ext(bytes: &[u8], ext: &str) -> InspectedBytes {
     let mut file = Builder::new()
         .suffix(&format!(".{}", ext))
@@ -86,7 +115,8 @@ fn detect_video(bytes: &[u8]) -> Option<&'static str> {
         return Some("flv");
     }
 
-    if bytes.get(0) == Some(&0x47) {
+    // transport streams have a sync byte every 188 bytes
+    if bytes.first() == Some(&0x47) && bytes.get(188) == Some(&0x47) {
         return Some("ts");
     }
 
@@ -127,15 +157,15 @@ fn detect_zip_based_doc(bytes: &[u8]) -> Option<&'static str> {
                     return Some("ods");
                 } else if name.ends_with(".odp") {
                     return Some("odp");
+                } else if name == "META-INF/container.xml" {
+                    return Some("epub");
                 }
             }
         }
-
-        // fallback to generic zip
-        return Some("zip");
     }
 
-    None
+    // fallback to generic zip
+    Some("zip")
 }
 
 fn is_html(bytes: &[u8]) -> bool {
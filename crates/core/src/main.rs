@@ -4,25 +4,29 @@ mod concater;
 mod config;
 mod converter;
 mod fetch_manager;
+mod glyphs;
 mod image_viewer;
 mod inspector;
 mod markdown_viewer;
 mod prompter;
 mod scrapy;
+mod text_viewer;
 
 use clap::{
     Arg, ColorChoice, Command,
     builder::{Styles, styling::AnsiColor},
 };
 use clap_complete::{Generator, Shell, generate};
+use concater::{FileKind, UnifiedFormat};
 use config::McatConfig;
 use crossterm::tty::IsTty;
 use dirs::home_dir;
 use rasteroid::term_misc;
 use scrapy::MediaScrapeOptions;
 use std::{
+    fs::{self, File},
     io::{BufWriter, Read, Write},
-    path::Path,
+    path::{Path, PathBuf},
 };
 
 fn print_completions<G: Generator>(gene: G, cmd: &mut Command) {
@@ -45,44 +49,145 @@ fn build_core_args() -> Vec<Arg> {
         Arg::new("theme")
             .long("theme")
             .short('t')
-            .help("Color theme [default: github]")
-            .value_parser([
-                "catppuccin",
-                "nord",
-                "monokai",
-                "dracula",
-                "gruvbox",
-                "one_dark",
-                "solarized",
-                "tokyo_night",
-                "makurai_light",
-                "makurai_dark",
-                "ayu",
-                "ayu_mirage",
-                "github",
-                "synthwave",
-                "material",
-                "rose_pine",
-                "kanagawa",
-                "vscode",
-                "everforest",
-                "autumn",
-                "spring",
-            ]),
+            .value_name("name|file.toml")
+            .help("Color theme, a built-in name, `terminal` for the terminal colors, or a toml file [default: github]")
+            .value_parser(config::parse_theme),
+        Arg::new("list-themes")
+            .long("list-themes")
+            .help("Render a sample document with every built-in theme, to compare them")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("theme-sample")
+            .long("theme-sample")
+            .value_name("file")
+            .help("Markdown file --list-themes renders, instead of its own sample")
+            .value_parser(clap::value_parser!(PathBuf))
+            .requires("list-themes"),
+        Arg::new("code-theme")
+            .long("code-theme")
+            .value_name("name|file.toml")
+            .help("Color theme of code blocks, same options as --theme [default: the --theme]")
+            .value_parser(config::parse_theme),
+        Arg::new("syntax-dir")
+            .long("syntax-dir")
+            .value_name("dir")
+            .help("Highlight code with the .sublime-syntax files in this directory too")
+            .value_parser(clap::value_parser!(PathBuf)),
+        Arg::new("header")
+            .long("header")
+            .short('H')
+            .value_name("'Name: Value'")
+            .help("Extra http header for fetching urls, only sent to their host, can be repeated")
+            .value_parser(config::parse_header)
+            .action(clap::ArgAction::Append),
+        Arg::new("cookie")
+            .long("cookie")
+            .value_name("cookies")
+            .help("Cookie header for fetching urls (e.g. 'session=abc; theme=dark')"),
+        Arg::new("out-file")
+            .long("out-file")
+            .short('O')
+            .value_name("path")
+            .help("Write the output into a file instead of stdout")
+            .value_parser(clap::value_parser!(PathBuf))
+            .conflicts_with("copy"),
+        Arg::new("convert-to")
+            .long("convert-to")
+            .value_name("format")
+            .help("Convert the image output into png, jpg, webp, bmp or gif (implied by the extension of --out-file)")
+            .value_parser(config::parse_image_format)
+            .conflicts_with_all(NO_OUTPUT_ARGS),
+        Arg::new("quality")
+            .long("quality")
+            .value_name("1-100")
+            .help("Quality of jpg images made by --convert-to [default: 90]")
+            .value_parser(clap::value_parser!(u8).range(1..=100)),
+        Arg::new("copy")
+            .long("copy")
+            .help("Copy the output to the clipboard instead of printing it (use -o image for images)")
+            .action(clap::ArgAction::SetTrue)
+            .conflicts_with_all(NO_OUTPUT_ARGS),
     ]
 }
 
+/// the system operations, they have no output --copy or --convert-to could take
+const NO_OUTPUT_ARGS: [&str; 6] = [
+    "fetch-clean",
+    "fetch-chromium",
+    "fetch-ffmpeg",
+    "fetch-ytdlp",
+    "generate-completions",
+    "delete-all-images",
+];
+
 fn build_markdown_viewer_args() -> Vec<Arg> {
     vec![
         Arg::new("no-linenumbers")
             .long("no-linenumbers")
             .help("Disable line numbers in code blocks")
             .action(clap::ArgAction::SetTrue),
+        Arg::new("linenumbers-min")
+            .long("linenumbers-min")
+            .value_name("N")
+            .help("Number the lines of code blocks longer than N lines, 0 for all [default: 10]")
+            .value_parser(clap::value_parser!(usize)),
+        Arg::new("no-nerd-fonts")
+            .long("no-nerd-fonts")
+            .help("Draw plain unicode instead of nerd font icons")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("collapse-details")
+            .long("collapse-details")
+            .help("Only show the summary of <details> that aren't open")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("toc")
+            .long("toc")
+            .help("Start markdown with a table of contents of its headings")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("toc-depth")
+            .long("toc-depth")
+            .value_name("N")
+            .help("Deepest heading level in the --toc [default: 6]")
+            .requires("toc")
+            .value_parser(clap::value_parser!(u8).range(1..=6)),
+        Arg::new("stats")
+            .long("stats")
+            .help("Add a footer with the word count, reading time and headings of markdown")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("show-frontmatter")
+            .long("show-frontmatter")
+            .help("Print the front matter of markdown as is, instead of a title banner")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("no-emoji")
+            .long("no-emoji")
+            .help("Keep :shortcodes: as they are, instead of emojis")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("as-code")
+            .long("as-code")
+            .help("Show text files as highlighted source, even the ones mcat converts (md, json, csv..)")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("diff")
+            .long("diff")
+            .help("Show the input as a unified diff (e.g. `git diff | mcat --diff`), colored with the theme")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("extract-code")
+            .long("extract-code")
+            .value_name("N")
+            .help("Print the raw text of the Nth code block, without highlighting or borders")
+            .value_parser(clap::value_parser!(u64).range(1..)),
         Arg::new("md-image")
             .long("md-image")
             .value_name("mode")
             .help("what images to render in the markdown [default: auto]")
             .value_parser(["all", "small", "none", "auto"]),
+        Arg::new("tab-width")
+            .long("tab-width")
+            .value_name("N")
+            .help("Spaces per tab in code blocks and tables [default: 4]")
+            .value_parser(clap::value_parser!(usize)),
+        Arg::new("table-mode")
+            .long("table-mode")
+            .value_name("mode")
+            .help("what to do with markdown tables wider than the terminal [default: wrap]")
+            .value_parser(["wrap", "truncate", "shrink"]),
         Arg::new("fast")
             .short('f')
             .help("sets md-image to none, for speed.")
@@ -103,7 +208,7 @@ fn build_markdown_viewer_args() -> Vec<Arg> {
         Arg::new("pager")
             .long("pager")
             .value_name("command")
-            .help("Modify the default pager [default: 'less -r']"),
+            .help("Modify the default pager, 'builtin' for mcat's own [default: $PAGER, or 'less -R']"),
         Arg::new("paging")
             .long("paging")
             .value_name("mode")
@@ -117,6 +222,48 @@ fn build_markdown_viewer_args() -> Vec<Arg> {
             .short('p')
             .help("Shortcut for --paging always")
             .action(clap::ArgAction::SetTrue),
+        Arg::new("pages")
+            .long("pages")
+            .value_name("range")
+            .help("Only convert these pages of a pdf or tiff (e.g. 3-7 or 4)")
+            .value_parser(config::parse_pages),
+        Arg::new("lines")
+            .long("lines")
+            .short('L')
+            .value_name("range")
+            .help("Only show these lines of the input (e.g. 10:40, 10: or :40)")
+            .value_parser(config::parse_lines),
+        Arg::new("grep")
+            .long("grep")
+            .value_name("pattern")
+            .help("Only show the lines of the rendered output matching this regex, with some context")
+            .value_parser(config::parse_grep),
+        Arg::new("grep-context")
+            .long("grep-context")
+            .value_name("N")
+            .help("Lines of context around --grep matches [default: 2]")
+            .value_parser(clap::value_parser!(usize)),
+        Arg::new("sheet")
+            .long("sheet")
+            .value_name("name")
+            .help("Only convert this sheet of a spreadsheet, by name or position (1-based)"),
+        Arg::new("csv-delimiter")
+            .long("csv-delimiter")
+            .value_name("char")
+            .help("Delimiter of csv files [default: detected]")
+            .value_parser(config::parse_csv_delimiter),
+        Arg::new("notes")
+            .long("notes")
+            .help("Include speaker notes of presentations")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("ocr")
+            .long("ocr")
+            .help("Read scanned pdf pages with tesseract")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("keep-headers")
+            .long("keep-headers")
+            .help("Keep the headers and footers repeated on every page of pdfs")
+            .action(clap::ArgAction::SetTrue),
     ]
 }
 fn build_image_viewer_args() -> Vec<Arg> {
@@ -133,7 +280,13 @@ fn build_image_viewer_args() -> Vec<Arg> {
         Arg::new("report")
             .long("report")
             .action(clap::ArgAction::SetTrue)
-            .help("Reports image/video dimensions and additional info"),
+            .help("Reports image/video dimensions, document metadata and additional info"),
+        Arg::new("format")
+            .long("format")
+            .value_name("format")
+            .help("Format of --report, document metadata goes to stderr [default: human]")
+            .value_parser(["human", "json"])
+            .requires("report"),
         Arg::new("silent")
             .long("silent")
             .action(clap::ArgAction::SetTrue)
@@ -154,10 +307,71 @@ fn build_image_viewer_args() -> Vec<Arg> {
             .long("ascii")
             .help("Use ASCII art output")
             .action(clap::ArgAction::SetTrue),
+        Arg::new("halfblock")
+            .long("halfblock")
+            .help("Use truecolor half-block output")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("braille")
+            .long("braille")
+            .help("Use Braille art output, higher resolution than ASCII")
+            .action(clap::ArgAction::SetTrue),
         Arg::new("horizontal")
             .long("hori")
             .action(clap::ArgAction::SetTrue)
             .help("Concatenate images horizontally"),
+        Arg::new("as-frames")
+            .long("as-frames")
+            .help("Play the images (or the images of a directory) as the frames of an animation, in --sort order")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("fps")
+            .long("fps")
+            .value_name("fps")
+            .help("Frames per second of --as-frames [default: 10]")
+            .value_parser(config::parse_fps),
+        Arg::new("start")
+            .long("start")
+            .value_name("time")
+            .help("Start videos at this time, in seconds or hh:mm:ss")
+            .value_parser(config::parse_timestamp),
+        Arg::new("end")
+            .long("end")
+            .value_name("time")
+            .help("Stop videos at this time, in seconds or hh:mm:ss")
+            .value_parser(config::parse_timestamp),
+        Arg::new("duration")
+            .long("duration")
+            .value_name("time")
+            .help("Play videos for this long after --start, in seconds or hh:mm:ss")
+            .value_parser(config::parse_timestamp)
+            .conflicts_with("end"),
+        Arg::new("speed")
+            .long("speed")
+            .value_name("factor")
+            .help("Playback speed of videos, 2 plays twice as fast [default: 1]")
+            .value_parser(config::parse_speed),
+        Arg::new("no-loop")
+            .long("no-loop")
+            .help("Play videos once, instead of repeating them until interrupted")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("max-image-px")
+            .long("max-image-px")
+            .value_name("WxH")
+            .help("Cap the encoded image size in pixels [default: 2000x2000 for sixel]")
+            .value_parser(config::parse_max_image_px),
+        Arg::new("max-image-pixels")
+            .long("max-image-pixels")
+            .value_name("N")
+            .help("Downsample images with more pixels than this before encoding, 0 for no limit [default: 12M]")
+            .value_parser(config::parse_count),
+        Arg::new("max-image-bytes")
+            .long("max-image-bytes")
+            .value_name("N")
+            .help("Downsample images that encode into more bytes than this, 0 for no limit [default: 8M]")
+            .value_parser(config::parse_count),
+        Arg::new("no-auto-orient")
+            .long("no-auto-orient")
+            .help("Show images as stored, ignoring their EXIF orientation")
+            .action(clap::ArgAction::SetTrue),
         Arg::new("delete-all-images")
             .long("delete-images")
             .help("Delete all images (Kitty only)")
@@ -165,6 +379,7 @@ fn build_image_viewer_args() -> Vec<Arg> {
         Arg::new("inline-options").long("opts").help(
             "Options for --output inline:\n\
                      *  center=<bool>\n\
+                     *  vcenter=<bool> (centered vertically too)\n\
                      *  inline=<bool>\n\
                      *  width=<string>\n\
                      *  height=<string>\n\
@@ -174,7 +389,10 @@ fn build_image_viewer_args() -> Vec<Arg> {
                      *  zoom=<usize>\n\
                      *  x=<int>\n\
                      *  y=<int>\n\
-                     Example: --opts 'center=false,inline=true,width=80%,height=20c,scale=0.5,spx=1920x1080,sc=100x20xforce,zoom=2,x=16,y=8'",
+                     *  crop=<x,y,width,height> (px / c / % of the image)\n\
+                     *  rotate=<90|180|270> (clockwise)\n\
+                     *  flip=<h|v>\n\
+                     Example: --opts 'center=false,inline=true,width=80%,height=20c,scale=0.5,spx=1920x1080,sc=100x20xforce,zoom=2,x=16,y=8,crop=0,0,50%,50%,rotate=90,flip=h'",
         ),
     ]
 }
@@ -193,6 +411,10 @@ fn build_fetcher_args() -> Vec<Arg> {
             .long("fetch-ffmpeg")
             .help("Download and prepare ffmpeg")
             .action(clap::ArgAction::SetTrue),
+        Arg::new("fetch-ytdlp")
+            .long("fetch-ytdlp")
+            .help("Download and prepare yt-dlp, for youtube and vimeo urls")
+            .action(clap::ArgAction::SetTrue),
         Arg::new("fetch-clean")
             .long("fetch-clean")
             .help("Clean up local binaries")
@@ -206,6 +428,32 @@ fn build_ls_args() -> Vec<Arg> {
             .short('a')
             .help("Include hidden files")
             .action(clap::ArgAction::SetTrue),
+        Arg::new("recursive")
+            .long("recursive")
+            .short('r')
+            .help("Show every text file inside of a directory, instead of prompting for them")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("no-preview")
+            .long("no-preview")
+            .help("Don't preview the highlighted file when prompting for files in a directory")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("contact-sheet")
+            .long("contact-sheet")
+            .help("Tile the images of a directory into a single image, without names (same as -o image on a directory)")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("sort")
+            .long("sort")
+            .value_name("order")
+            .help("How directory listings are ordered, natural puts img2 before img10, mtime and size put the newest and biggest first [default: natural]")
+            .value_parser(["name", "natural", "mtime", "size"]),
+        Arg::new("reverse")
+            .long("reverse")
+            .help("Reverse the --sort order of directory listings")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("no-dirs-first")
+            .long("no-dirs-first")
+            .help("Sort directories along with files in directory listings, instead of first")
+            .action(clap::ArgAction::SetTrue),
         Arg::new("ls-options").long("ls-opts").help(
             "Options for directory listings:\n\
                  *  x_padding=<string>\n\
@@ -214,6 +462,10 @@ fn build_ls_args() -> Vec<Arg> {
                  *  max_width=<string>\n\
                  *  height=<string>\n\
                  *  items_per_row=<usize>\n\
+                 *  columns=<usize> (for --contact-sheet)\n\
+                 *  sort=<name|natural|mtime|size>\n\
+                 *  reverse=<bool>\n\
+                 *  dirs_first=<bool>\n\
                  Example: --ls-opts 'x_padding=4c,y_padding=2c,min_width=4c,max_width=16c,height=8%,items_per_row=12'",
         ),
     ]
@@ -229,9 +481,11 @@ fn build_input_arg(stdin_streamed: bool) -> Arg {
             "fetch-clean",
             "fetch-chromium",
             "fetch-ffmpeg",
+            "fetch-ytdlp",
             "report",
             "generate-completions",
             "delete-all-images",
+            "list-themes",
         ]);
     }
     input_arg
@@ -242,7 +496,10 @@ fn build_cli(stdin_streamed: bool) -> Command {
         .version(env!("CARGO_PKG_VERSION"))
         .author(env!("CARGO_PKG_AUTHORS"))
         .about("A powerful extended cat command - cat all the things you couldn't before")
-        .color(ColorChoice::Always)
+        .color(match config::no_color() {
+            true => ColorChoice::Never,
+            false => ColorChoice::Always,
+        })
         .styles(
             Styles::styled()
                 .header(AnsiColor::Green.on_default().bold())
@@ -267,14 +524,39 @@ fn build_cli(stdin_streamed: bool) -> Command {
 }
 
 fn main() {
+    #[cfg(target_os = "linux")]
+    if std::env::args().nth(1).as_deref() == Some(SERVE_CLIPBOARD) {
+        serve_clipboard().unwrap_or_exit();
+        return;
+    }
+
     let stdin_streamed = !std::io::stdin().is_tty();
-    let stdout = std::io::stdout().lock();
-    let mut out = BufWriter::new(stdout);
     let opts = build_cli(stdin_streamed).get_matches();
 
     let mut config = McatConfig::default();
+    config.extend_from_file();
     config.extend_from_env();
     config.extend_from_args(&opts);
+    rasteroid::image_extended::set_image_budget(config.max_image_pixels, config.max_image_bytes);
+    glyphs::set_nerd_fonts(config.nerd_fonts);
+    if let Some(dir) = &config.syntax_dir {
+        let dir = expand_tilde(&dir.to_string_lossy());
+        markdown_viewer::syntaxes::set_syntax_dir(PathBuf::from(dir));
+    }
+
+    // with --copy or --convert-to the output is collected, and handled at the end
+    let mut collected = Vec::new();
+    let collect = config.copy || config.convert_to.is_some();
+    let sink: Box<dyn Write + '_> = match (collect, &config.out_file) {
+        (true, _) => Box::new(&mut collected),
+        (false, Some(path)) => Box::new(
+            File::create(path)
+                .map_err(|e| format!("failed creating {}: {e}", path.display()))
+                .unwrap_or_exit(),
+        ),
+        (false, None) => Box::new(std::io::stdout().lock()),
+    };
+    let mut out = BufWriter::new(sink);
 
     // setting the winsize
     let spx = term_misc::break_size_string(config.inline_options.spx.as_ref()).unwrap_or_exit();
@@ -288,7 +570,7 @@ fn main() {
     );
 
     // fn and leave
-    if let Some(fn_and_leave) = config.fn_and_leave {
+    if let Some(fn_and_leave) = config.fn_and_leave.clone() {
         match fn_and_leave {
             config::FnAndLeave::ShellGenerate(shell) => {
                 let mut cmd = build_cli(stdin_streamed);
@@ -305,14 +587,34 @@ fn main() {
             }
             config::FnAndLeave::FetchChromium => fetch_manager::fetch_chromium().unwrap_or_exit(),
             config::FnAndLeave::FetchFfmpeg => fetch_manager::fetch_ffmpeg().unwrap_or_exit(),
+            config::FnAndLeave::FetchYtdlp => fetch_manager::fetch_ytdlp().unwrap_or_exit(),
             config::FnAndLeave::FetchClean => fetch_manager::clean().unwrap_or_exit(),
-            config::FnAndLeave::Report => report_full(),
+            config::FnAndLeave::Report { json } => {
+                if collect {
+                    eprintln!("--report without inputs can't be copied or converted");
+                    std::process::exit(1);
+                }
+                report_full(json)
+            }
+            config::FnAndLeave::ListThemes { ref sample } => {
+                let sample = match sample {
+                    Some(path) => fs::read_to_string(path)
+                        .map_err(|e| format!("failed reading {}: {e}", path.display()))
+                        .unwrap_or_exit(),
+                    None => markdown_viewer::THEME_SAMPLE.to_owned(),
+                };
+                let gallery = markdown_viewer::theme_gallery(&sample, &config);
+                out.write_all(gallery.as_bytes()).unwrap_or_exit();
+            }
         };
+        out.flush().unwrap();
+        drop(out);
+        finish_collected(collected, &config);
         return;
     };
 
-    // if ls
-    if config.is_ls {
+    // if ls, contact sheets go through the usual inputs
+    if config.is_ls && !config.contact_sheet {
         let d = ".".to_string();
         let input = config.input.get(1).unwrap_or(&d);
         if config.is_tmux {
@@ -323,9 +625,13 @@ fn main() {
             &mut out,
             &config.ls_options,
             config.hidden,
+            !config.no_auto_orient,
             &config.inline_encoder,
         )
         .unwrap_or_exit();
+        out.flush().unwrap();
+        drop(out);
+        finish_collected(collected, &config);
         return;
     }
 
@@ -349,64 +655,284 @@ fn main() {
     }
     let mut scraper_opts = MediaScrapeOptions::default();
     scraper_opts.silent = config.silent;
-    for i in config.input.iter() {
-        if i.starts_with("https://") {
-            if let Ok(tmp) = scrapy::scrape_biggest_media(&i, &scraper_opts) {
-                let path = tmp.path().to_path_buf();
-                tmp_files.push(tmp);
-                path_bufs.push((path, Some(i.clone())));
-            } else {
-                eprintln!("{} didn't contain any supported media", i);
+    scraper_opts.headers = config.headers.clone();
+    scraper_opts.cookie = config.cookie.clone();
+    let inputs = match config.is_ls {
+        true if config.input.len() > 1 => config.input[1..].to_vec(),
+        true => vec![".".to_owned()],
+        false => config.input.clone(),
+    };
+    let wants_image = config.output.as_deref() == Some("image") && !config.recursive;
+    for i in inputs.iter() {
+        if i.starts_with("https://") || i.starts_with("http://") {
+            match scrapy::scrape_biggest_media(i, &scraper_opts) {
+                Ok(tmp) => {
+                    let path = tmp.path().to_path_buf();
+                    tmp_files.push(tmp);
+                    path_bufs.push((path, Some(i.clone())));
+                }
+                Err(e) => eprintln!("{}: {}", i, e),
             }
         } else {
-            let i = expand_tilde(&i);
+            // file:// urls are plain local paths
+            let i = match file_url_to_path(i) {
+                Some(path) => path.to_string_lossy().into_owned(),
+                None => expand_tilde(i),
+            };
             let path = Path::new(&i);
+            // quoted globs, the shell didn't expand them
+            if !path.exists() && prompter::is_glob(&i) {
+                let matched = prompter::expand_glob(&i, config.hidden).unwrap_or_exit();
+                if matched.is_empty() {
+                    eprintln!("{} didn't match any file", i);
+                    std::process::exit(1);
+                }
+                path_bufs.extend(matched.into_iter().map(|path| (path, None)));
+                continue;
+            }
             if !path.exists() {
                 eprintln!("{} doesn't exists", path.display());
                 std::process::exit(1);
             }
-            if path.is_dir() {
-                let mut selected_files =
-                    prompter::prompt_for_files(path, config.hidden).unwrap_or_exit();
-                selected_files.sort();
+            // iWork documents saved on macOS are directory packages
+            let is_dir = path.is_dir() && !converter::is_iwork(path);
+            if is_dir && config.as_frames {
+                let frames = converter::dir_images(path, &config.ls_options, config.hidden);
+                path_bufs.extend(frames.into_iter().map(|(path, _)| (path, None)));
+            } else if is_dir && (config.contact_sheet || wants_image) {
+                let sheet = converter::contact_sheet(
+                    path,
+                    &config.ls_options,
+                    config.hidden,
+                    !config.no_auto_orient,
+                )
+                .unwrap_or_exit();
+                let mut tmp = tempfile::Builder::new()
+                    .suffix(".png")
+                    .tempfile()
+                    .unwrap_or_exit();
+                tmp.write_all(&sheet).unwrap_or_exit();
+                path_bufs.push((tmp.path().to_path_buf(), Some(i.clone())));
+                tmp_files.push(tmp);
+            } else if is_dir && config.recursive {
+                let files = prompter::collect_text_files(path, config.hidden).unwrap_or_exit();
+                path_bufs.extend(files.into_iter().map(|path| (path, None)));
+            } else if is_dir {
+                let mut selected_files = prompter::prompt_for_files(path, &config).unwrap_or_exit();
+                let sort = config.ls_options.sort;
+                selected_files.sort_by(|a, b| sort.cmp_paths(&a.0, &b.0));
                 path_bufs.extend_from_slice(&selected_files);
+            } else if let Some(tmp) = inspector::with_detected_ext(path).unwrap_or_exit() {
+                // no extension, its format was sniffed out of its content
+                path_bufs.push((tmp.path().to_path_buf(), Some(i.clone())));
+                tmp_files.push(tmp);
             } else {
                 path_bufs.push((path.to_path_buf(), None));
             }
         }
     }
 
+    // the images become a single animation, played like any other video
+    #[allow(unused_variables)]
+    let frames_dir = match config.as_frames {
+        true => {
+            let (dir, path) =
+                concater::concat_frames(&path_bufs, config.fps, !config.no_auto_orient)
+                    .unwrap_or_exit();
+            path_bufs = vec![(path, None)];
+            Some(dir)
+        }
+        false => None,
+    };
+
     // concating and printing the result
-    let formats = concater::check_unified_format(&path_bufs);
+    let format = concater::check_unified_format(&path_bufs);
     let is_interactive = config.output.clone().unwrap_or_default() == "interactive";
-    let contains_video = formats.contains(&"video");
-    let contains_image = formats.contains(&"image");
-    let contains_text = formats.contains(&"text");
-    match (
-        contains_video,
-        contains_image,
-        contains_text,
-        is_interactive,
-    ) {
-        // images and videos and interactive
-        (false, true | false, true | false, true) => {
+    // mixed inputs keep going past a failed one, but still exit with an error
+    let mut failed = false;
+    match format {
+        // images and text in interactive mode
+        ref format if is_interactive && !format.contains(FileKind::Video) => {
             let paths = path_bufs.iter().map(|v| v.0.as_path()).collect();
             catter::cat(paths, &mut out, &config).unwrap_or_exit();
         }
+        UnifiedFormat::Pdf if path_bufs.len() > 1 => {
+            let to = config.output.as_deref().unwrap_or_default();
+            let image = matches!(to, "image" | "inline");
+            let tmp = concater::concat_pdf(&path_bufs, image, &config).unwrap_or_exit();
+            catter::cat(vec![tmp.path()], &mut out, &config).unwrap_or_exit();
+        }
+        UnifiedFormat::Text | UnifiedFormat::Pdf => {
+            cat_kind(FileKind::Text, &path_bufs, &mut out, &config).unwrap_or_exit()
+        }
+        UnifiedFormat::Image => {
+            cat_kind(FileKind::Image, &path_bufs, &mut out, &config).unwrap_or_exit()
+        }
+        UnifiedFormat::Video => {
+            cat_kind(FileKind::Video, &path_bufs, &mut out, &config).unwrap_or_exit()
+        }
+        // each input is shown on its own, in the order it was given
+        UnifiedFormat::Mixed(_) => {
+            for (path, name) in path_bufs.iter() {
+                let kind = concater::classify(path);
+                let input = [(path.clone(), name.clone())];
+                if let Err(err) = cat_kind(kind, &input, &mut out, &config) {
+                    eprintln!("{}: {}", path.display(), err);
+                    failed = true;
+                }
+                out.flush().unwrap();
+            }
+        }
+    }
+    out.flush().unwrap();
+    drop(out);
+
+    finish_collected(collected, &config);
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+/// `--convert-to` and `--copy`, on the output that was collected instead of written
+fn finish_collected(mut collected: Vec<u8>, config: &McatConfig) {
+    if let Some(format) = config.convert_to {
+        collected = converter::convert_image(&collected, format, config.quality)
+            .map_err(|e| format!("failed converting the output: {e}"))
+            .unwrap_or_exit();
+        if !config.copy {
+            match &config.out_file {
+                Some(path) => fs::write(path, &collected)
+                    .map_err(|e| format!("failed writing {}: {e}", path.display())),
+                None => std::io::stdout()
+                    .write_all(&collected)
+                    .map_err(|e| e.to_string()),
+            }
+            .unwrap_or_exit();
+        }
+    }
+    if config.copy {
+        let what = copy_to_clipboard(&collected)
+            .map_err(|e| format!("failed copying to the clipboard: {e}"))
+            .unwrap_or_exit();
+        eprintln!("copied {what} to the clipboard");
+    }
+}
+
+enum ClipContent {
+    Image(arboard::ImageData<'static>),
+    Text(String),
+}
+
+/// images are copied as images, anything else as text
+fn copy_to_clipboard(bytes: &[u8]) -> Result<String, Box<dyn std::error::Error>> {
+    let (content, what) = clip_content(bytes);
+    set_clipboard(content, bytes)?;
+    Ok(what)
+}
+
+/// what `bytes` are copied as, and a description of it
+fn clip_content(bytes: &[u8]) -> (ClipContent, String) {
+    match image::load_from_memory(bytes) {
+        Ok(img) => {
+            let rgba = img.to_rgba8();
+            let (width, height) = rgba.dimensions();
+            let img = arboard::ImageData {
+                width: width as usize,
+                height: height as usize,
+                bytes: rgba.into_raw().into(),
+            };
+            (ClipContent::Image(img), format!("a {width}x{height} image"))
+        }
+        Err(_) => {
+            let text = String::from_utf8_lossy(bytes).into_owned();
+            let what = format!("{} lines of text", text.lines().count());
+            (ClipContent::Text(text), what)
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_clipboard(content: ClipContent, _bytes: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut clipboard = arboard::Clipboard::new()?;
+    match content {
+        ClipContent::Image(img) => clipboard.set_image(img)?,
+        ClipContent::Text(text) => clipboard.set_text(text)?,
+    }
+    Ok(())
+}
+
+/// the hidden flag mcat runs itself with to serve the clipboard, see [`set_clipboard`]
+#[cfg(target_os = "linux")]
+const SERVE_CLIPBOARD: &str = "--serve-clipboard";
+
+/// on x11 and wayland the clipboard is served by the process that set it, and is gone once it exits.
+/// another mcat is started in the background with the bytes on its stdin, and serves them until
+/// something else is copied. forking instead isn't safe with arboard's and rayon's threads around
+#[cfg(target_os = "linux")]
+fn set_clipboard(_content: ClipContent, bytes: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+    use std::{os::unix::process::CommandExt, process::Stdio};
+
+    // a missing clipboard is reported here, the helper can't
+    drop(arboard::Clipboard::new()?);
+
+    // nothing but stdin is kept, so a pipe reading our output isn't held open
+    // until the clipboard changes, and its own process group keeps ctrl-c away from it
+    let mut helper = std::process::Command::new(std::env::current_exe()?)
+        .arg(SERVE_CLIPBOARD)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .process_group(0)
+        .spawn()?;
+    helper
+        .stdin
+        .take()
+        .ok_or("the clipboard helper has no stdin")?
+        .write_all(bytes)?;
+    Ok(())
+}
+
+/// the background half of [`set_clipboard`], blocks until something else is copied
+#[cfg(target_os = "linux")]
+fn serve_clipboard() -> Result<(), Box<dyn std::error::Error>> {
+    use arboard::SetExtLinux;
+
+    let mut bytes = Vec::new();
+    std::io::stdin().read_to_end(&mut bytes)?;
+    let (content, _) = clip_content(&bytes);
+    let mut clipboard = arboard::Clipboard::new()?;
+    let set = clipboard.set().wait();
+    match content {
+        ClipContent::Image(img) => set.image(img)?,
+        ClipContent::Text(text) => set.text(text)?,
+    }
+    Ok(())
+}
+
+fn cat_kind(
+    kind: FileKind,
+    path_bufs: &[(PathBuf, Option<String>)],
+    out: &mut impl Write,
+    config: &McatConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match kind {
         // only text
-        (false, false, true, _) => {
+        FileKind::Text => {
             if path_bufs.len() == 1 {
-                catter::cat(vec![&path_bufs[0].0], &mut out, &config).unwrap_or_exit();
+                catter::cat(vec![&path_bufs[0].0], out, config)?;
             } else {
-                let path_bufs = concater::assign_names(&path_bufs);
-                let tmp = concater::concat_text(path_bufs);
-                catter::cat(vec![tmp.path()], &mut out, &config).unwrap_or_exit();
+                let path_bufs = concater::assign_names(path_bufs);
+                let tmp = concater::concat_text(path_bufs, config);
+                catter::cat(vec![tmp.path()], out, config)?;
             }
         }
         // only videos
-        (true, false, false, _) => {
+        FileKind::Video => {
             match config.inline_encoder {
-                rasteroid::InlineEncoder::Ascii | rasteroid::InlineEncoder::Sixel => {}
+                rasteroid::InlineEncoder::Ascii
+                | rasteroid::InlineEncoder::HalfBlock
+                | rasteroid::InlineEncoder::Braille
+                | rasteroid::InlineEncoder::Sixel => {}
                 _ => {
                     if config.is_tmux {
                         rasteroid::set_tmux_passthrough(true);
@@ -414,17 +940,19 @@ fn main() {
                 }
             }
             if path_bufs.len() == 1 {
-                catter::cat(vec![&path_bufs[0].0], &mut out, &config).unwrap_or_exit();
+                catter::cat(vec![&path_bufs[0].0], out, config)?;
             } else {
                 #[allow(unused_variables)]
-                let (dir, path) = concater::concat_video(&path_bufs).unwrap_or_exit();
-                catter::cat(vec![&path], &mut out, &config).unwrap_or_exit();
+                let (dir, path) = concater::concat_video(path_bufs)?;
+                catter::cat(vec![&path], out, config)?;
             }
         }
         // only images
-        (false, true, false, _) => {
+        FileKind::Image => {
             match config.inline_encoder {
-                rasteroid::InlineEncoder::Ascii => {}
+                rasteroid::InlineEncoder::Ascii
+                | rasteroid::InlineEncoder::HalfBlock
+                | rasteroid::InlineEncoder::Braille => {}
                 _ => {
                     if config.is_tmux {
                         rasteroid::set_tmux_passthrough(true);
@@ -432,19 +960,18 @@ fn main() {
                 }
             }
             if path_bufs.len() == 1 {
-                catter::cat(vec![&path_bufs[0].0], &mut out, &config).unwrap_or_exit();
+                catter::cat(vec![&path_bufs[0].0], out, config)?;
             } else {
-                let img = concater::concat_images(path_bufs, config.horizontal_image_stacking)
-                    .unwrap_or_exit();
-                catter::cat(vec![img.path()], &mut out, &config).unwrap_or_exit();
+                let img = concater::concat_images(
+                    path_bufs.to_vec(),
+                    config.horizontal_image_stacking,
+                    !config.no_auto_orient,
+                )?;
+                catter::cat(vec![img.path()], out, config)?;
             }
         }
-        _ => {
-            eprintln!("Error: Cannot have 2 different formats [text / images / videos]");
-            std::process::exit(1);
-        }
     }
-    out.flush().unwrap();
+    Ok(())
 }
 
 trait UnwrapOrExit<T> {
@@ -463,6 +990,13 @@ impl<T, E: std::fmt::Display> UnwrapOrExit<T> for Result<T, E> {
     }
 }
 
+fn file_url_to_path(input: &str) -> Option<PathBuf> {
+    if !input.starts_with("file://") {
+        return None;
+    }
+    reqwest::Url::parse(input).ok()?.to_file_path().ok()
+}
+
 fn expand_tilde(path: &str) -> String {
     if path.starts_with("~") {
         if let Some(home) = home_dir() {
@@ -472,14 +1006,18 @@ fn expand_tilde(path: &str) -> String {
     path.to_string()
 }
 
-fn report_full() {
-    let is_chromium_installed = fetch_manager::is_chromium_installed();
-    let is_ffmpeg_installed = fetch_manager::is_ffmpeg_installed();
+fn report_full(json: bool) {
+    let chromium = fetch_manager::find_chromium();
+    let ffmpeg = fetch_manager::find_ffmpeg();
     let is_poppler_installed = fetch_manager::is_poppler_installed();
+    let ytdlp = fetch_manager::find_ytdlp();
     let mut env = term_misc::EnvIdentifiers::new();
-    let kitty = rasteroid::kitty_encoder::is_kitty_capable(&mut env);
-    let iterm = rasteroid::iterm_encoder::is_iterm_capable(&mut env);
-    let sixel = rasteroid::sixel_encoder::is_sixel_capable(&mut env);
+    let rasteroid::Capabilities {
+        kitty,
+        iterm,
+        sixel,
+        truecolor,
+    } = rasteroid::Capabilities::detect(&mut env);
     let ascii = true; //not sure what doesn't support it
     let winsize = term_misc::get_wininfo();
     let tmux = winsize.is_tmux;
@@ -508,6 +1046,55 @@ fn report_full() {
             .unwrap_or("Unknown")
     };
     let ver = env!("CARGO_PKG_VERSION");
+    let (max_pixels, max_bytes) = rasteroid::image_extended::image_budget();
+
+    if json {
+        let install = |install: Option<fetch_manager::Install>| match install {
+            Some(install) => serde_json::json!({
+                "installed": true,
+                "kind": install.kind(),
+                "path": install.path(),
+            }),
+            None => serde_json::json!({ "installed": false }),
+        };
+        let report = serde_json::json!({
+            "dependencies": {
+                "chromium": install(chromium),
+                "ffmpeg": install(ffmpeg),
+                "poppler": { "installed": is_poppler_installed },
+                "ytdlp": install(ytdlp),
+            },
+            "graphics": {
+                "kitty": kitty,
+                "iterm": iterm,
+                "sixel": sixel,
+                "ascii": ascii,
+                "halfblock": truecolor,
+                "braille": ascii,
+            },
+            "terminal": {
+                "width": winsize.sc_width,
+                "height": winsize.sc_height,
+                "pixel_width": winsize.spx_width(),
+                "pixel_height": winsize.spx_height(),
+            },
+            "image_budget": {
+                "max_pixels": max_pixels,
+                "max_bytes": max_bytes,
+            },
+            "tmux": tmux,
+            "inline": inline,
+            "os": os,
+            "term": term,
+            "term_program": tmux_program,
+            "version": ver,
+        });
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).unwrap_or_default()
+        );
+        return;
+    }
 
     // Print header with fancy box
     println!("┌────────────────────────────────────────────────────┐");
@@ -530,6 +1117,12 @@ fn report_full() {
             red("× MISSING")
         }
     }
+    fn format_install(install: Option<&fetch_manager::Install>) -> String {
+        match install {
+            Some(install) => green(&format!("✓ INSTALLED ({})", install.kind())),
+            None => red("× MISSING"),
+        }
+    }
     fn format_capability(status: bool) -> String {
         if status {
             green("✓ SUPPORTED")
@@ -547,15 +1140,13 @@ fn report_full() {
 
     // Print required dependencies
     println!("│ Optional Dependencies:                             │");
-    println!(
-        "│   Chromium: {:<47} │",
-        format_status(is_chromium_installed)
-    );
-    println!("│   FFmpeg:   {:<47} │", format_status(is_ffmpeg_installed));
+    println!("│   Chromium: {:<47} │", format_install(chromium.as_ref()));
+    println!("│   FFmpeg:   {:<47} │", format_install(ffmpeg.as_ref()));
     println!(
         "│   Poppler:  {:<47} │",
         format_status(is_poppler_installed)
     );
+    println!("│   yt-dlp:   {:<47} │", format_install(ytdlp.as_ref()));
 
     // Print terminal capabilities
     println!("├────────────────────────────────────────────────────┤");
@@ -564,14 +1155,26 @@ fn report_full() {
     println!("│   iTerm2:   {:<47} │", format_capability(iterm));
     println!("│   Sixel:    {:<47} │", format_capability(sixel));
     println!("│   ASCII:    {:<47} │", format_capability(ascii));
+    println!("│   HalfBlock:{:<47} │", format_capability(truecolor));
+    println!("│   Braille:  {:<47} │", format_capability(ascii));
 
     // Print terminal dimensions
     println!("├────────────────────────────────────────────────────┤");
     println!("│ Terminal Info:                                     │");
     println!("│   Width:          {:<32} │", winsize.sc_width);
     println!("│   Height:         {:<32} │", winsize.sc_height);
-    println!("│   Pixel Width:    {:<32} │", winsize.spx_width);
-    println!("│   Pixel Height:   {:<32} │", winsize.spx_height);
+    println!("│   Pixel Width:    {:<32} │", winsize.spx_width());
+    println!("│   Pixel Height:   {:<32} │", winsize.spx_height());
+
+    // Image budget
+    let limit = |n: u64| match n {
+        0 => "no limit".to_string(),
+        n => n.to_string(),
+    };
+    println!("├────────────────────────────────────────────────────┤");
+    println!("│ Image Budget:                                      │");
+    println!("│   Max Pixels:     {:<32} │", limit(max_pixels));
+    println!("│   Max Bytes:      {:<32} │", limit(max_bytes));
 
     // Others
     println!("├────────────────────────────────────────────────────┤");
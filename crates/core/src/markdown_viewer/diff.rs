@@ -0,0 +1,136 @@
+use super::{
+    render::{BOLD, RESET},
+    themes::CustomTheme,
+};
+
+/// lines left in the current hunk, on the old and the new side
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Hunk {
+    old: usize,
+    new: usize,
+}
+
+impl Hunk {
+    /// from `@@ -1,5 +1,6 @@`, a range without a count is one line.
+    /// a header that doesn't parse (combined diffs) runs until the next one
+    fn parse(header: &str) -> Hunk {
+        let mut ranges = header.split_whitespace().skip(1);
+        let mut count = |sign: char| -> Option<usize> {
+            let range = ranges.next()?.strip_prefix(sign)?;
+            match range.split_once(',') {
+                Some((_, count)) => count.parse().ok(),
+                None => range.parse::<usize>().ok().map(|_| 1),
+            }
+        };
+        match (count('-'), count('+')) {
+            (Some(old), Some(new)) => Hunk { old, new },
+            _ => Hunk {
+                old: usize::MAX,
+                new: usize::MAX,
+            },
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.old == 0 && self.new == 0
+    }
+}
+
+/// a unified diff (`git diff`, `diff -u`) colored with the theme: added lines green,
+/// removed ones red and hunk headers like comments.
+/// `---`/`+++` are file headers only between hunks, inside one they're removed or added lines
+pub fn diff_to_ansi(diff: &str, theme: &CustomTheme) -> String {
+    let paint = |color: &str, line: &str| format!("{color}{line}{RESET}");
+    let mut hunk: Option<Hunk> = None;
+    let mut res = String::with_capacity(diff.len() * 2);
+    res.push_str(&theme.foreground.fg);
+
+    for line in diff.lines() {
+        let styled = if line.starts_with("@@") {
+            hunk = Some(Hunk::parse(line));
+            hunk_header(line, theme)
+        } else if let Some(current) = hunk.as_mut().filter(|_| !line.starts_with("diff ")) {
+            match line.chars().next() {
+                Some('+') => {
+                    current.new = current.new.saturating_sub(1);
+                    paint(&theme.green.fg, line)
+                }
+                Some('-') => {
+                    current.old = current.old.saturating_sub(1);
+                    paint(&theme.red.fg, line)
+                }
+                // `\ No newline at end of file`
+                Some('\\') => paint(&theme.comment.fg, line),
+                _ => {
+                    current.old = current.old.saturating_sub(1);
+                    current.new = current.new.saturating_sub(1);
+                    line.to_string()
+                }
+            }
+        } else {
+            hunk = None;
+            file_header(line, theme)
+        };
+        if hunk.is_some_and(|h| h.is_done()) {
+            hunk = None;
+        }
+        res.push_str(&styled);
+        res.push('\n');
+    }
+
+    res.replace(RESET, &format!("{RESET}{}", theme.foreground.fg)) + RESET
+}
+
+/// the `@@ .. @@` ranges like a comment, the function name git puts after them as is
+fn hunk_header(line: &str, theme: &CustomTheme) -> String {
+    let end = line[2..].find("@@").map_or(line.len(), |i| i + 4);
+    let (ranges, context) = line.split_at(end);
+    format!("{}{ranges}{RESET}{context}", theme.comment.fg)
+}
+
+/// the lines between hunks: `diff`, `---`/`+++`, `index`, or a commit message with `git show`
+fn file_header(line: &str, theme: &CustomTheme) -> String {
+    const META: [&str; 9] = [
+        "index ",
+        "new file mode",
+        "deleted file mode",
+        "old mode",
+        "new mode",
+        "similarity index",
+        "rename ",
+        "copy ",
+        "Binary files",
+    ];
+    if line.starts_with("diff ") {
+        format!("{BOLD}{}{line}{RESET}", theme.keyword.fg)
+    } else if line.starts_with("--- ") || line.starts_with("+++ ") {
+        format!("{BOLD}{line}{RESET}")
+    } else if line.starts_with("commit ") {
+        format!("{}{line}{RESET}", theme.yellow.fg)
+    } else if META.iter().any(|meta| line.starts_with(meta)) {
+        format!("{}{line}{RESET}", theme.comment.fg)
+    } else {
+        line.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::themes::CustomTheme, Hunk, diff_to_ansi};
+
+    #[test]
+    fn file_headers_are_not_removed_lines() {
+        assert_eq!(Hunk::parse("@@ -3 +3,2 @@"), Hunk { old: 1, new: 2 });
+
+        let theme = CustomTheme::from("github");
+        let diff = "diff --git a/q.sql b/q.sql\n--- a/q.sql\n+++ b/q.sql\n@@ -1,2 +1,2 @@ main\n--- old comment\n+-- new comment\n select 1;\n";
+        let res = diff_to_ansi(diff, &theme);
+        let lines: Vec<_> = res.lines().collect();
+
+        assert!(!lines[1].contains(&theme.red.fg));
+        assert!(!lines[2].contains(&theme.green.fg));
+        assert!(lines[3].contains(&theme.comment.fg));
+        assert!(lines[4].contains(&format!("{}--- old comment", theme.red.fg)));
+        assert!(lines[5].contains(&format!("{}+-- new comment", theme.green.fg)));
+    }
+}
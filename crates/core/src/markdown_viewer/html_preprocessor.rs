@@ -14,6 +14,8 @@ pub struct ProcessingContext {
     rules: HashMap<String, ProcessorFn>,
     centered_lines: Vec<usize>,
     ensure_space_flag: bool,
+    // details without the `open` attribute only show their summary
+    collapse_details: bool,
 }
 
 type ProcessorFn = fn(ElementRef, &mut ProcessingContext);
@@ -41,13 +43,14 @@ macro_rules! heading_processor {
 }
 
 impl ProcessingContext {
-    fn new() -> Self {
+    fn new(collapse_details: bool) -> Self {
         let mut ctx = Self {
             output: String::new(),
             collect_stack: Vec::new(),
             rules: HashMap::new(),
             centered_lines: Vec::new(),
             ensure_space_flag: false,
+            collapse_details,
         };
 
         ctx.add_div_rules();
@@ -294,9 +297,31 @@ impl ProcessingContext {
 
     fn add_details_rules(&mut self) {
         self.rules.insert("details".to_string(), |element, ctx| {
+            let summary = element
+                .children()
+                .filter_map(ElementRef::wrap)
+                .find(|e| e.value().name() == "summary");
+            let collapsed = ctx.collapse_details && element.value().attr("open").is_none();
             let content = ctx.collect(element);
             ctx.ensure_empty_line();
 
+            if collapsed {
+                // the summary is the first line of the content
+                let hidden = content
+                    .trim()
+                    .lines()
+                    .skip(usize::from(summary.is_some()))
+                    .filter(|line| !line.trim().is_empty())
+                    .count();
+                let title = summary.map(|s| ctx.collect(s)).unwrap_or("Details".into());
+                ctx.write(&format!(
+                    "> ▶ {} *\\[{hidden} lines hidden\\]*",
+                    bold(title.trim())
+                ));
+                ctx.ensure_space();
+                return;
+            }
+
             let content = content
                 .trim()
                 .lines()
@@ -309,7 +334,7 @@ impl ProcessingContext {
 
         self.rules.insert("summary".to_string(), |element, ctx| {
             let content = ctx.collect(element);
-            ctx.write(&format!("▼ {}", content.trim()));
+            ctx.write(&format!("▼ {}", bold(content.trim())));
         });
     }
 
@@ -368,6 +393,14 @@ impl ProcessingContext {
     }
 }
 
+/// `**{s}**`, unless it already is
+fn bold(s: &str) -> String {
+    match s.starts_with("**") && s.ends_with("**") {
+        true => s.to_string(),
+        false => format!("**{s}**"),
+    }
+}
+
 /// # the tags handled:
 /// img, pre, code, a, blockquote, br, var, i, hr, b, strong, em, del, s, strike, h1-6, q, div, p, deatils, summary
 /// elements not included will remain the same.
@@ -401,12 +434,13 @@ impl ProcessingContext {
 /// p,div:      <block>,   {v}
 /// q:          <block>,   "{v}" (quoted)
 /// details:    <block>,   maps each line to "> {v}"
-/// summary:    <inline>,  "▼ {v}"
+///                        collapsed (without `open`): "> ▶ **{summary}** *[N lines hidden]*"
+/// summary:    <inline>,  "▼ **{v}**"
 ///
 /// # NOTE
 /// paragraphs don't enforce a \n\n like it should in markdown spec.
-pub fn process(markdown: &str) -> ProcessingResult {
-    let mut ctx = ProcessingContext::new();
+pub fn process(markdown: &str, collapse_details: bool) -> ProcessingResult {
+    let mut ctx = ProcessingContext::new(collapse_details);
 
     let escaped_markdown = ctx.escape_unknown_elements(markdown);
     let document = Html::parse_fragment(&escaped_markdown);
@@ -486,7 +520,7 @@ and one without href: bare link.
 > A quoted
 > paragraph.
 
-> ▼ Details title
+> ▼ **Details title**
 > Hidden *code* goes here.
 
 hello world
@@ -515,8 +549,20 @@ this sure is a good day
 Text after.
 "#;
 
-        let res = process(html);
+        let res = process(html, false);
 
         assert_eq!(res.content, expected);
     }
+
+    #[test]
+    fn collapses_closed_details() {
+        let html = "<details>\n<summary>More</summary>\n\none\n\ntwo\n</details>\n\n<details open>\n<summary>Open</summary>\nshown\n</details>";
+
+        let res = process(html, true);
+
+        assert_eq!(
+            res.content.trim(),
+            "> ▶ **More** *\\[2 lines hidden\\]*\n\n> ▼ **Open**\n> shown"
+        );
+    }
 }
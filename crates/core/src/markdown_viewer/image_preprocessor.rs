@@ -1,6 +1,6 @@
-use std::{collections::HashMap, fs, io::Write, path::Path};
+use std::{collections::HashMap, fs, io::Write, path::Path, time::Duration};
 
-use comrak::nodes::{AstNode, NodeValue};
+use comrak::nodes::{AstNode, NodeCodeBlock, NodeValue};
 use image::{DynamicImage, GenericImageView, ImageFormat};
 use itertools::Itertools;
 use rasteroid::{
@@ -15,11 +15,14 @@ use tempfile::NamedTempFile;
 
 use crate::{
     config::{McatConfig, MdImageRender},
-    converter::svg_to_image,
+    converter::{mermaid_to_image, svg_to_image},
     scrapy::{MediaScrapeOptions, scrape_biggest_media},
 };
 
-use super::render::UNDERLINE_OFF;
+use super::{render::UNDERLINE_OFF, themes::CustomTheme};
+
+const REMOTE_IMAGE_TIMEOUT: Duration = Duration::from_secs(10);
+const REMOTE_IMAGE_MAX_BYTES: u64 = 20_000_000;
 
 fn is_local_path(url: &str) -> bool {
     !url.starts_with("http://") && !url.starts_with("https://") && !url.starts_with("data:")
@@ -29,6 +32,7 @@ fn handle_local_image(
     path: &str,
     markdown_file_dir: Option<&Path>,
 ) -> Result<NamedTempFile, Box<dyn std::error::Error>> {
+    let path = path.strip_prefix("file://").unwrap_or(path);
     let original_path = Path::new(path);
 
     // Get the file extension
@@ -37,36 +41,25 @@ fn handle_local_image(
         .and_then(|ext| ext.to_str())
         .unwrap_or("");
 
-    // Try absolute or CWD-relative path first
-    if original_path.exists() {
-        let file_data = fs::read(original_path)?;
-        let mut temp_file = NamedTempFile::with_suffix(&format!(".{}", extension))?;
-        temp_file.write_all(&file_data)?;
-        temp_file.flush()?;
-        return Ok(temp_file);
-    }
-
-    // If that fails and we have a markdown file directory, try relative to that
-    if let Some(md_dir) = markdown_file_dir {
-        let relative_path = md_dir.join(path);
-        if relative_path.exists() {
-            let file_data = fs::read(&relative_path)?;
-            let mut temp_file = NamedTempFile::with_suffix(&format!(".{}", extension))?;
-            temp_file.write_all(&file_data)?;
-            temp_file.flush()?;
-            return Ok(temp_file);
-        } else {
-            return Err(format!(
-                "Local image file not found: {} (tried {} and {})",
-                path,
-                path,
-                relative_path.display()
-            )
-            .into());
-        }
+    // relative paths are relative to the markdown file, like in any markdown viewer.
+    // falls back to the CWD for files written against it
+    let resolved = match markdown_file_dir {
+        Some(md_dir) if original_path.is_relative() => md_dir.join(original_path),
+        _ => original_path.to_path_buf(),
+    };
+    let resolved = match resolved.exists() || !original_path.exists() {
+        true => resolved,
+        false => original_path.to_path_buf(),
+    };
+    if !resolved.exists() {
+        return Err(format!("Local image file not found: {}", resolved.display()).into());
     }
 
-    Err(format!("Local image file not found: {}", path).into())
+    let file_data = fs::read(&resolved)?;
+    let mut temp_file = NamedTempFile::with_suffix(&format!(".{}", extension))?;
+    temp_file.write_all(&file_data)?;
+    temp_file.flush()?;
+    Ok(temp_file)
 }
 
 pub struct ImagePreprocessor {
@@ -89,7 +82,9 @@ impl ImagePreprocessor {
                 InlineEncoder::Kitty => MdImageRender::All,
                 InlineEncoder::Iterm => MdImageRender::Small,
                 InlineEncoder::Sixel => MdImageRender::Small,
-                InlineEncoder::Ascii => MdImageRender::None,
+                InlineEncoder::Ascii | InlineEncoder::HalfBlock | InlineEncoder::Braille => {
+                    MdImageRender::None
+                }
             }
         };
         let markdown_dir = markdown_file_path.and_then(|p| p.parent());
@@ -98,11 +93,14 @@ impl ImagePreprocessor {
         scrape_opts.videos = false;
         scrape_opts.documents = false;
         scrape_opts.max_content_length = match render_mode {
-            MdImageRender::All => None,
+            MdImageRender::All => Some(REMOTE_IMAGE_MAX_BYTES),
             _ => Some(50_000), // filter complex images -- won't scale down good
         };
+        // a slow host shouldn't hold up the whole document, it keeps the placeholder
+        scrape_opts.timeout = Some(REMOTE_IMAGE_TIMEOUT);
+        // --header and --cookie are left out, they belong to the url mcat was given, not to image hosts
 
-        let items: Vec<(&ImageUrl, Vec<u8>, u32)> = urls
+        let mut items: Vec<(String, Vec<u8>, u32)> = urls
             .par_iter()
             .filter_map(|url| {
                 // fail everything early if needed.
@@ -120,36 +118,35 @@ impl ImagePreprocessor {
                 let (width, height) = img.dimensions();
                 let width = url.width.map(|v| v as u32).unwrap_or(width);
                 let height = url.height.map(|v| v as u32).unwrap_or(height);
-                let width_fm = if width as f32 > term_misc::get_wininfo().spx_width as f32 * 0.8 {
-                    "80%"
-                } else {
-                    &format!("{width}px")
-                };
-                let height_fm = if render_mode == MdImageRender::Small {
-                    let px = term_misc::dim_to_px("1c", term_misc::SizeDirection::Height)
-                        .unwrap_or_default()
-                        .saturating_sub(1); // it ceils, so we must make sure 1c
-                    &format!("{px}px")
-                } else if height as f32 > term_misc::get_wininfo().spx_height as f32 * 0.4 {
-                    "40%"
-                } else {
-                    &format!("{height}px")
-                };
+                let (img, new_width) =
+                    fit_image(&img, width, height, render_mode == MdImageRender::Small)?;
 
-                let (img, _, new_width, _) = img
-                    .resize_plus(Some(&width_fm), Some(&height_fm), false, false)
-                    .ok()?;
-
-                return Some((url, img, new_width));
+                Some((url.original_url.clone(), img, new_width))
             })
             .collect();
 
+        // mermaid diagrams, rendered with chromium. left as code blocks when it fails
+        if render_mode != MdImageRender::None {
+            let mut blocks = Vec::new();
+            extract_mermaid_blocks(node, &mut blocks);
+            let dark = !blocks.is_empty() && CustomTheme::from(conf.theme.as_str()).is_dark();
+            for source in blocks {
+                let Ok(img) = mermaid_to_image(&source, dark) else {
+                    continue;
+                };
+                let (width, height) = img.dimensions();
+                if let Some((img, new_width)) = fit_image(&img, width, height, false) {
+                    items.push((mermaid_key(&source), img, new_width));
+                }
+            }
+        }
+
         let mut mapper: HashMap<String, ImageElement> = HashMap::new();
-        for (i, (url, img, width)) in items.iter().enumerate() {
+        for (i, (key, img, width)) in items.iter().enumerate() {
             let mut buffer = Vec::new();
             if let Err(e) = inline_an_image(&img, &mut buffer, None, None, &conf.inline_encoder) {
                 if !conf.silent {
-                    eprintln!("Failed to encode image '{}': {}", url.original_url, e);
+                    eprintln!("Failed to encode image '{}': {}", key, e);
                 }
             } else {
                 let img_str = String::from_utf8(buffer).unwrap_or_default();
@@ -163,7 +160,7 @@ impl ImagePreprocessor {
                     ),
                     img: img_str,
                 };
-                mapper.insert(url.original_url.clone(), img);
+                mapper.insert(key.clone(), img);
             }
         }
 
@@ -171,6 +168,53 @@ impl ImagePreprocessor {
     }
 }
 
+/// resizes into the markdown, `small` keeps it a single line tall.
+/// returns the png and its new width
+fn fit_image(img: &DynamicImage, width: u32, height: u32, small: bool) -> Option<(Vec<u8>, u32)> {
+    let width_fm = if width as f32 > term_misc::get_wininfo().spx_width() as f32 * 0.8 {
+        "80%"
+    } else {
+        &format!("{width}px")
+    };
+    let height_fm = if small {
+        let px = term_misc::dim_to_px("1c", term_misc::SizeDirection::Height)
+            .unwrap_or_default()
+            .saturating_sub(1); // it ceils, so we must make sure 1c
+        &format!("{px}px")
+    } else if height as f32 > term_misc::get_wininfo().spx_height() as f32 * 0.4 {
+        "40%"
+    } else {
+        &format!("{height}px")
+    };
+
+    let (img, _, new_width, _) = img
+        .resize_plus(Some(width_fm), Some(height_fm), false, false)
+        .ok()?;
+    Some((img, new_width))
+}
+
+/// the key of a mermaid block inside of [`ImagePreprocessor::mapper`]
+pub fn mermaid_key(source: &str) -> String {
+    format!("mermaid:{source}")
+}
+
+pub fn is_mermaid(info: &str) -> bool {
+    info.split_whitespace().next() == Some("mermaid")
+}
+
+fn extract_mermaid_blocks<'a>(node: &'a AstNode<'a>, blocks: &mut Vec<String>) {
+    if let NodeValue::CodeBlock(NodeCodeBlock { info, literal, .. }) = &node.data.borrow().value
+        && is_mermaid(info)
+        && !blocks.contains(literal)
+    {
+        blocks.push(literal.clone());
+    }
+
+    for child in node.children() {
+        extract_mermaid_blocks(child, blocks);
+    }
+}
+
 fn create_placeholder(img: &str, id: usize, inline_encoder: &InlineEncoder, width: u32) -> String {
     let fg_color = 16 + (id % 216);
     let bg_color = 16 + ((id / 216) % 216);
@@ -0,0 +1,438 @@
+use std::{iter::Peekable, str::Chars};
+
+// a small subset of latex, enough for the math people usually put in a readme.
+// anything unknown is left as it was written
+
+fn symbol(name: &str) -> Option<&'static str> {
+    let s = match name {
+        // greek
+        "alpha" => "α",
+        "beta" => "β",
+        "gamma" => "γ",
+        "delta" => "δ",
+        "epsilon" => "ϵ",
+        "varepsilon" => "ε",
+        "zeta" => "ζ",
+        "eta" => "η",
+        "theta" => "θ",
+        "vartheta" => "ϑ",
+        "iota" => "ι",
+        "kappa" => "κ",
+        "lambda" => "λ",
+        "mu" => "μ",
+        "nu" => "ν",
+        "xi" => "ξ",
+        "omicron" => "ο",
+        "pi" => "π",
+        "varpi" => "ϖ",
+        "rho" => "ρ",
+        "varrho" => "ϱ",
+        "sigma" => "σ",
+        "varsigma" => "ς",
+        "tau" => "τ",
+        "upsilon" => "υ",
+        "phi" => "ϕ",
+        "varphi" => "φ",
+        "chi" => "χ",
+        "psi" => "ψ",
+        "omega" => "ω",
+        "Gamma" => "Γ",
+        "Delta" => "Δ",
+        "Theta" => "Θ",
+        "Lambda" => "Λ",
+        "Xi" => "Ξ",
+        "Pi" => "Π",
+        "Sigma" => "Σ",
+        "Upsilon" => "Υ",
+        "Phi" => "Φ",
+        "Psi" => "Ψ",
+        "Omega" => "Ω",
+        // operators and relations
+        "times" => "×",
+        "cdot" => "·",
+        "pm" => "±",
+        "mp" => "∓",
+        "div" => "÷",
+        "ast" => "∗",
+        "star" => "⋆",
+        "circ" => "∘",
+        "leq" | "le" => "≤",
+        "geq" | "ge" => "≥",
+        "neq" | "ne" => "≠",
+        "ll" => "≪",
+        "gg" => "≫",
+        "approx" => "≈",
+        "equiv" => "≡",
+        "sim" => "∼",
+        "simeq" => "≃",
+        "cong" => "≅",
+        "propto" => "∝",
+        "sum" => "∑",
+        "prod" => "∏",
+        "coprod" => "∐",
+        "int" => "∫",
+        "iint" => "∬",
+        "iiint" => "∭",
+        "oint" => "∮",
+        "partial" => "∂",
+        "nabla" => "∇",
+        "infty" => "∞",
+        // sets and logic
+        "in" => "∈",
+        "notin" => "∉",
+        "ni" => "∋",
+        "subset" => "⊂",
+        "subseteq" => "⊆",
+        "supset" => "⊃",
+        "supseteq" => "⊇",
+        "cup" => "∪",
+        "cap" => "∩",
+        "setminus" => "∖",
+        "emptyset" | "varnothing" => "∅",
+        "forall" => "∀",
+        "exists" => "∃",
+        "nexists" => "∄",
+        "neg" | "lnot" => "¬",
+        "land" | "wedge" => "∧",
+        "lor" | "vee" => "∨",
+        "oplus" => "⊕",
+        "otimes" => "⊗",
+        "top" => "⊤",
+        "bot" | "perp" => "⊥",
+        "vdash" => "⊢",
+        "models" => "⊨",
+        // arrows
+        "to" | "rightarrow" => "→",
+        "gets" | "leftarrow" => "←",
+        "leftrightarrow" => "↔",
+        "Rightarrow" => "⇒",
+        "Leftarrow" => "⇐",
+        "Leftrightarrow" | "iff" => "⇔",
+        "implies" => "⟹",
+        "impliedby" => "⟸",
+        "mapsto" => "↦",
+        "uparrow" => "↑",
+        "downarrow" => "↓",
+        // misc
+        "cdots" => "⋯",
+        "ldots" | "dots" => "…",
+        "vdots" => "⋮",
+        "ddots" => "⋱",
+        "angle" => "∠",
+        "parallel" => "∥",
+        "mid" => "∣",
+        "prime" => "′",
+        "degree" => "°",
+        "hbar" => "ℏ",
+        "ell" => "ℓ",
+        "Re" => "ℜ",
+        "Im" => "ℑ",
+        "aleph" => "ℵ",
+        "langle" => "⟨",
+        "rangle" => "⟩",
+        "lfloor" => "⌊",
+        "rfloor" => "⌋",
+        "lceil" => "⌈",
+        "rceil" => "⌉",
+        "lbrace" => "{",
+        "rbrace" => "}",
+        "vert" => "|",
+        "Vert" => "‖",
+        "quad" => "  ",
+        "qquad" => "    ",
+        // named functions are just upright text
+        "sin" => "sin",
+        "cos" => "cos",
+        "tan" => "tan",
+        "cot" => "cot",
+        "sec" => "sec",
+        "csc" => "csc",
+        "arcsin" => "arcsin",
+        "arccos" => "arccos",
+        "arctan" => "arctan",
+        "sinh" => "sinh",
+        "cosh" => "cosh",
+        "tanh" => "tanh",
+        "log" => "log",
+        "ln" => "ln",
+        "exp" => "exp",
+        "lim" => "lim",
+        "sup" => "sup",
+        "inf" => "inf",
+        "max" => "max",
+        "min" => "min",
+        "det" => "det",
+        "gcd" => "gcd",
+        "deg" => "deg",
+        "dim" => "dim",
+        "ker" => "ker",
+        "arg" => "arg",
+        "mod" | "bmod" => "mod",
+        _ => return None,
+    };
+    Some(s)
+}
+
+fn superscript(c: char) -> Option<char> {
+    let s = match c {
+        '0' => '⁰',
+        '1' => '¹',
+        '2' => '²',
+        '3' => '³',
+        '4' => '⁴',
+        '5' => '⁵',
+        '6' => '⁶',
+        '7' => '⁷',
+        '8' => '⁸',
+        '9' => '⁹',
+        '+' => '⁺',
+        '-' | '−' => '⁻',
+        '=' => '⁼',
+        '(' => '⁽',
+        ')' => '⁾',
+        'a' => 'ᵃ',
+        'b' => 'ᵇ',
+        'c' => 'ᶜ',
+        'd' => 'ᵈ',
+        'e' => 'ᵉ',
+        'f' => 'ᶠ',
+        'g' => 'ᵍ',
+        'h' => 'ʰ',
+        'i' => 'ⁱ',
+        'j' => 'ʲ',
+        'k' => 'ᵏ',
+        'l' => 'ˡ',
+        'm' => 'ᵐ',
+        'n' => 'ⁿ',
+        'o' => 'ᵒ',
+        'p' => 'ᵖ',
+        'r' => 'ʳ',
+        's' => 'ˢ',
+        't' => 'ᵗ',
+        'u' => 'ᵘ',
+        'v' => 'ᵛ',
+        'w' => 'ʷ',
+        'x' => 'ˣ',
+        'y' => 'ʸ',
+        'z' => 'ᶻ',
+        'T' => 'ᵀ',
+        '′' => '′',
+        '∗' | '*' => '*',
+        _ => return None,
+    };
+    Some(s)
+}
+
+fn subscript(c: char) -> Option<char> {
+    let s = match c {
+        '0' => '₀',
+        '1' => '₁',
+        '2' => '₂',
+        '3' => '₃',
+        '4' => '₄',
+        '5' => '₅',
+        '6' => '₆',
+        '7' => '₇',
+        '8' => '₈',
+        '9' => '₉',
+        '+' => '₊',
+        '-' | '−' => '₋',
+        '=' => '₌',
+        '(' => '₍',
+        ')' => '₎',
+        'a' => 'ₐ',
+        'e' => 'ₑ',
+        'h' => 'ₕ',
+        'i' => 'ᵢ',
+        'j' => 'ⱼ',
+        'k' => 'ₖ',
+        'l' => 'ₗ',
+        'm' => 'ₘ',
+        'n' => 'ₙ',
+        'o' => 'ₒ',
+        'p' => 'ₚ',
+        'r' => 'ᵣ',
+        's' => 'ₛ',
+        't' => 'ₜ',
+        'u' => 'ᵤ',
+        'v' => 'ᵥ',
+        'x' => 'ₓ',
+        _ => return None,
+    };
+    Some(s)
+}
+
+fn blackboard(c: char) -> Option<char> {
+    let s = match c {
+        'C' => 'ℂ',
+        'H' => 'ℍ',
+        'N' => 'ℕ',
+        'P' => 'ℙ',
+        'Q' => 'ℚ',
+        'R' => 'ℝ',
+        'Z' => 'ℤ',
+        _ => return None,
+    };
+    Some(s)
+}
+
+fn vulgar_fraction(num: &str, den: &str) -> Option<&'static str> {
+    let s = match (num, den) {
+        ("1", "2") => "½",
+        ("1", "3") => "⅓",
+        ("2", "3") => "⅔",
+        ("1", "4") => "¼",
+        ("3", "4") => "¾",
+        ("1", "5") => "⅕",
+        ("1", "6") => "⅙",
+        ("1", "8") => "⅛",
+        _ => return None,
+    };
+    Some(s)
+}
+
+/// converts latex math into unicode text, e.g. `\alpha^2 \leq \frac{1}{2}` into `α² ≤ ½`.
+/// commands it doesn't know are kept verbatim
+pub fn latex_to_unicode(latex: &str) -> String {
+    let mut chars = latex.chars().peekable();
+    let out = parse_seq(&mut chars, false);
+    out.trim().to_string()
+}
+
+/// parses until the end of the input, or a closing brace when `in_group`
+fn parse_seq(chars: &mut Peekable<Chars>, in_group: bool) -> String {
+    let mut out = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '}' if in_group => return out,
+            '{' => out.push_str(&parse_seq(chars, true)),
+            '\\' => out.push_str(&parse_command(chars)),
+            '^' => {
+                let arg = parse_arg(chars);
+                out.push_str(&script(&arg, '^', superscript));
+            }
+            '_' => {
+                let arg = parse_arg(chars);
+                out.push_str(&script(&arg, '_', subscript));
+            }
+            '~' => out.push(' '),
+            '\'' => out.push('′'),
+            '-' => out.push('−'),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// a single argument, a `{group}`, a command or a single char
+fn parse_arg(chars: &mut Peekable<Chars>) -> String {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    match chars.next() {
+        Some('{') => parse_seq(chars, true),
+        Some('\\') => parse_command(chars),
+        Some(c) => c.to_string(),
+        None => String::new(),
+    }
+}
+
+/// an optional `[arg]`
+fn parse_optional(chars: &mut Peekable<Chars>) -> Option<String> {
+    chars.next_if_eq(&'[')?;
+    let mut arg = String::new();
+    for c in chars.by_ref() {
+        if c == ']' {
+            break;
+        }
+        arg.push(c);
+    }
+    Some(latex_to_unicode(&arg))
+}
+
+fn parse_command(chars: &mut Peekable<Chars>) -> String {
+    let mut name = String::new();
+    while let Some(c) = chars.next_if(|c| c.is_ascii_alphabetic()) {
+        name.push(c);
+    }
+    if name.is_empty() {
+        return match chars.next() {
+            Some(',' | ';' | ':' | ' ') => " ".into(),
+            Some('!') => String::new(),
+            Some('\\') => "\n".into(),
+            Some(c) => c.to_string(),
+            None => "\\".into(),
+        };
+    }
+
+    if let Some(s) = symbol(&name) {
+        return s.to_string();
+    }
+
+    match name.as_str() {
+        "frac" | "dfrac" | "tfrac" => {
+            let num = parse_arg(chars);
+            let den = parse_arg(chars);
+            if let Some(f) = vulgar_fraction(&num, &den) {
+                return f.to_string();
+            }
+            format!("{}/{}", wrap_compound(&num), wrap_compound(&den))
+        }
+        "sqrt" => {
+            let root = match parse_optional(chars).as_deref() {
+                None | Some("2") => "√".to_string(),
+                Some("3") => "∛".to_string(),
+                Some("4") => "∜".to_string(),
+                Some(n) => format!("{}√", script(n, '^', superscript)),
+            };
+            format!("{root}{}", wrap_compound(&parse_arg(chars)))
+        }
+        "mathbb" => parse_arg(chars)
+            .chars()
+            .map(|c| blackboard(c).unwrap_or(c))
+            .collect(),
+        "text" | "textrm" | "textbf" | "textit" | "mathrm" | "mathit" | "mathbf" | "mathsf"
+        | "mathtt" | "mathcal" | "operatorname" | "mbox" | "boldsymbol" => parse_arg(chars),
+        "overline" | "bar" => parse_arg(chars)
+            .chars()
+            .flat_map(|c| [c, '\u{305}'])
+            .collect(),
+        "hat" => format!("{}\u{302}", parse_arg(chars)),
+        "tilde" => format!("{}\u{303}", parse_arg(chars)),
+        "vec" => format!("{}\u{20d7}", parse_arg(chars)),
+        "dot" => format!("{}\u{307}", parse_arg(chars)),
+        "ddot" => format!("{}\u{308}", parse_arg(chars)),
+        // sizing only, the delimiter itself follows
+        "left" | "right" | "big" | "Big" | "bigg" | "Bigg" | "displaystyle" => {
+            // `\left.` is an invisible delimiter
+            chars.next_if_eq(&'.');
+            String::new()
+        }
+        _ => {
+            let mut unknown = format!("\\{name}");
+            while chars.next_if_eq(&'{').is_some() {
+                unknown.push_str(&format!("{{{}}}", parse_seq(chars, true)));
+            }
+            unknown
+        }
+    }
+}
+
+/// `x^{2}` into `x²`, `x^{q}` into `x^q` when there is no unicode version
+fn script(arg: &str, marker: char, map: fn(char) -> Option<char>) -> String {
+    if let Some(mapped) = arg.chars().map(map).collect::<Option<String>>() {
+        return mapped;
+    }
+    format!("{marker}{}", wrap_compound(arg))
+}
+
+/// puts parens around anything longer than a single term
+fn wrap_compound(s: &str) -> String {
+    let s = s.trim();
+    if s.chars().count() <= 1
+        || s.chars().all(|c| c.is_alphanumeric() || c == '.')
+        || (s.starts_with('(') && s.ends_with(')'))
+    {
+        s.to_string()
+    } else {
+        format!("({s})")
+    }
+}
@@ -1,30 +1,52 @@
+pub mod diff;
 pub mod html_preprocessor;
 pub mod image_preprocessor;
+pub mod math;
 pub mod render;
+pub mod stats;
+pub mod syntaxes;
 pub mod themes;
+pub mod toc;
 pub mod utils;
 
 use comrak::{
-    Arena, ComrakOptions, ComrakPlugins, markdown_to_html_with_plugins,
+    Arena, ComrakOptions, ComrakPlugins, markdown_to_html_with_plugins, nodes::NodeValue,
     plugins::syntect::SyntectAdapterBuilder,
 };
 use image_preprocessor::ImagePreprocessor;
 use rasteroid::term_misc::{self, break_size_string};
 use render::{AnsiContext, RESET, parse_node};
-use syntect::{highlighting::ThemeSet, parsing::SyntaxSet};
+use stats::{MdStats, show_footer};
+use syntect::highlighting::ThemeSet;
 use themes::CustomTheme;
+use toc::{render_toc, toc_entries};
 use utils::limit_newlines;
 
-use crate::{UnwrapOrExit, config::McatConfig};
+use crate::{
+    UnwrapOrExit,
+    config::{AlwaysOrNever, McatConfig},
+};
 use std::path::Path;
 
 pub fn md_to_ansi(md: &str, config: &McatConfig, markdown_file_path: Option<&Path>) -> String {
-    let res = &html_preprocessor::process(md);
+    md_to_ansi_with_width(md, config, markdown_file_path, None)
+}
+
+/// same as [`md_to_ansi`], wrapped to `width` columns instead of the terminal width
+pub fn md_to_ansi_with_width(
+    md: &str,
+    config: &McatConfig,
+    markdown_file_path: Option<&Path>,
+    width: Option<usize>,
+) -> String {
+    let res = &html_preprocessor::process(md, config.collapse_details);
     let md = &res.content;
 
     let arena = Arena::new();
-    let opts = comrak_options();
+    let opts = comrak_options(!config.no_emoji);
     let root = comrak::parse_document(&arena, md, &opts);
+    let stats = MdStats::from_ast(root);
+    let toc = config.toc.map(|depth| toc_entries(root, depth));
 
     // changing to forced inline in case of images rendered
     let _ = term_misc::init_wininfo(
@@ -35,15 +57,28 @@ pub fn md_to_ansi(md: &str, config: &McatConfig, markdown_file_path: Option<&Pat
         true,
     );
 
-    let ps = SyntaxSet::load_defaults_newlines();
+    let langs: Vec<String> = root
+        .descendants()
+        .filter_map(|node| match &node.data.borrow().value {
+            NodeValue::CodeBlock(block) => Some(block.info.clone()),
+            _ => None,
+        })
+        .collect();
+    let ps = syntaxes::syntax_set_for(langs.iter().map(String::as_str));
     let theme = CustomTheme::from(config.theme.as_ref());
+    let code_theme = CustomTheme::from(config.code_theme()).to_syntect_theme();
     let image_preprocessor = ImagePreprocessor::new(root, config, markdown_file_path);
     let mut ctx = AnsiContext {
         ps,
         theme,
+        code_theme,
         hide_line_numbers: config.no_linenumbers,
+        line_numbers_min: config.linenumbers_min,
+        show_front_matter: config.show_front_matter,
+        table_mode: config.table_mode,
+        tab_width: config.tab_width,
         centered_lines: &res.centered_lines,
-        term_width: term_misc::get_wininfo().sc_width as usize,
+        term_width: width.unwrap_or(term_misc::get_wininfo().sc_width as usize),
         image_preprocessor: &image_preprocessor,
 
         blockquote_fenced_offset: None,
@@ -53,14 +88,19 @@ pub fn md_to_ansi(md: &str, config: &McatConfig, markdown_file_path: Option<&Pat
         under_header: false,
         force_simple_code_block: 0,
         list_depth: 0,
+        list_counters: Vec::new(),
     };
 
     let mut output = String::new();
     output.push_str(&ctx.theme.foreground.fg);
+    if let Some(toc) = toc.filter(|toc| !toc.is_empty()) {
+        output.push_str(&render_toc(&toc, &ctx.theme, markdown_file_path));
+        output.push_str("\n\n");
+    }
     output.push_str(&parse_node(root, &mut ctx).trim_matches('\n'));
 
     // making sure its wrapped to fit into the termianl size
-    let lines: Vec<String> = textwrap::wrap(&output, term_misc::get_wininfo().sc_width as usize)
+    let lines: Vec<String> = textwrap::wrap(&output, ctx.term_width)
         .into_iter()
         .map(|cow| cow.into_owned())
         .collect();
@@ -71,25 +111,97 @@ pub fn md_to_ansi(md: &str, config: &McatConfig, markdown_file_path: Option<&Pat
     // force at max 2 \n at a row (we're adding newlines based on sourcepos)
     let mut res = limit_newlines(&res).to_string();
 
+    let footer = show_footer(&config.stats, true)
+        .then(|| format!("\n\n{}{}{RESET}", ctx.theme.comment.fg, stats.footer()));
+
     // replace images
     for (_, img) in image_preprocessor.mapper {
         img.insert_into_text(&mut res);
     }
+    res.push_str(&footer.unwrap_or_default());
     res
 }
 
-pub fn md_to_html(markdown: &str, style: Option<&str>) -> String {
-    let options = comrak_options();
+/// the [`MdStats`] of `md` without rendering it, for the uncolored output
+pub fn md_stats(md: &str, config: &McatConfig) -> MdStats {
+    let res = html_preprocessor::process(md, config.collapse_details);
+    let arena = Arena::new();
+    let opts = comrak_options(!config.no_emoji);
+    MdStats::from_ast(comrak::parse_document(&arena, &res.content, &opts))
+}
+
+/// what `--list-themes` renders with each theme
+pub const THEME_SAMPLE: &str = r#"# Heading
+Some *emphasis*, **strong** text, `inline code` and a [link](https://github.com).
+
+```rust
+fn main() {
+    let greeting = "hello";
+    println!("{greeting}, {}!", 42);
+}
+```
+
+| Theme | Dark |
+|-------|------|
+| nord  | yes  |
+
+- a list
+  - nested
+- [x] done
+
+> [!NOTE]
+> an alert
+"#;
+
+/// `sample` rendered once with every built-in theme, under its name
+pub fn theme_gallery(sample: &str, config: &McatConfig) -> String {
+    let mut config = config.clone();
+    config.code_theme = None;
+    config.stats = AlwaysOrNever::Never;
+    themes::THEMES
+        .iter()
+        .map(|name| {
+            config.theme = name.to_string();
+            let label = format!("{}{name}{RESET}", CustomTheme::from(*name).keyword.fg);
+            format!("{label}\n{}{RESET}\n", md_to_ansi(sample, &config, None))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// the raw text of the `n`th (1-based) code block of `md`, in document order
+pub fn code_block(md: &str, n: usize, config: &McatConfig) -> Option<String> {
+    let arena = Arena::new();
+    let root = comrak::parse_document(&arena, md, &comrak_options(!config.no_emoji));
+    root.descendants()
+        .filter_map(|node| match &node.data.borrow().value {
+            NodeValue::CodeBlock(block) => Some(block.literal.clone()),
+            _ => None,
+        })
+        .nth(n.checked_sub(1)?)
+}
+
+/// `style` is the theme of the page, and `code_style` of its code blocks (`style` when `None`).
+/// without a style the html is left plain
+pub fn md_to_html(
+    markdown: &str,
+    style: Option<&str>,
+    code_style: Option<&str>,
+    emoji: bool,
+) -> String {
+    let options = comrak_options(emoji);
 
     let theme = CustomTheme::from(style.unwrap_or_default());
+    let code_theme = CustomTheme::from(code_style.or(style).unwrap_or_default());
     let mut theme_set = ThemeSet::load_defaults();
     let mut plugins = ComrakPlugins::default();
     theme_set
         .themes
-        .insert("dark".to_string(), theme.to_syntect_theme());
+        .insert("dark".to_string(), code_theme.to_syntect_theme());
     let adapter = SyntectAdapterBuilder::new()
         .theme("dark")
         .theme_set(theme_set)
+        .syntax_set(syntaxes::extended().clone())
         .build();
     if style.is_some() {
         plugins.render.codefence_syntax_highlighter = Some(&adapter);
@@ -121,7 +233,7 @@ pub fn md_to_html(markdown: &str, style: Option<&str>) -> String {
     }
 }
 
-fn comrak_options<'a>() -> ComrakOptions<'a> {
+fn comrak_options<'a>(emoji: bool) -> ComrakOptions<'a> {
     let mut options = ComrakOptions::default();
     // Enable extensions
     options.extension.strikethrough = true;
@@ -133,10 +245,14 @@ fn comrak_options<'a>() -> ComrakOptions<'a> {
     options.extension.tasklist = true;
     options.extension.description_lists = true;
     options.extension.math_code = true;
+    options.extension.math_dollars = true;
     options.extension.alerts = true;
     options.extension.wikilinks_title_after_pipe = true;
     options.extension.spoiler = true;
     options.extension.multiline_block_quotes = true;
+    options.extension.front_matter_delimiter = Some("---".to_string());
+    // `:rocket:` into 🚀, code spans and blocks are left alone
+    options.extension.shortcodes = emoji;
 
     // Parsing options
     options.parse.smart = true;
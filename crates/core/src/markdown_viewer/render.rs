@@ -1,20 +1,28 @@
 use comrak::nodes::{
     AstNode, NodeAlert, NodeCode, NodeCodeBlock, NodeHeading, NodeHtmlBlock, NodeLink, NodeMath,
-    NodeValue, NodeWikiLink,
+    NodeShortCode, NodeValue, NodeWikiLink,
 };
 use itertools::Itertools;
-use syntect::parsing::SyntaxSet;
-
-use crate::markdown_viewer::utils::{get_title_box, string_len, trim_ansi_string, wrap_lines};
+use syntect::{highlighting::Theme, parsing::SyntaxSet};
+
+use crate::{
+    config::TableMode,
+    glyphs::Glyph,
+    markdown_viewer::utils::{
+        expand_tabs, front_matter_fields, get_title_box, string_len, trim_ansi_string,
+        truncate_ansi, wrap_lines,
+    },
+};
 
 use super::{
-    image_preprocessor::ImagePreprocessor,
+    image_preprocessor::{ImagePreprocessor, is_mermaid, mermaid_key},
+    math::latex_to_unicode,
     themes::CustomTheme,
     utils::{format_code_full, format_code_simple, format_tb, limit_newlines, wrap_char_based},
 };
 
 pub const RESET: &str = "\x1B[0m";
-const BOLD: &str = "\x1B[1m";
+pub const BOLD: &str = "\x1B[1m";
 const ITALIC: &str = "\x1B[3m";
 const UNDERLINE: &str = "\x1B[4m";
 const STRIKETHROUGH: &str = "\x1B[9m";
@@ -26,9 +34,17 @@ pub const UNDERLINE_OFF: &str = "\x1B[24m";
 const INDENT: usize = 2;
 
 pub struct AnsiContext<'a> {
-    pub ps: SyntaxSet,
+    pub ps: &'a SyntaxSet,
     pub theme: CustomTheme,
+    /// highlights code blocks, from `--code-theme` (or the main theme)
+    pub code_theme: Theme,
     pub hide_line_numbers: bool,
+    /// code blocks longer than this get line numbers
+    pub line_numbers_min: usize,
+    /// the front matter as it's written, instead of a title banner
+    pub show_front_matter: bool,
+    pub table_mode: TableMode,
+    pub tab_width: usize,
     pub centered_lines: &'a [usize],
     pub term_width: usize,
     pub image_preprocessor: &'a ImagePreprocessor,
@@ -40,6 +56,8 @@ pub struct AnsiContext<'a> {
     pub under_header: bool,
     pub force_simple_code_block: usize,
     pub list_depth: usize,
+    // the number of the next item, per open list
+    pub list_counters: Vec<usize>,
 }
 
 impl<'a> AnsiContext<'a> {
@@ -102,11 +120,12 @@ pub fn parse_node<'a>(node: &'a AstNode<'a>, ctx: &mut AnsiContext) -> String {
         NodeValue::Alert(_) => render_alert(node, ctx),
         NodeValue::FootnoteDefinition(_) => render_footnote_def(node, ctx),
         NodeValue::FootnoteReference(_) => render_footnote_ref(node, ctx),
+        NodeValue::Math(_) => render_math(node, ctx),
         // leave as is
         NodeValue::Text(literal) => literal.to_owned(),
         NodeValue::Raw(literal) => literal.to_owned(),
         NodeValue::SoftBreak => " ".to_owned(),
-        NodeValue::Math(NodeMath { literal, .. }) => literal.to_owned(),
+        NodeValue::ShortCode(NodeShortCode { emoji, .. }) => emoji.to_owned(),
         NodeValue::LineBreak => "".to_owned(),
         NodeValue::TableRow(_) => String::new(),
         NodeValue::TableCell => String::new(),
@@ -130,12 +149,51 @@ fn render_document<'a>(node: &'a AstNode<'a>, ctx: &mut AnsiContext) -> String {
         .collect()
 }
 
-fn render_front_matter<'a>(node: &'a AstNode<'a>, _ctx: &mut AnsiContext) -> String {
+/// the title of the front matter in a box, with its author and date under it.
+/// the rest of the keys are dropped
+fn render_front_matter<'a>(node: &'a AstNode<'a>, ctx: &mut AnsiContext) -> String {
     let NodeValue::FrontMatter(ref literal) = node.data.borrow().value else {
         panic!()
     };
+    if ctx.show_front_matter {
+        return literal.to_owned();
+    }
 
-    literal.to_owned()
+    let fields = front_matter_fields(literal);
+    let field = |key: &str| {
+        fields
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    };
+    let mut banner = field("title")
+        .map(|title| title_box(title, ctx))
+        .unwrap_or_default();
+
+    let byline = [field("author"), field("date")]
+        .into_iter()
+        .flatten()
+        .join(" · ");
+    if !byline.is_empty() {
+        let spaces = " ".repeat(ctx.term_width.saturating_sub(string_len(&byline)) / 2);
+        let comment = &ctx.theme.comment.fg;
+        banner.push_str(&format!("{spaces}{comment}{byline}{RESET}\n"));
+    }
+    banner
+}
+
+fn title_box(title: &str, ctx: &AnsiContext) -> String {
+    let text_size = string_len(title);
+    let border_width = text_size + 4;
+    let center_padding = ctx.term_width.saturating_sub(border_width) / 2;
+
+    let fg_yellow = ctx.theme.yellow.fg.clone();
+    let border_line = "─".repeat(border_width);
+    let spaces = " ".repeat(center_padding);
+
+    format!(
+        "{spaces}┌{border_line}┐\n{spaces}│  {fg_yellow}{BOLD}{title}{RESET}  │\n{spaces}└{border_line}┘\n"
+    )
 }
 
 fn render_footnote_def<'a>(node: &'a AstNode<'a>, ctx: &mut AnsiContext) -> String {
@@ -221,12 +279,14 @@ fn render_block_quote<'a>(node: &'a AstNode<'a>, ctx: &mut AnsiContext) -> Strin
 }
 
 fn render_list<'a>(node: &'a AstNode<'a>, ctx: &mut AnsiContext) -> String {
-    let NodeValue::List(..) = node.data.borrow().value else {
+    let NodeValue::List(ref list) = node.data.borrow().value else {
         panic!()
     };
 
     ctx.list_depth += 1;
+    ctx.list_counters.push(list.start);
     let content = collect(node, ctx);
+    ctx.list_counters.pop();
     ctx.list_depth -= 1;
     let content = if ctx.should_indent() {
         wrap_lines(&content, true, INDENT, "", "  ") // 2 space extra because of the bullet
@@ -247,14 +307,21 @@ fn render_item<'a>(node: &'a AstNode<'a>, ctx: &mut AnsiContext) -> String {
     };
 
     let yellow = ctx.theme.yellow.fg.clone();
+    // counted before collecting, nested lists have their own counter
+    let index = match ctx.list_counters.last_mut() {
+        Some(counter) => {
+            *counter += 1;
+            *counter - 1
+        }
+        None => item.start,
+    };
     let content = collect(node, ctx);
     let content = content.trim();
     let depth = ctx.list_depth - 1;
 
-    let bullets = ["●", "○", "◆", "◇"];
     let bullet = match item.list_type {
-        comrak::nodes::ListType::Bullet => bullets[depth % 4],
-        comrak::nodes::ListType::Ordered => &format!("{}.", item.start),
+        comrak::nodes::ListType::Bullet => Glyph::Bullet(depth).get(),
+        comrak::nodes::ListType::Ordered => &format!("{}.", index),
     };
 
     format!(
@@ -272,13 +339,13 @@ fn render_task_item<'a>(node: &'a AstNode<'a>, ctx: &mut AnsiContext) -> String
     let content = collect(node, ctx);
     let content = content.trim();
     let (icon, colour) = match task.map(|c| c.to_ascii_lowercase()) {
-        Some('x') => ("󰱒", &ctx.theme.green.fg),
-        Some('-') | Some('~') => ("󰛲", &ctx.theme.yellow.fg),
-        Some('!') => ("󰳤", &ctx.theme.red.fg),
-        _ => ("󰄱", &ctx.theme.red.fg),
+        Some('x') => (Glyph::TaskDone, &ctx.theme.green.fg),
+        Some('-') | Some('~') => (Glyph::TaskPartial, &ctx.theme.yellow.fg),
+        Some('!') => (Glyph::TaskImportant, &ctx.theme.red.fg),
+        _ => (Glyph::TaskTodo, &ctx.theme.red.fg),
     };
 
-    format!("{offset}{colour}{icon}{RESET}  {content}\n")
+    format!("{offset}{colour}{}{RESET}  {content}\n", icon.get())
 }
 
 fn render_code_block<'a>(node: &'a AstNode<'a>, ctx: &mut AnsiContext) -> String {
@@ -291,10 +358,22 @@ fn render_code_block<'a>(node: &'a AstNode<'a>, ctx: &mut AnsiContext) -> String
         panic!()
     };
 
+    if is_mermaid(info)
+        && let Some(img) = ctx.image_preprocessor.mapper.get(&mermaid_key(literal))
+        && img.is_ok
+    {
+        return format!("\n\n{}\n\n", img.placeholder);
+    }
+
     let info = if info.trim().is_empty() { "text" } else { info };
+    // so the wrapping and the gutter agree with how wide the line is
+    let literal = &expand_tabs(literal, ctx.tab_width);
 
     // force_simple_code_block is a number because it may be recursive
-    if literal.lines().count() <= 10 || ctx.force_simple_code_block > 0 || ctx.hide_line_numbers {
+    if literal.lines().count() <= ctx.line_numbers_min
+        || ctx.force_simple_code_block > 0
+        || ctx.hide_line_numbers
+    {
         let indent = if ctx.should_indent() { INDENT } else { 0 };
         format_code_simple(literal, info, ctx, indent)
     } else {
@@ -308,17 +387,7 @@ fn render_html_block<'a>(node: &'a AstNode<'a>, ctx: &mut AnsiContext) -> String
     };
 
     if let Some(title) = get_title_box(literal) {
-        let text_size = string_len(title);
-        let border_width = text_size + 4;
-        let center_padding = (ctx.term_width - border_width) / 2;
-
-        let fg_yellow = ctx.theme.yellow.fg.clone();
-        let border_line = "─".repeat(border_width);
-        let spaces = " ".repeat(center_padding);
-
-        return format!(
-            "{spaces}┌{border_line}┐\n{spaces}│  {fg_yellow}{BOLD}{title}{RESET}  │\n{spaces}└{border_line}┘\n"
-        );
+        return title_box(title, ctx);
     }
 
     let sps = node.data.borrow().sourcepos;
@@ -385,15 +454,7 @@ fn render_heading<'a>(node: &'a AstNode<'a>, ctx: &mut AnsiContext) -> String {
     ctx.under_header = true;
     let content = collect(node, ctx);
     let content = content.trim();
-    let content = match level {
-        1 => format!(" 󰎤 {content}"),
-        2 => format!(" 󰎧 {content}"),
-        3 => format!(" 󰎬 {content}"),
-        4 => format!(" 󰎮 {content}"),
-        5 => format!(" 󰎰 {content}"),
-        6 => format!(" 󰎵 {content}"),
-        _ => unreachable!(),
-    };
+    let content = format!(" {} {content}", Glyph::Heading(level).get());
     let bg = &ctx.theme.keyword_bg.bg;
     let main_color = &ctx.theme.keyword.fg;
     let content = content.replace(RESET, &format!("{RESET}{bg}"));
@@ -452,8 +513,11 @@ fn render_table<'a>(node: &'a AstNode<'a>, ctx: &mut AnsiContext) -> String {
 
         for cell_node in child.children() {
             let cell_content = collect(cell_node, ctx);
-            let cell_lines: Vec<String> =
-                cell_content.lines().map(|s| s.trim().to_string()).collect();
+            // tabs have no width of their own, they'd push the borders around
+            let cell_lines: Vec<String> = cell_content
+                .lines()
+                .map(|s| expand_tabs(s.trim(), ctx.tab_width))
+                .collect();
             max_lines_in_row = max_lines_in_row.max(cell_lines.len());
             row_cells.push(cell_lines);
         }
@@ -473,6 +537,24 @@ fn render_table<'a>(node: &'a AstNode<'a>, ctx: &mut AnsiContext) -> String {
         }
     }
 
+    // room for the cells, without the borders and the padding around them
+    let indent = if ctx.should_indent() { INDENT } else { 0 };
+    let table_width = ctx.term_width.saturating_sub(indent);
+    let available = table_width.saturating_sub(column_widths.len() * 3 + 1);
+    if ctx.table_mode == TableMode::Shrink && column_widths.iter().sum::<usize>() > available {
+        shrink_columns(&mut column_widths, available);
+        for (row, height) in rows.iter_mut().zip(row_heights.iter_mut()) {
+            for (cell, &width) in row.iter_mut().zip(&column_widths) {
+                *cell = cell
+                    .iter()
+                    .flat_map(|line| textwrap::wrap(line, width))
+                    .map(|cow| cow.into_owned())
+                    .collect();
+                *height = (*height).max(cell.len());
+            }
+        }
+    }
+
     let color = &ctx.theme.border.fg;
     let header_color = &ctx.theme.yellow.fg;
     let mut result = String::new();
@@ -554,6 +636,20 @@ fn render_table<'a>(node: &'a AstNode<'a>, ctx: &mut AnsiContext) -> String {
         }
     }
 
+    if ctx.table_mode == TableMode::Truncate {
+        result = result
+            .lines()
+            .map(|line| match string_len(line) > table_width {
+                true => format!(
+                    "{}{RESET}{}…{RESET}",
+                    truncate_ansi(line, table_width.saturating_sub(1)),
+                    ctx.theme.border.fg
+                ),
+                false => line.to_owned(),
+            })
+            .join("\n");
+    }
+
     let sps = node.data.borrow().sourcepos;
     let result = if ctx.centered_lines.contains(&sps.start.line) {
         let le = string_len(result.lines().nth(1).unwrap_or_default());
@@ -578,6 +674,20 @@ fn render_table<'a>(node: &'a AstNode<'a>, ctx: &mut AnsiContext) -> String {
     format!("\n\n{result}\n\n")
 }
 
+/// narrows the widest columns, one cell at a time, until they all fit in `available`
+fn shrink_columns(widths: &mut [usize], available: usize) {
+    while widths.iter().sum::<usize>() > available {
+        let Some(widest) = widths.iter_mut().max() else {
+            return;
+        };
+        // too narrow to read anything anymore
+        if *widest <= 3 {
+            return;
+        }
+        *widest -= 1;
+    }
+}
+
 fn render_strong<'a>(node: &'a AstNode<'a>, ctx: &mut AnsiContext) -> String {
     let content = collect(node, ctx);
     format!("{BOLD}{content}{NORMAL}")
@@ -607,7 +717,8 @@ fn render_link<'a>(node: &'a AstNode<'a>, ctx: &mut AnsiContext) -> String {
         .enumerate()
         .map(|(i, line)| {
             if i == 0 {
-                format!("{osc8_start}{UNDERLINE}{cyan}\u{f0339} {line}{RESET}{osc8_end}")
+                let icon = Glyph::Link.get();
+                format!("{osc8_start}{UNDERLINE}{cyan}{icon} {line}{RESET}{osc8_end}")
             } else {
                 format!("  {osc8_start}{UNDERLINE}{cyan}{line}{RESET}{osc8_end}")
             }
@@ -628,7 +739,7 @@ fn render_image<'a>(node: &'a AstNode<'a>, ctx: &mut AnsiContext) -> String {
 
     let content = collect(node, ctx);
     let cyan = ctx.theme.cyan.fg.clone();
-    format!("{UNDERLINE}{cyan}\u{f0976} {}{RESET}", content)
+    format!("{UNDERLINE}{cyan}{} {}{RESET}", Glyph::Image.get(), content)
 }
 
 fn render_code<'a>(node: &'a AstNode<'a>, ctx: &mut AnsiContext) -> String {
@@ -640,6 +751,34 @@ fn render_code<'a>(node: &'a AstNode<'a>, ctx: &mut AnsiContext) -> String {
     format!("{fg}{}{RESET}", literal)
 }
 
+fn render_math<'a>(node: &'a AstNode<'a>, ctx: &mut AnsiContext) -> String {
+    let NodeValue::Math(NodeMath {
+        ref literal,
+        display_math,
+        ..
+    }) = node.data.borrow().value
+    else {
+        panic!()
+    };
+
+    let fg = &ctx.theme.constant.fg;
+    let math = latex_to_unicode(literal);
+    if !display_math {
+        return format!("{fg}{math}{RESET}");
+    }
+
+    // display math goes on its own lines, centered
+    let lines = math
+        .lines()
+        .map(|line| {
+            let line = line.trim();
+            let offset = ctx.term_width.saturating_sub(string_len(line)) / 2;
+            format!("{}{fg}{line}{RESET}", " ".repeat(offset))
+        })
+        .join("\n");
+    format!("\n{lines}\n")
+}
+
 fn render_html_inline<'a>(node: &'a AstNode<'a>, ctx: &mut AnsiContext) -> String {
     let NodeValue::HtmlInline(ref literal) = node.data.borrow().value else {
         panic!()
@@ -676,7 +815,7 @@ fn render_wiki_link<'a>(node: &'a AstNode<'a>, ctx: &mut AnsiContext) -> String
 
     let content = collect(node, ctx);
     let cyan = &ctx.theme.cyan.fg;
-    format!("{cyan}\u{f15d6} {}{RESET}", content)
+    format!("{cyan}{} {}{RESET}", Glyph::WikiLink.get(), content)
 }
 
 fn render_spoilered_text<'a>(node: &'a AstNode<'a>, ctx: &mut AnsiContext) -> String {
@@ -697,13 +836,14 @@ fn render_alert<'a>(node: &'a AstNode<'a>, ctx: &mut AnsiContext) -> String {
     let cyan = ctx.theme.cyan.fg.clone();
     let yellow = ctx.theme.yellow.fg.clone();
 
-    let (prefix, color) = match kind {
-        comrak::nodes::AlertType::Note => ("\u{f05d6} NOTE", blue),
-        comrak::nodes::AlertType::Tip => ("\u{f400} TIP", green),
-        comrak::nodes::AlertType::Important => ("\u{f017e} INFO", cyan),
-        comrak::nodes::AlertType::Warning => ("\u{ea6c} WARNING", yellow),
-        comrak::nodes::AlertType::Caution => ("\u{f0ce6} DANGER", red),
+    let (icon, title, color) = match kind {
+        comrak::nodes::AlertType::Note => (Glyph::AlertNote, "NOTE", blue),
+        comrak::nodes::AlertType::Tip => (Glyph::AlertTip, "TIP", green),
+        comrak::nodes::AlertType::Important => (Glyph::AlertImportant, "INFO", cyan),
+        comrak::nodes::AlertType::Warning => (Glyph::AlertWarning, "WARNING", yellow),
+        comrak::nodes::AlertType::Caution => (Glyph::AlertCaution, "DANGER", red),
     };
+    let prefix = format!("{} {title}", icon.get());
 
     let mut result = format!("\n\n{}▌ {BOLD}{}{RESET}", color, prefix);
 
@@ -731,3 +871,100 @@ fn render_alert<'a>(node: &'a AstNode<'a>, ctx: &mut AnsiContext) -> String {
 
     content + "\n\n"
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        config::{McatConfig, TableMode},
+        markdown_viewer::{code_block, md_to_ansi, md_to_ansi_with_width},
+    };
+    use strip_ansi_escapes::strip_str;
+    use unicode_width::UnicodeWidthStr;
+
+    #[test]
+    fn ordered_list_items_are_numbered() {
+        let md = "1. one\n1. two\n1. three\n1. four\n1. five\n";
+        let res = strip_str(md_to_ansi(md, &McatConfig::default(), None));
+        let numbers: Vec<&str> = res
+            .lines()
+            .filter_map(|l| l.split_whitespace().next())
+            .collect();
+
+        assert_eq!(numbers, ["1.", "2.", "3.", "4.", "5."]);
+    }
+
+    #[test]
+    fn code_blocks_are_extracted_in_order() {
+        let md = "```rust\nfn main() {}\n```\n\n> ```sh\n> echo hi\n> ```\n\n    indented\n";
+        let config = McatConfig::default();
+
+        assert_eq!(
+            code_block(md, 1, &config).as_deref(),
+            Some("fn main() {}\n")
+        );
+        assert_eq!(code_block(md, 2, &config).as_deref(), Some("echo hi\n"));
+        assert_eq!(code_block(md, 3, &config).as_deref(), Some("indented\n"));
+        assert_eq!(code_block(md, 4, &config), None);
+    }
+
+    #[test]
+    fn front_matter_becomes_a_title_banner() {
+        let md = "---\ntitle: \"Hello\"\nauthor: me\ntags:\n  - a\nlayout: post\n---\n\nbody\n";
+        let res = strip_str(md_to_ansi(md, &McatConfig::default(), None));
+
+        assert!(res.contains("│  Hello  │"));
+        assert!(res.contains("me"));
+        assert!(!res.contains("layout") && !res.contains("title:"));
+    }
+
+    #[test]
+    fn nested_ordered_lists_keep_their_own_numbers() {
+        let md = "3. three\n   1. inner\n   2. inner\n4. four\n";
+        let res = strip_str(md_to_ansi(md, &McatConfig::default(), None));
+        let numbers: Vec<&str> = res
+            .lines()
+            .filter_map(|l| l.split_whitespace().next())
+            .collect();
+
+        assert_eq!(numbers, ["3.", "1.", "2.", "4."]);
+    }
+
+    #[test]
+    fn table_borders_line_up_with_wide_characters() {
+        let md = "| name | v |\n|---|:-:|\n| 日本語テキスト | ✅ |\n| a\tb | ⚠️ |\n| x<br>絵文字 | 👍 |\n";
+        let res = strip_str(md_to_ansi(md, &McatConfig::default(), None));
+        let widths: Vec<usize> = res
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| l.width())
+            .collect();
+
+        assert_eq!(widths.len(), 8);
+        assert!(widths.iter().all(|&w| w == widths[0]), "{res}");
+    }
+
+    #[test]
+    fn wide_tables_fit_the_terminal() {
+        let md = "| id | description |\n|---|---|\n| 1 | a rather long description that won't fit into a narrow terminal |\n";
+        for mode in [TableMode::Truncate, TableMode::Shrink] {
+            let mut config = McatConfig::default();
+            config.table_mode = mode;
+            let res = strip_str(md_to_ansi_with_width(md, &config, None, Some(30)));
+            let widths: Vec<usize> = res.lines().map(|l| l.width()).collect();
+
+            assert!(widths.iter().all(|&w| w <= 30), "{res}");
+            match mode {
+                TableMode::Truncate => assert_eq!(widths.len(), 5, "{res}"),
+                _ => assert!(widths.iter().all(|&w| w == widths[0]), "{res}"),
+            }
+        }
+    }
+
+    #[test]
+    fn inline_math_is_converted_to_unicode() {
+        let md = "$\\alpha^2 \\leq \\frac{1}{2}$ and $\\unknown{x}$\n";
+        let res = strip_str(md_to_ansi(md, &McatConfig::default(), None));
+
+        assert_eq!(res.trim(), "α² ≤ ½ and \\unknown{x}");
+    }
+}
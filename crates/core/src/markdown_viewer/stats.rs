@@ -0,0 +1,105 @@
+use comrak::nodes::{AstNode, NodeValue};
+
+use crate::config::AlwaysOrNever;
+
+/// an average reading speed, for the reading time
+const WORDS_PER_MINUTE: usize = 230;
+
+/// how long a markdown document is, the `--stats` footer
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct MdStats {
+    pub words: usize,
+    pub headings: usize,
+}
+
+impl MdStats {
+    /// only prose is counted, code blocks and markup aren't read as words
+    pub fn from_ast<'a>(root: &'a AstNode<'a>) -> MdStats {
+        let mut stats = MdStats::default();
+        for node in root.descendants() {
+            match &node.data.borrow().value {
+                NodeValue::Text(text) => stats.words += text.split_whitespace().count(),
+                NodeValue::Heading(_) => stats.headings += 1,
+                _ => {}
+            }
+        }
+        stats
+    }
+
+    /// rounded up, anything with words takes at least a minute
+    pub fn reading_minutes(&self) -> usize {
+        self.words.div_ceil(WORDS_PER_MINUTE)
+    }
+
+    pub fn footer(&self) -> String {
+        format!(
+            "{} · {} min read · {}",
+            plural(self.words, "word"),
+            self.reading_minutes(),
+            plural(self.headings, "heading")
+        )
+    }
+}
+
+/// colored output has the footer unless it's turned off (`Auto`),
+/// plain output only when `--stats` asks for it (`Always`)
+pub fn show_footer(when: &AlwaysOrNever, colored: bool) -> bool {
+    when.should_use(colored)
+}
+
+fn plural(count: usize, word: &str) -> String {
+    match count {
+        1 => format!("1 {word}"),
+        _ => format!("{count} {word}s"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use comrak::{Arena, ComrakOptions};
+
+    use super::{MdStats, show_footer};
+    use crate::config::AlwaysOrNever;
+
+    #[test]
+    fn counts_prose_words_and_headings() {
+        let md = "# Title\n\nSome *emphasized* words and `code`\n\n```\nnot read as words\n```\n\n## Next\n\n- one two\n";
+        let arena = Arena::new();
+        let root = comrak::parse_document(&arena, md, &ComrakOptions::default());
+
+        let stats = MdStats::from_ast(root);
+        assert_eq!(
+            stats,
+            MdStats {
+                words: 8,
+                headings: 2
+            }
+        );
+        assert_eq!(stats.footer(), "8 words · 1 min read · 2 headings");
+    }
+
+    #[test]
+    fn reading_time_rounds_up() {
+        let minutes = |words| MdStats { words, headings: 1 }.reading_minutes();
+        assert_eq!(minutes(0), 0);
+        assert_eq!(minutes(1), 1);
+        assert_eq!(minutes(230), 1);
+        assert_eq!(minutes(231), 2);
+        assert_eq!(
+            MdStats {
+                words: 1,
+                headings: 1
+            }
+            .footer(),
+            "1 word · 1 min read · 1 heading"
+        );
+    }
+
+    #[test]
+    fn footer_rules() {
+        assert!(show_footer(&AlwaysOrNever::Auto, true));
+        assert!(!show_footer(&AlwaysOrNever::Auto, false));
+        assert!(show_footer(&AlwaysOrNever::Always, false));
+        assert!(!show_footer(&AlwaysOrNever::Never, true));
+    }
+}
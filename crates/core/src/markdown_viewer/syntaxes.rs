@@ -0,0 +1,173 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use syntect::parsing::{SyntaxDefinition, SyntaxSet};
+
+/// languages syntect doesn't ship with
+const BUNDLED: [(&str, &str); 4] = [
+    (
+        "Nix",
+        include_str!("../../assets/syntaxes/Nix.sublime-syntax"),
+    ),
+    (
+        "Zig",
+        include_str!("../../assets/syntaxes/Zig.sublime-syntax"),
+    ),
+    (
+        "TOML",
+        include_str!("../../assets/syntaxes/TOML.sublime-syntax"),
+    ),
+    (
+        "Dockerfile",
+        include_str!("../../assets/syntaxes/Dockerfile.sublime-syntax"),
+    ),
+];
+
+static SYNTAX_DIR: OnceLock<PathBuf> = OnceLock::new();
+static DEFAULTS: OnceLock<SyntaxSet> = OnceLock::new();
+static EXTENDED: OnceLock<SyntaxSet> = OnceLock::new();
+static BUNDLED_DEFS: OnceLock<Vec<SyntaxDefinition>> = OnceLock::new();
+
+/// `.sublime-syntax` files to load on top of the bundled ones (`--syntax-dir`),
+/// has to be set before anything is highlighted
+pub fn set_syntax_dir(dir: PathBuf) {
+    let _ = SYNTAX_DIR.set(dir);
+}
+
+fn defaults() -> &'static SyntaxSet {
+    DEFAULTS.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn bundled() -> &'static [SyntaxDefinition] {
+    BUNDLED_DEFS.get_or_init(|| {
+        BUNDLED
+            .iter()
+            .filter_map(|(name, source)| {
+                SyntaxDefinition::load_from_str(source, true, Some(name)).ok()
+            })
+            .collect()
+    })
+}
+
+/// syntect's syntaxes, with the bundled and `--syntax-dir` ones
+pub fn extended() -> &'static SyntaxSet {
+    EXTENDED.get_or_init(|| build(SYNTAX_DIR.get().map(PathBuf::as_path)))
+}
+
+/// the syntaxes to highlight `langs` (code block languages, file names or extensions) with.
+/// adding to syntect's set means rebuilding all of it, which is slow,
+/// so it's only done when one of the bundled languages is used
+pub fn syntax_set_for<'a>(langs: impl IntoIterator<Item = &'a str>) -> &'static SyntaxSet {
+    if SYNTAX_DIR.get().is_some() {
+        return extended();
+    }
+    let defaults = defaults();
+    let needs_bundled = langs.into_iter().any(|lang| {
+        defaults.find_syntax_by_token(lang).is_none()
+            && bundled().iter().any(|syntax| {
+                syntax.name.eq_ignore_ascii_case(lang)
+                    || syntax
+                        .file_extensions
+                        .iter()
+                        .any(|ext| ext.eq_ignore_ascii_case(lang))
+            })
+    });
+    match needs_bundled {
+        true => extended(),
+        false => defaults,
+    }
+}
+
+fn build(dir: Option<&Path>) -> SyntaxSet {
+    let mut builder = defaults().clone().into_builder();
+    for syntax in bundled() {
+        builder.add(syntax.clone());
+    }
+    // user syntaxes come last, so they win over the others for the same extension
+    if let Some(dir) = dir
+        && let Err(e) = builder.add_from_folder(dir, true)
+    {
+        eprintln!("warning: ignoring syntaxes in {}: {e}", dir.display());
+    }
+    builder.build()
+}
+
+#[cfg(test)]
+mod tests {
+    use syntect::{
+        parsing::{ParseState, ScopeStackOp, SyntaxDefinition},
+        util::LinesWithEndings,
+    };
+
+    use super::{BUNDLED, build, syntax_set_for};
+
+    #[test]
+    fn bundled_syntaxes_load() {
+        for (name, source) in BUNDLED {
+            assert!(
+                SyntaxDefinition::load_from_str(source, true, Some(name)).is_ok(),
+                "{name} failed to load"
+            );
+        }
+
+        let ps = build(None);
+        for token in ["nix", "zig", "toml", "dockerfile", "rust"] {
+            assert!(
+                ps.find_syntax_by_token(token).is_some(),
+                "{token} is missing"
+            );
+        }
+        assert!(
+            syntax_set_for(["rust", "mermaid"])
+                .find_syntax_by_token("nix")
+                .is_none()
+        );
+        assert!(
+            syntax_set_for(["rust", "Nix"])
+                .find_syntax_by_token("nix")
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn bundled_syntaxes_highlight() {
+        // the regexes are only compiled once a line is parsed
+        let samples = [
+            (
+                "nix",
+                "{ pkgs ? import <nixpkgs> {} }:\nlet\n  name = \"mcat\"; # the name\nin pkgs.hello\n",
+            ),
+            (
+                "zig",
+                "const std = @import(\"std\");\npub fn main() !void {\n    std.debug.print(\"{d}\\n\", .{42});\n}\n",
+            ),
+            (
+                "toml",
+                "[package]\nname = \"mcat\" # the name\nversion = \"0.4.0\"\ndate = 1979-05-27T07:32:00Z\n",
+            ),
+            (
+                "dockerfile",
+                "FROM rust:1.85 AS build\nRUN cargo build --release\nCOPY --from=build /app /app\nENTRYPOINT [\"mcat\"]\n",
+            ),
+        ];
+        assert_eq!(samples.len(), BUNDLED.len());
+
+        let ps = build(None);
+        for (token, sample) in samples {
+            let syntax = ps.find_syntax_by_token(token).unwrap();
+            let mut state = ParseState::new(syntax);
+            let mut scoped = false;
+            for line in LinesWithEndings::from(sample) {
+                let ops = state
+                    .parse_line(line, &ps)
+                    .unwrap_or_else(|e| panic!("{token} failed on {line:?}: {e}"));
+                scoped |= ops
+                    .iter()
+                    .any(|(_, op)| matches!(op, ScopeStackOp::Push(_)));
+            }
+            assert!(scoped, "{token} didn't scope anything");
+        }
+    }
+}
@@ -1,4 +1,7 @@
-use std::str::FromStr;
+use std::{error::Error, path::Path, str::FromStr, time::Duration};
+
+use rasteroid::term_misc::{self, Rgb};
+use serde::Deserialize;
 
 use syntect::highlighting::{Color, ScopeSelectors, StyleModifier, Theme, ThemeSettings};
 
@@ -48,6 +51,11 @@ impl From<&str> for CustomTheme {
             "everforest" => CustomTheme::everforest(),
             "autumn" => CustomTheme::autumn(),
             "spring" => CustomTheme::spring(),
+            "terminal" => CustomTheme::from_terminal(),
+            // validated when parsing the args
+            s if s.ends_with(".toml") => {
+                CustomTheme::from_toml_path(Path::new(s)).unwrap_or_else(|_| CustomTheme::github())
+            }
             _ => CustomTheme::github(),
         }
     }
@@ -88,6 +96,74 @@ pub struct CustomTheme {
     black: ThemeColor,
 }
 
+/// the built-in themes, by name
+pub const THEMES: &[&str] = &[
+    "catppuccin",
+    "nord",
+    "monokai",
+    "dracula",
+    "gruvbox",
+    "one_dark",
+    "solarized",
+    "tokyo_night",
+    "makurai_light",
+    "makurai_dark",
+    "ayu",
+    "ayu_mirage",
+    "github",
+    "synthwave",
+    "material",
+    "rose_pine",
+    "kanagawa",
+    "vscode",
+    "everforest",
+    "autumn",
+    "spring",
+    "terminal",
+];
+
+/// a theme file, colors that aren't set are taken from github
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct ThemeFile {
+    keyword: Option<String>,
+    keyword_bg: Option<String>,
+    function: Option<String>,
+    string: Option<String>,
+    module: Option<String>,
+    constant: Option<String>,
+    comment: Option<String>,
+    foreground: Option<String>,
+    guide: Option<String>,
+    background: Option<String>,
+    surface: Option<String>,
+    border: Option<String>,
+
+    red: Option<String>,
+    green: Option<String>,
+    blue: Option<String>,
+    cyan: Option<String>,
+    magenta: Option<String>,
+    yellow: Option<String>,
+    white: Option<String>,
+    black: Option<String>,
+}
+
+fn rgb_to_hex((r, g, b): Rgb) -> String {
+    format!("#{r:02X}{g:02X}{b:02X}")
+}
+
+// `amount` of the way from `from` to `to`
+fn blend(from: Rgb, to: Rgb, amount: f32) -> Rgb {
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * amount).round() as u8;
+    (mix(from.0, to.0), mix(from.1, to.1), mix(from.2, to.2))
+}
+
+fn is_hex_color(s: &str) -> bool {
+    let hex = s.trim_start_matches('#');
+    hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit())
+}
+
 fn hex_to_rgba(hex: &str) -> Color {
     let hex = hex.trim_start_matches('#');
     let r = u8::from_str_radix(&hex[0..2], 16).unwrap_or(255);
@@ -97,6 +173,96 @@ fn hex_to_rgba(hex: &str) -> Color {
 }
 
 impl CustomTheme {
+    /// if the background is dark, by its relative luminance
+    pub fn is_dark(&self) -> bool {
+        let Color { r, g, b, .. } = self.background.color;
+        0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * (b as f32) < 128.0
+    }
+
+    /// a theme out of the terminal's own ansi colors,
+    /// github when the terminal doesn't answer
+    pub fn from_terminal() -> Self {
+        let base = CustomTheme::github();
+        let Some(colors) = term_misc::query_terminal_colors(Duration::from_millis(200)) else {
+            return base;
+        };
+
+        let palette = colors.palette;
+        let color = |rgb: Option<Rgb>, fallback: ThemeColor| match rgb {
+            Some(rgb) => ThemeColor::from(rgb_to_hex(rgb).as_str()),
+            None => fallback,
+        };
+        let bright = |i: usize| palette[i + 8].or(palette[i]);
+        let background = colors.background.or(palette[0]);
+        let foreground = colors.foreground.or(palette[7]);
+        let mixed = |i: usize, amount: f32| match (background, palette[i].or(foreground)) {
+            (Some(bg), Some(fg)) => Some(blend(bg, fg, amount)),
+            _ => None,
+        };
+
+        CustomTheme {
+            keyword: color(palette[5], base.keyword),
+            keyword_bg: color(mixed(5, 0.15), base.keyword_bg),
+            function: color(palette[4], base.function),
+            string: color(palette[2], base.string),
+            module: color(palette[3], base.module),
+            constant: color(palette[6], base.constant),
+            comment: color(palette[8], base.comment),
+            foreground: color(foreground, base.foreground),
+            guide: color(mixed(8, 0.5), base.guide),
+            background: color(background, base.background),
+            surface: color(mixed(7, 0.08), base.surface),
+            border: color(palette[8], base.border),
+
+            red: color(palette[1], base.red),
+            green: color(palette[2], base.green),
+            blue: color(palette[4], base.blue),
+            cyan: color(palette[6], base.cyan),
+            magenta: color(palette[5], base.magenta),
+            yellow: color(bright(3), base.yellow),
+            white: color(palette[7], base.white),
+            black: color(palette[0], base.black),
+        }
+    }
+
+    /// loads a theme out of a toml file of `name = "#rrggbb"` colors,
+    /// missing colors fall back to the github theme
+    pub fn from_toml_path(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let content = std::fs::read_to_string(path)?;
+        let file: ThemeFile = toml::from_str(&content)?;
+        let base = CustomTheme::github();
+
+        let color = |name: &str, value: Option<String>, fallback: ThemeColor| match value {
+            Some(v) if is_hex_color(&v) => Ok(ThemeColor::from(v.as_str())),
+            Some(v) => Err(format!("{name} = '{v}' isn't a hex color (#rrggbb)")),
+            None => Ok(fallback),
+        };
+
+        Ok(CustomTheme {
+            keyword: color("keyword", file.keyword, base.keyword)?,
+            keyword_bg: color("keyword_bg", file.keyword_bg, base.keyword_bg)?,
+            function: color("function", file.function, base.function)?,
+            string: color("string", file.string, base.string)?,
+            module: color("module", file.module, base.module)?,
+            constant: color("constant", file.constant, base.constant)?,
+            comment: color("comment", file.comment, base.comment)?,
+            foreground: color("foreground", file.foreground, base.foreground)?,
+            guide: color("guide", file.guide, base.guide)?,
+            background: color("background", file.background, base.background)?,
+            surface: color("surface", file.surface, base.surface)?,
+            border: color("border", file.border, base.border)?,
+
+            red: color("red", file.red, base.red)?,
+            green: color("green", file.green, base.green)?,
+            blue: color("blue", file.blue, base.blue)?,
+            cyan: color("cyan", file.cyan, base.cyan)?,
+            magenta: color("magenta", file.magenta, base.magenta)?,
+            yellow: color("yellow", file.yellow, base.yellow)?,
+            white: color("white", file.white, base.white)?,
+            black: color("black", file.black, base.black)?,
+        })
+    }
+
     pub fn autumn() -> Self {
         CustomTheme {
             keyword: "#fc6501".into(),
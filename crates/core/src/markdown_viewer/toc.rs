@@ -0,0 +1,115 @@
+use std::{collections::HashMap, path::Path};
+
+use comrak::nodes::{AstNode, NodeHeading, NodeValue};
+
+use crate::glyphs::Glyph;
+
+use super::{render::RESET, themes::CustomTheme};
+
+/// a heading in the `--toc`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TocEntry {
+    pub level: u8,
+    pub title: String,
+    /// the anchor github gives the heading
+    pub slug: String,
+}
+
+/// the headings of the document down to `depth` (1 is only `#`)
+pub fn toc_entries<'a>(root: &'a AstNode<'a>, depth: u8) -> Vec<TocEntry> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    root.descendants()
+        .filter_map(|node| match node.data.borrow().value {
+            NodeValue::Heading(NodeHeading { level, .. }) => Some((node, level)),
+            _ => None,
+        })
+        .filter(|(_, level)| *level <= depth)
+        .map(|(node, level)| {
+            let title = plain_text(node);
+            let slug = slugify(&title);
+            let count = seen.entry(slug.clone()).or_default();
+            let slug = match *count {
+                0 => slug,
+                n => format!("{slug}-{n}"),
+            };
+            *count += 1;
+            TocEntry { level, title, slug }
+        })
+        .collect()
+}
+
+fn plain_text<'a>(node: &'a AstNode<'a>) -> String {
+    node.descendants()
+        .filter_map(|child| match &child.data.borrow().value {
+            NodeValue::Text(text) => Some(text.to_string()),
+            NodeValue::Code(code) => Some(code.literal.clone()),
+            _ => None,
+        })
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// lowercase, spaces into dashes, punctuation dropped
+fn slugify(title: &str) -> String {
+    title
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            '-' | '_' => Some(c),
+            c if c.is_alphanumeric() => Some(c),
+            _ => None,
+        })
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// the entries as an indented list, nested by level.
+/// with the markdown file they link to its headings
+pub fn render_toc(entries: &[TocEntry], theme: &CustomTheme, file: Option<&Path>) -> String {
+    let Some(top) = entries.iter().map(|e| e.level).min() else {
+        return String::new();
+    };
+    let file = file
+        .and_then(|f| f.canonicalize().ok())
+        .map(|f| format!("file://{}", f.display()));
+
+    let mut toc = format!("{}Contents{RESET}\n", theme.keyword.fg);
+    for entry in entries {
+        let depth = (entry.level - top) as usize;
+        let bullet = Glyph::Bullet(depth).get();
+        let title = match &file {
+            Some(file) => format!(
+                "\x1b]8;;{file}#{}\x1b\\{}\x1b]8;;\x1b\\",
+                entry.slug, entry.title
+            ),
+            None => entry.title.clone(),
+        };
+        toc.push_str(&format!(
+            "{}{}{bullet}{RESET} {title}\n",
+            "  ".repeat(depth + 1),
+            theme.yellow.fg
+        ));
+    }
+    toc
+}
+
+#[cfg(test)]
+mod tests {
+    use comrak::{Arena, ComrakOptions};
+
+    use super::toc_entries;
+
+    #[test]
+    fn toc_respects_depth_and_dedupes_slugs() {
+        let md = "# Intro\n## Set up `mcat`!\n### Deep\n## Set up `mcat`!\n";
+        let arena = Arena::new();
+        let root = comrak::parse_document(&arena, md, &ComrakOptions::default());
+
+        let entries = toc_entries(root, 2);
+        let slugs: Vec<_> = entries.iter().map(|e| e.slug.as_str()).collect();
+        assert_eq!(slugs, ["intro", "set-up-mcat", "set-up-mcat-1"]);
+        assert_eq!(entries[1].title, "Set up mcat!");
+        assert_eq!(entries[1].level, 2);
+    }
+}
@@ -1,3 +1,4 @@
+use std::path::Path;
 use std::{borrow::Cow, collections::HashMap, sync::OnceLock, usize};
 
 use itertools::Itertools;
@@ -9,9 +10,13 @@ use syntect::{
     highlighting::Style,
     util::{LinesWithEndings, as_24_bit_terminal_escaped},
 };
-use unicode_width::UnicodeWidthStr;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
-use super::render::{AnsiContext, RESET};
+use super::{
+    render::{AnsiContext, RESET},
+    syntaxes,
+};
+use crate::glyphs::Glyph;
 
 static NEWLINE_REGEX: OnceLock<Regex> = OnceLock::new();
 static ANSI_ESCAPE_REGEX: OnceLock<Regex> = OnceLock::new();
@@ -110,6 +115,15 @@ pub fn get_lang_icon_and_color(lang: &str) -> Option<(&'static str, &'static str
         ("webp", ("\u{f1c5}", "\x1b[38;5;149m")),
         ("tiff", ("\u{f1c5}", "\x1b[38;5;144m")),
         ("ico", ("\u{f1c5}", "\x1b[38;5;221m")),
+        ("heic", ("\u{f1c5}", "\x1b[38;5;180m")),
+        ("heif", ("\u{f1c5}", "\x1b[38;5;180m")),
+        ("avif", ("\u{f1c5}", "\x1b[38;5;150m")),
+        ("jxl", ("\u{f1c5}", "\x1b[38;5;116m")),
+        ("cr2", ("\u{f1c5}", "\x1b[38;5;167m")),
+        ("cr3", ("\u{f1c5}", "\x1b[38;5;167m")),
+        ("nef", ("\u{f1c5}", "\x1b[38;5;220m")),
+        ("arw", ("\u{f1c5}", "\x1b[38;5;173m")),
+        ("dng", ("\u{f1c5}", "\x1b[38;5;110m")),
         // videos
         ("mp4", ("\u{f03d}", "\x1b[38;5;203m")),
         ("mkv", ("\u{f03d}", "\x1b[38;5;132m")),
@@ -149,7 +163,8 @@ pub fn get_lang_icon_and_color(lang: &str) -> Option<(&'static str, &'static str
     ]
     .into();
 
-    map.get(lang.to_lowercase().as_str()).copied()
+    map.get(lang.to_lowercase().as_str())
+        .map(|&(icon, color)| (Glyph::FileType(icon).get(), color))
 }
 
 pub fn trim_ansi_string(mut str: String) -> String {
@@ -192,6 +207,77 @@ pub fn string_len(str: &str) -> usize {
     strip_ansi_escapes::strip_str(&str).width()
 }
 
+/// replaces tabs with spaces, up to the next multiple of `width` columns
+pub fn expand_tabs(s: &str, width: usize) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut col = 0;
+    for c in s.chars() {
+        match c {
+            '\t' => {
+                let n = width - col % width.max(1);
+                out.push_str(&" ".repeat(n));
+                col += n;
+            }
+            '\n' => {
+                out.push(c);
+                col = 0;
+            }
+            c => {
+                out.push(c);
+                col += c.width().unwrap_or(0);
+            }
+        }
+    }
+    out
+}
+
+/// the length in bytes of the escape sequence at the start of `s`,
+/// either CSI (colors, cursor) or OSC (hyperlinks) ended by BEL or ST
+pub fn escape_len(s: &str) -> usize {
+    let bytes = s.as_bytes();
+    match bytes.get(1) {
+        Some(b'[') => bytes[2..]
+            .iter()
+            .position(|b| (0x40..=0x7e).contains(b))
+            .map_or(bytes.len(), |i| i + 3),
+        Some(b']') => (2..bytes.len())
+            .find_map(|i| match bytes[i] {
+                0x07 => Some(i + 1),
+                0x1b if bytes.get(i + 1) == Some(&b'\\') => Some(i + 2),
+                _ => None,
+            })
+            .unwrap_or(bytes.len()),
+        Some(_) => 1 + s[1..].chars().next().map_or(0, |c| c.len_utf8()),
+        None => 1,
+    }
+}
+
+/// cuts `s` to `width` columns, escape sequences don't take any room.
+/// the ones past the cut are kept too, so links and colors still get closed
+pub fn truncate_ansi(s: &str, width: usize) -> String {
+    let mut out = String::new();
+    let mut used = 0;
+    let mut full = false;
+    let mut i = 0;
+    while let Some(c) = s[i..].chars().next() {
+        if c == '\x1b' {
+            let len = escape_len(&s[i..]);
+            out.push_str(&s[i..i + len]);
+            i += len;
+            continue;
+        }
+        i += c.len_utf8();
+        let w = c.width().unwrap_or(0);
+        full = full || used + w > width;
+        if full {
+            continue;
+        }
+        used += w;
+        out.push(c);
+    }
+    out
+}
+
 fn find_last_fg_color_sequence(text: &str) -> Option<String> {
     let re = ANSI_ESCAPE_REGEX.get_or_init(|| Regex::new(r"\x1b\[[0-9;]*m").unwrap());
     let mut last_fg_color = None;
@@ -364,12 +450,11 @@ pub fn format_code_simple<'a>(code: &str, lang: &str, ctx: &AnsiContext, indent:
         None => lang,
     };
 
-    let ts = ctx.theme.to_syntect_theme();
     let syntax = ctx
         .ps
         .find_syntax_by_token(lang)
         .unwrap_or_else(|| ctx.ps.find_syntax_plain_text());
-    let mut highlighter = HighlightLines::new(syntax, &ts);
+    let mut highlighter = HighlightLines::new(syntax, &ctx.code_theme);
 
     let line_count = code.lines().count().saturating_sub(1);
     let content = LinesWithEndings::from(code)
@@ -378,7 +463,7 @@ pub fn format_code_simple<'a>(code: &str, lang: &str, ctx: &AnsiContext, indent:
             if line_count == i && line.trim().is_empty() {
                 return None;
             }
-            let ranges: Vec<(Style, &str)> = highlighter.highlight_line(line, &ctx.ps).unwrap();
+            let ranges: Vec<(Style, &str)> = highlighter.highlight_line(line, ctx.ps).unwrap();
             let highlighted = as_24_bit_terminal_escaped(&ranges[..], false);
             Some(format!("  {}", highlighted.trim_matches('\n')))
         })
@@ -401,12 +486,11 @@ pub fn format_code_simple<'a>(code: &str, lang: &str, ctx: &AnsiContext, indent:
 }
 
 pub fn format_code_full<'a>(code: &str, lang: &str, ctx: &AnsiContext) -> String {
-    let ts = ctx.theme.to_syntect_theme();
     let syntax = ctx
         .ps
         .find_syntax_by_token(lang)
         .unwrap_or_else(|| ctx.ps.find_syntax_plain_text());
-    let mut highlighter = HighlightLines::new(syntax, &ts);
+    let mut highlighter = HighlightLines::new(syntax, &ctx.code_theme);
 
     let header = match get_lang_icon_and_color(lang) {
         Some((icon, color)) => &format!("{color}{icon} {lang}",),
@@ -446,7 +530,7 @@ pub fn format_code_full<'a>(code: &str, lang: &str, ctx: &AnsiContext) -> String
         let left_space = num_width - num.to_string().chars().count();
         let left_offset = left_space / 2;
         let right_offset = left_space - left_offset;
-        let ranges: Vec<(Style, &str)> = highlighter.highlight_line(line, &ctx.ps).unwrap();
+        let ranges: Vec<(Style, &str)> = highlighter.highlight_line(line, ctx.ps).unwrap();
         let highlighted = as_24_bit_terminal_escaped(&ranges[..], false);
         let highlighted =
             wrap_highlighted_line(highlighted, text_size, sub_text_size, &prefix, true);
@@ -475,11 +559,64 @@ pub fn format_tb(ctx: &AnsiContext, offset: usize) -> String {
     format!("{border}{br}{RESET}")
 }
 
+/// the language of a source file, from its name (`main.rs`, `Makefile`) or its shebang line.
+/// `None` when syntect doesn't know it
+pub fn detect_source_lang(path: &Path, first_line: &str) -> Option<String> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let ext = path.extension().unwrap_or_default().to_string_lossy();
+    let ps = syntaxes::syntax_set_for([name.as_ref(), ext.as_ref()]);
+    let syntax = match ps.find_syntax_for_file(path).ok().flatten() {
+        Some(syntax) => syntax,
+        None => ps.find_syntax_by_first_line(first_line)?,
+    };
+    if syntax.name == ps.find_syntax_plain_text().name {
+        return None;
+    }
+
+    // the extension keeps the icon lookup working, when syntect knows it
+    if syntax.file_extensions.iter().any(|e| *e == ext) {
+        return Some(ext.into_owned());
+    }
+    Some(
+        syntax
+            .file_extensions
+            .first()
+            .cloned()
+            .unwrap_or_else(|| syntax.name.to_lowercase()),
+    )
+}
+
+/// wraps code in a fenced block, the fence is longer than any backtick run inside of it
+pub fn fence_code(code: &str, lang: &str) -> String {
+    let longest = code
+        .split(|c| c != '`')
+        .map(|run| run.len())
+        .max()
+        .unwrap_or_default();
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{fence}{lang}\n{}\n{fence}\n", code.trim_end_matches('\n'))
+}
+
 pub fn limit_newlines<'a>(original: &'a str) -> Cow<'a, str> {
     let re = NEWLINE_REGEX.get_or_init(|| Regex::new(r"\n([ \t]*\n){2,}").unwrap());
     re.replace_all(&original, "\n\n")
 }
 
+/// the top level `key: value` pairs of yaml front matter, keys lowercased and quotes stripped.
+/// lists and nested maps are skipped
+pub fn front_matter_fields(literal: &str) -> Vec<(String, String)> {
+    literal
+        .lines()
+        .filter(|line| !line.starts_with([' ', '\t', '-', '#']))
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| {
+            let value = value.trim().trim_matches(['"', '\'']);
+            (key.trim().to_lowercase(), value.to_string())
+        })
+        .filter(|(_, value)| !value.is_empty())
+        .collect()
+}
+
 pub fn get_title_box<'a>(literal: &'a str) -> Option<&'a str> {
     let re = TITLE_REGEX.get_or_init(|| Regex::new(r#"<!--\s*S-TITLE:\s*(.*?)\s*-->"#).unwrap());
 
@@ -1,34 +1,89 @@
-use ignore::WalkBuilder;
-use inquire::MultiSelect;
+use crossterm::{
+    cursor::{Hide, MoveTo, Show},
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::Print,
+    terminal::{
+        Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode,
+        enable_raw_mode, size,
+    },
+};
+use ignore::{WalkBuilder, overrides::OverrideBuilder};
+use rasteroid::{InlineEncoder, image_extended::InlineImage, term_misc};
 use std::collections::{HashMap, HashSet};
+use std::io::{self, Cursor, Write};
 use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::Duration;
 
-use crate::markdown_viewer::utils::get_lang_icon_and_color;
+use crate::{
+    catter::{self, LoadResult},
+    concater::{self, FileKind},
+    config::{McatConfig, MdImageRender},
+    converter,
+    glyphs::Glyph,
+    markdown_viewer,
+    markdown_viewer::utils::{get_lang_icon_and_color, truncate_ansi},
+};
+
+/// an entry of the prompt, shown as a tree until there's a query;
+/// then as its relative path with the fuzzy matched characters highlighted
+struct FileOption {
+    path: PathBuf,
+    tree_label: String,
+    icon: String,
+    rel: String,
+}
+
+impl FileOption {
+    fn label(&self, query: &str) -> String {
+        match fuzzy_match(query, &self.rel) {
+            Some(matched) if !query.is_empty() => {
+                format!("{}{}", self.icon, highlight(&self.rel, &matched))
+            }
+            _ => self.tree_label.clone(),
+        }
+    }
+}
 
 pub fn prompt_for_files(
     dir: &Path,
-    hidden: bool,
+    config: &McatConfig,
 ) -> Result<Vec<(PathBuf, Option<String>)>, String> {
-    let mut all_paths = collect_gitignored_paths(dir, hidden)?;
+    let mut all_paths = collect_gitignored_paths(dir, config.hidden)?;
     all_paths.sort(); // Ensures folders come before contents
 
     let tree_view = format_file_list(&all_paths, dir);
-
-    let index_map: HashMap<String, PathBuf> = tree_view
-        .iter()
-        .cloned()
-        .zip(all_paths.iter().cloned())
+    let options: Vec<FileOption> = tree_view
+        .into_iter()
+        .zip(all_paths.iter())
+        .map(|(tree_label, path)| {
+            let mut rel = path
+                .strip_prefix(dir)
+                .unwrap_or(path)
+                .to_string_lossy()
+                .into_owned();
+            if path.is_dir() {
+                rel.push('/');
+            }
+            FileOption {
+                path: path.clone(),
+                tree_label,
+                icon: path_icon(path),
+                rel,
+            }
+        })
         .collect();
 
-    let selected = MultiSelect::new("Select files or folders", tree_view)
-        .with_page_size(20)
-        .with_vim_mode(true)
-        .prompt()
-        .map_err(|e| e.to_string())?;
+    let preview = (!config.no_preview).then_some(config);
+    let selected = Picker::new(&options, preview)
+        .run()
+        .map_err(|e| e.to_string())?
+        .ok_or("Operation was canceled by the user")?;
 
     let selected_paths: HashSet<PathBuf> = selected
         .into_iter()
-        .filter_map(|label| index_map.get(&label).cloned())
+        .map(|i| options[i].path.clone())
         .collect();
 
     // Avoid duplicates: if a folder is selected, skip its inner files
@@ -56,6 +111,462 @@ pub fn prompt_for_files(
     Ok(final_files.into_iter().collect())
 }
 
+/// the output of a preview, and the kitty id of its image
+type Preview = (Vec<u8>, Option<u32>);
+
+/// a preview for the worker to render, of the entry `index` into `size` cells at `at`
+struct PreviewJob {
+    index: usize,
+    path: PathBuf,
+    at: (u16, u16),
+    size: (u16, u16),
+}
+
+/// a multi select over the entries, filtered as you type.
+/// the highlighted entry is previewed on the right, unless there's no config for it
+struct Picker<'a> {
+    options: &'a [FileOption],
+    preview: Option<&'a McatConfig>,
+    query: String,
+    filtered: Vec<usize>,
+    cursor: usize,
+    scroll: usize,
+    selected: HashSet<usize>,
+    // the entry on screen, its kitty image, the size of the pane and the rendered previews
+    shown: Option<usize>,
+    image_id: Option<u32>,
+    pane: (u16, u16),
+    cache: HashMap<usize, Preview>,
+    // the worker rendering previews, and the entry it was last asked for
+    worker: Option<(Sender<PreviewJob>, Receiver<(usize, (u16, u16), Preview)>)>,
+    requested: Option<usize>,
+}
+
+impl<'a> Picker<'a> {
+    fn new(options: &'a [FileOption], preview: Option<&'a McatConfig>) -> Self {
+        Picker {
+            options,
+            preview,
+            query: String::new(),
+            filtered: (0..options.len()).collect(),
+            cursor: 0,
+            scroll: 0,
+            selected: HashSet::new(),
+            shown: None,
+            image_id: None,
+            pane: (0, 0),
+            cache: HashMap::new(),
+            worker: None,
+            requested: None,
+        }
+    }
+
+    /// the indices of the selected entries, `None` when canceled
+    fn run(mut self) -> io::Result<Option<HashSet<usize>>> {
+        let mut out = io::stderr();
+        if let Some(config) = self.preview {
+            // asked up front, the worker must not race the key reading for the terminal's answers
+            let _ = term_misc::get_wininfo().spx_width();
+            if [config.theme.as_str(), config.code_theme()].contains(&"terminal") {
+                let _ = term_misc::query_terminal_colors(Duration::from_millis(200));
+            }
+            self.worker = Some(spawn_preview_worker(config.clone()));
+        }
+        enable_raw_mode()?;
+        // no autowrap, long lines must not spill into the preview
+        execute!(out, EnterAlternateScreen, Hide, Print("\x1b[?7l"))?;
+        let result = self.event_loop(&mut out);
+        if let Some(id) = self.image_id {
+            let _ = rasteroid::kitty_encoder::delete_image(id, &mut out);
+        }
+        let _ = execute!(out, Print("\x1b[?7h"), Show, LeaveAlternateScreen);
+        let _ = disable_raw_mode();
+        result
+    }
+
+    fn event_loop(&mut self, out: &mut impl Write) -> io::Result<Option<HashSet<usize>>> {
+        execute!(out, Clear(ClearType::All))?;
+        let mut dirty = true;
+        loop {
+            if dirty {
+                self.draw(out)?;
+            }
+            // the keys and the finished previews are waited on together
+            if !event::poll(Duration::from_millis(30))? {
+                dirty = self.receive_previews();
+                continue;
+            }
+            dirty = true;
+            let key = match event::read()? {
+                Event::Key(key) if key.kind != KeyEventKind::Release => key,
+                Event::Resize(..) => {
+                    self.shown = None;
+                    self.requested = None;
+                    self.cache.clear();
+                    execute!(out, Clear(ClearType::All))?;
+                    continue;
+                }
+                _ => continue,
+            };
+
+            let page = size()?.1.saturating_sub(2).max(1) as isize;
+            match (key.code, key.modifiers) {
+                (KeyCode::Esc, _) | (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
+                    return Ok(None);
+                }
+                (KeyCode::Enter, _) => {
+                    // nothing marked, taking the highlighted one
+                    if self.selected.is_empty()
+                        && let Some(&i) = self.filtered.get(self.cursor)
+                    {
+                        self.selected.insert(i);
+                    }
+                    return Ok(Some(std::mem::take(&mut self.selected)));
+                }
+                (KeyCode::Up, _) | (KeyCode::Char('p'), KeyModifiers::CONTROL) => {
+                    self.move_cursor(-1)
+                }
+                (KeyCode::Down, _) | (KeyCode::Char('n'), KeyModifiers::CONTROL) => {
+                    self.move_cursor(1)
+                }
+                (KeyCode::PageUp, _) => self.move_cursor(-page),
+                (KeyCode::PageDown, _) => self.move_cursor(page),
+                (KeyCode::Char(' '), _) => {
+                    if let Some(&i) = self.filtered.get(self.cursor)
+                        && !self.selected.remove(&i)
+                    {
+                        self.selected.insert(i);
+                    }
+                }
+                (KeyCode::Right, _) => self.selected.extend(self.filtered.iter().copied()),
+                (KeyCode::Left, _) => self.selected.clear(),
+                (KeyCode::Backspace, _) => {
+                    self.query.pop();
+                    self.refilter();
+                }
+                (KeyCode::Char('u'), KeyModifiers::CONTROL) => {
+                    self.query.clear();
+                    self.refilter();
+                }
+                (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
+                    self.query.push(c);
+                    self.refilter();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// takes in the previews the worker finished, true when the highlighted one is among them
+    fn receive_previews(&mut self) -> bool {
+        let Some((_, done)) = &self.worker else {
+            return false;
+        };
+        let mut current_done = false;
+        while let Ok((i, size, preview)) = done.try_recv() {
+            // rendered for a pane that was resized since
+            if size != self.pane {
+                continue;
+            }
+            self.cache.insert(i, preview);
+            if self.filtered.get(self.cursor) == Some(&i) {
+                // drawn again, now from the cache
+                self.shown = None;
+                current_done = true;
+            }
+        }
+        current_done
+    }
+
+    fn move_cursor(&mut self, by: isize) {
+        let last = self.filtered.len().saturating_sub(1) as isize;
+        self.cursor = (self.cursor as isize + by).clamp(0, last) as usize;
+    }
+
+    // the filtered items keep their order, there's no ranking
+    fn refilter(&mut self) {
+        self.filtered = (0..self.options.len())
+            .filter(|&i| fuzzy_match(&self.query, &self.options[i].rel).is_some())
+            .collect();
+        self.cursor = 0;
+        self.scroll = 0;
+    }
+
+    fn draw(&mut self, out: &mut impl Write) -> io::Result<()> {
+        let (width, height) = size()?;
+        let list_width = match self.preview {
+            Some(_) => (width * 2 / 5).max(30).min(width),
+            None => width,
+        };
+        let rows = height.saturating_sub(2) as usize;
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if self.cursor >= self.scroll + rows {
+            self.scroll = self.cursor + 1 - rows;
+        }
+
+        let header = format!(
+            "\x1b[32m?\x1b[0m Select files or folders \x1b[36m›\x1b[0m {}",
+            self.query
+        );
+        queue!(
+            out,
+            MoveTo(0, 0),
+            Clear(ClearType::CurrentLine),
+            Print(header)
+        )?;
+        for row in 0..rows {
+            let line = match self.filtered.get(self.scroll + row) {
+                Some(&i) => {
+                    let pointer = match self.scroll + row == self.cursor {
+                        true => "\x1b[36m>\x1b[0m",
+                        false => " ",
+                    };
+                    let check = match self.selected.contains(&i) {
+                        true => "\x1b[32m[x]\x1b[0m",
+                        false => "[ ]",
+                    };
+                    let label = self.options[i].label(&self.query);
+                    format!("{pointer} {check} {label}")
+                }
+                None => String::new(),
+            };
+            let line = truncate_ansi(&line, list_width.saturating_sub(1) as usize);
+            let y = row as u16 + 1;
+            queue!(
+                out,
+                MoveTo(0, y),
+                Print(" ".repeat(list_width as usize)),
+                MoveTo(0, y),
+                Print(line),
+                Print("\x1b[0m")
+            )?;
+            if self.preview.is_some() {
+                queue!(out, MoveTo(list_width, y), Print("\x1b[90m│\x1b[0m"))?;
+            }
+        }
+        let help = "[↑↓ to move, space to select one, → to all, ← to none, type to filter, enter to confirm, esc to cancel]";
+        queue!(
+            out,
+            MoveTo(0, height.saturating_sub(1)),
+            Clear(ClearType::CurrentLine),
+            Print(format!(
+                "\x1b[36m{}\x1b[0m",
+                truncate_ansi(help, width as usize)
+            ))
+        )?;
+
+        let current = self.filtered.get(self.cursor).copied();
+        if self.preview.is_some() && current != self.shown {
+            self.shown = current;
+            let x = list_width + 2;
+            self.pane = (width.saturating_sub(x), rows as u16);
+            if let Some(id) = self.image_id.take() {
+                rasteroid::kitty_encoder::delete_image(id, out)?;
+            }
+            for y in 1..=self.pane.1 {
+                queue!(out, MoveTo(x, y), Clear(ClearType::UntilNewLine))?;
+            }
+            if let Some(i) = current
+                && self.pane.0 > 0
+                && self.pane.1 > 0
+            {
+                match self.cache.get(&i) {
+                    Some((preview, id)) => {
+                        out.write_all(preview)?;
+                        self.image_id = *id;
+                    }
+                    // left empty until the worker is done with it
+                    None if self.requested != Some(i) => {
+                        self.requested = Some(i);
+                        if let Some((jobs, _)) = &self.worker {
+                            let _ = jobs.send(PreviewJob {
+                                index: i,
+                                path: self.options[i].path.clone(),
+                                at: (x, 1),
+                                size: self.pane,
+                            });
+                        }
+                    }
+                    None => {}
+                }
+            }
+        }
+        out.flush()
+    }
+}
+
+/// renders the previews on a thread of its own, so moving and typing never wait on one.
+/// the jobs that pile up while it's busy are skipped, only the latest one is rendered
+fn spawn_preview_worker(
+    config: McatConfig,
+) -> (Sender<PreviewJob>, Receiver<(usize, (u16, u16), Preview)>) {
+    let (jobs, job_rx) = mpsc::channel::<PreviewJob>();
+    let (done_tx, done) = mpsc::channel();
+    std::thread::spawn(move || {
+        while let Ok(mut job) = job_rx.recv() {
+            while let Ok(newer) = job_rx.try_recv() {
+                job = newer;
+            }
+            let preview = render_preview(&job.path, &config, job.at, job.size);
+            if done_tx.send((job.index, job.size, preview)).is_err() {
+                break;
+            }
+        }
+    });
+    (jobs, done)
+}
+
+/// renders `path` into `size` cells at `at`, with the catter pipeline.
+/// images are shown inline, documents by their first rendered lines,
+/// and anything that can't be shown as its file type icon.
+/// returns the kitty id of the image along with the output
+fn render_preview(
+    path: &Path,
+    config: &McatConfig,
+    at: (u16, u16),
+    size: (u16, u16),
+) -> (Vec<u8>, Option<u32>) {
+    let mut buf = Vec::new();
+    let ext = path
+        .extension()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_lowercase();
+
+    let mut opts = config.clone();
+    // pdfs show their first page, and documents don't pull in their images
+    opts.output = Some("image".to_owned());
+    opts.pages = Some(1..=1);
+    opts.lines = Some(1..=size.1 as usize * 4);
+    opts.md_image_render = MdImageRender::None;
+    // pdf pages are written out as a png, instead of returned
+    let mut handled = Vec::new();
+    let loaded = match path.is_dir() || catter::is_video(&ext) {
+        true => None,
+        false => catter::load(path, &mut handled, &opts).ok(),
+    };
+
+    let image = match loaded {
+        Some((LoadResult::Text(text), from, _)) => {
+            let md = match from.as_ref() {
+                "html" => format!("```html\n{text}\n```"),
+                _ => text,
+            };
+            let ansi = markdown_viewer::md_to_ansi_with_width(
+                &md,
+                &opts,
+                Some(path),
+                Some(size.0 as usize),
+            );
+            for (row, line) in ansi.lines().take(size.1 as usize).enumerate() {
+                let _ = queue!(
+                    buf,
+                    MoveTo(at.0, at.1 + row as u16),
+                    Print(truncate_ansi(line, size.0 as usize)),
+                    Print("\x1b[0m")
+                );
+            }
+            return (buf, None);
+        }
+        Some((LoadResult::Image(image), _, _)) => Some(image),
+        Some((LoadResult::Handled(_), _, _)) => image::load_from_memory(&handled).ok(),
+        _ => None,
+    };
+
+    let (w, h) = (format!("{}c", size.0), format!("{}c", size.1));
+    let image = image.or_else(|| {
+        let key = match path.is_dir() {
+            true => "IAMADIR",
+            false => &ext,
+        };
+        let svg = Cursor::new(converter::ext_to_svg(key));
+        converter::svg_to_image(svg, Some(&w), Some(&h)).ok()
+    });
+    let resize_for_ascii = matches!(
+        config.inline_encoder,
+        InlineEncoder::Ascii | InlineEncoder::HalfBlock
+    );
+    if let Some(image) = image
+        && let Ok((img, _, _, _)) = image.resize_plus(Some(&w), Some(&h), resize_for_ascii, false)
+    {
+        // in the middle of the preview, below `at`
+        let top = match config.inline_options.vcenter {
+            true => rasteroid::image_cells(&img, &config.inline_encoder)
+                .map_or(0, |(_, rows)| size.1.saturating_sub(rows) / 2),
+            false => 0,
+        };
+        // print_at is 1 based
+        let at = (at.0 + 1, at.1 + top + 1);
+        if let Ok((_, _, id)) =
+            rasteroid::inline_an_image(&img, &mut buf, None, Some(at), &config.inline_encoder)
+        {
+            return (buf, id);
+        }
+    }
+    (buf, None)
+}
+
+/// every text file under `dir`, sorted. gitignored files are skipped, so are hidden ones unless `hidden`
+pub fn collect_text_files(dir: &Path, hidden: bool) -> Result<Vec<PathBuf>, String> {
+    let mut files: Vec<PathBuf> = collect_gitignored_paths(dir, hidden)?
+        .into_iter()
+        .filter(|p| p.is_file() && concater::classify(p) == FileKind::Text)
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+pub fn is_glob(s: &str) -> bool {
+    s.contains(['*', '?', '[', '{'])
+}
+
+/// expands a glob like `src/**/*.rs` into the files it matches, sorted.
+/// gitignored and hidden files are skipped like in [`collect_text_files`]
+pub fn expand_glob(pattern: &str, hidden: bool) -> Result<Vec<PathBuf>, String> {
+    // the walk starts at the part of the pattern that has no glob in it
+    let mut base = PathBuf::new();
+    let mut rest = Vec::new();
+    // by components, so the root of `/usr/*.rs` (or `C:\`) stays in the base
+    for part in Path::new(pattern).components() {
+        let text = part.as_os_str().to_string_lossy();
+        if rest.is_empty() && !is_glob(&text) {
+            base.push(part);
+        } else {
+            rest.push(text);
+        }
+    }
+    if base.as_os_str().is_empty() {
+        base.push(".");
+    }
+    let rest = rest.join("/");
+    let max_depth = match rest.contains("**") {
+        true => None,
+        false => Some(rest.split('/').count()),
+    };
+
+    // anchored, `*.rs` should only match at the top, like in a shell
+    let overrides = OverrideBuilder::new(&base)
+        .add(&format!("/{rest}"))
+        .and_then(|b| b.build())
+        .map_err(|e| format!("invalid glob '{pattern}': {e}"))?;
+    let walker = WalkBuilder::new(&base)
+        .standard_filters(!hidden)
+        .hidden(!hidden)
+        .follow_links(true)
+        .max_depth(max_depth)
+        .overrides(overrides)
+        .build();
+
+    let mut files: Vec<PathBuf> = walker
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.into_path())
+        .filter(|path| path.is_file())
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
 fn collect_gitignored_paths(dir: &Path, hidden: bool) -> Result<Vec<PathBuf>, String> {
     let walker = WalkBuilder::new(dir)
         .standard_filters(!hidden)
@@ -124,7 +635,8 @@ fn format_file_list(paths: &[PathBuf], base: &Path) -> Vec<String> {
             ""
         };
         if is_dir {
-            line.push_str(&format!("{name_color}\u{f024b} {name}/{reset}"));
+            let icon = Glyph::Folder.get();
+            line.push_str(&format!("{name_color}{icon} {name}/{reset}"));
         } else {
             if let Some((icon, color)) = get_lang_icon_and_color(&ext) {
                 line.push_str(&format!("{color}{icon}{reset} {name_color}{name}{reset}"));
@@ -133,26 +645,54 @@ fn format_file_list(paths: &[PathBuf], base: &Path) -> Vec<String> {
             }
         }
 
-        // Add invisible unique suffix to make each label distinct
-        line.push_str(&encode_invisible_id(i));
-
         formatted.push(line);
     }
 
     formatted
 }
 
-fn encode_invisible_id(id: usize) -> String {
-    let charset = ['\u{200B}', '\u{200C}', '\u{200D}', '\u{2060}'];
-    let mut encoded = String::new();
-    let mut n = id;
-    if n == 0 {
-        encoded.push(charset[0]);
-    } else {
-        while n > 0 {
-            encoded.push(charset[n % 4]);
-            n /= 4;
+fn path_icon(path: &Path) -> String {
+    let reset = "\x1b[0m";
+    if path.is_dir() {
+        return format!("\x1b[1m\x1b[34m{}{reset} ", Glyph::Folder.get());
+    }
+    let ext = path
+        .extension()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_lowercase();
+    match get_lang_icon_and_color(&ext) {
+        Some((icon, color)) => format!("{color}{icon}{reset} "),
+        None => String::new(),
+    }
+}
+
+/// subsequence match like fzf, returns the char indices of `text` that matched.
+/// case insensitive unless the query has an uppercase letter
+fn fuzzy_match(query: &str, text: &str) -> Option<Vec<usize>> {
+    let case_sensitive = query.chars().any(char::is_uppercase);
+    let eq = |a: char, b: char| match case_sensitive {
+        true => a == b,
+        false => a.to_lowercase().eq(b.to_lowercase()),
+    };
+
+    let mut matched = Vec::new();
+    let mut chars = text.chars().enumerate();
+    for q in query.chars().filter(|c| !c.is_whitespace()) {
+        let (i, _) = chars.find(|(_, c)| eq(q, *c))?;
+        matched.push(i);
+    }
+    Some(matched)
+}
+
+fn highlight(text: &str, matched: &[usize]) -> String {
+    let mut out = String::new();
+    for (i, c) in text.chars().enumerate() {
+        if matched.contains(&i) {
+            out.push_str(&format!("\x1b[1;33m{c}\x1b[0m"));
+        } else {
+            out.push(c);
         }
     }
-    encoded
+    out
 }
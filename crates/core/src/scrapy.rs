@@ -4,15 +4,19 @@ use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use regex::Regex;
 use reqwest::{Client, Response};
 use scraper::Html;
+use std::fs;
 use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 use std::sync::OnceLock;
 use std::time::Duration;
 use tempfile::NamedTempFile;
 use tokio::runtime::{Builder, Runtime};
 
-use crate::catter;
+use crate::{catter, fetch_manager};
 
 static GITHUB_BLOB_URL: OnceLock<Regex> = OnceLock::new();
+static VIDEO_SITE_URL: OnceLock<Regex> = OnceLock::new();
 static HTTP_CLIENT: OnceLock<Client> = OnceLock::new();
 static RUNTIME: OnceLock<Runtime> = OnceLock::new();
 
@@ -140,6 +144,11 @@ pub struct MediaScrapeOptions {
     pub images: bool,
     pub videos: bool,
     pub documents: bool,
+    /// sent with the page, and with the media inside of it that is on the same host
+    pub headers: Vec<(String, String)>,
+    pub cookie: Option<String>,
+    /// for the whole request, body included
+    pub timeout: Option<Duration>,
 }
 
 impl Default for MediaScrapeOptions {
@@ -150,6 +159,9 @@ impl Default for MediaScrapeOptions {
             images: true,
             videos: true,
             documents: true,
+            headers: Vec::new(),
+            cookie: None,
+            timeout: None,
         }
     }
 }
@@ -198,8 +210,16 @@ pub fn scrape_biggest_media(
         url
     };
 
+    // the page itself has no media in it, yt-dlp knows how to get the stream
+    if is_video_site(url) {
+        if !options.videos {
+            return Err("Media type Video is not allowed by current options".into());
+        }
+        return download_with_ytdlp(url, options);
+    }
+
     rt.block_on(async {
-        let response = get_response(&client, url, options).await?;
+        let response = get_response(&client, url, url, options).await?;
 
         // Check content length before proceeding
         if let Some(max_length) = options.max_content_length {
@@ -214,12 +234,18 @@ pub fn scrape_biggest_media(
             }
         }
 
-        // Direct file download if mime type is recognized
-        if let Some(ext) = get_ext_from_response(&response) {
-            let media_type = get_media_type_from_ext(ext);
+        // Direct file download if mime type is recognized,
+        // or if the url points at a media file and the server didn't say what it is
+        let ext = match get_ext_from_response(&response) {
+            Some(ext) => Some(ext.to_owned()),
+            None if !is_html(&response) => get_ext_from_url(url),
+            None => None,
+        };
+        if let Some(ext) = ext {
+            let media_type = get_media_type_from_ext(&ext);
             if is_media_type_allowed(&media_type, options) {
                 let data = download_media(response, options).await?;
-                return write_to_tmp_file(&data, ext);
+                return write_to_tmp_file(&data, &ext);
             } else {
                 return Err(format!(
                     "Media type {:?} is not allowed by current options",
@@ -234,6 +260,106 @@ pub fn scrape_biggest_media(
     })
 }
 
+/// youtube and vimeo pages, the video can only be fetched through yt-dlp
+pub fn is_video_site(url: &str) -> bool {
+    let re = VIDEO_SITE_URL.get_or_init(|| {
+        Regex::new(
+            r"^https?://(www\.|m\.)?(youtube\.com/(watch|shorts/|live/)|youtu\.be/|(player\.)?vimeo\.com/)",
+        )
+        .unwrap()
+    });
+    re.is_match(url)
+}
+
+fn download_with_ytdlp(
+    url: &str,
+    options: &MediaScrapeOptions,
+) -> Result<NamedTempFile, Box<dyn std::error::Error>> {
+    let ytdlp = fetch_manager::get_ytdlp().ok_or(
+        "yt-dlp isn't installed. either install it manually, or call `mcat --fetch-ytdlp`",
+    )?;
+
+    let dir = tempfile::tempdir()?;
+    let template = dir.path().join("video.%(ext)s");
+    let mut command = Command::new(ytdlp);
+    command
+        .args(["-f", "bv*+ba/b", "--no-playlist", "-o"])
+        .arg(&template)
+        // the final path, after any merge or remux renamed it
+        .args(["--print", "after_move:filepath"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit());
+    // merging the best video and audio needs ffmpeg, the one we fetched isn't on the PATH
+    if let Some(ffmpeg) = fetch_manager::find_ffmpeg() {
+        command.arg("--ffmpeg-location").arg(ffmpeg.path());
+    }
+    for (name, value) in request_headers(options, url, url) {
+        command.arg("--add-header").arg(format!("{name}:{value}"));
+    }
+    command.arg(url);
+    // --print makes it quiet, the progress still goes to stderr
+    if options.silent {
+        command.arg("--no-warnings");
+    } else {
+        command.arg("--progress");
+    }
+    let output = command.output()?;
+    if !output.status.success() {
+        return Err(format!("yt-dlp failed with code {:?}", output.status.code()).into());
+    }
+
+    let video = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .rfind(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .filter(|path| path.is_file())
+        .ok_or("yt-dlp didn't download anything")?;
+    let ext = video
+        .extension()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned();
+    let tmp_file = NamedTempFile::with_suffix(format!(".{}", ext))?;
+    // the temp dir is removed once this returns
+    fs::copy(&video, tmp_file.path())?;
+    Ok(tmp_file)
+}
+
+/// the user given headers, along with the cookie.
+/// only for `url`s on the host of `origin` (the url that was asked for), media elsewhere gets none
+fn request_headers<'a>(
+    options: &'a MediaScrapeOptions,
+    url: &str,
+    origin: &str,
+) -> impl Iterator<Item = (&'a str, &'a str)> {
+    let host = |u: &str| reqwest::Url::parse(u).ok()?.host_str().map(str::to_owned);
+    let same_host = host(url).is_some_and(|h| host(origin) == Some(h));
+    let cookie = options.cookie.as_deref().map(|c| ("Cookie", c));
+    options
+        .headers
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_str()))
+        .chain(cookie)
+        .filter(move |_| same_host)
+}
+
+fn is_html(response: &Response) -> bool {
+    response
+        .headers()
+        .get("Content-Type")
+        .and_then(|h| h.to_str().ok())
+        .is_some_and(|mime| mime.contains("text/html"))
+}
+
+/// the extension of the url path, when it's an image or a video
+fn get_ext_from_url(url: &str) -> Option<String> {
+    let url = reqwest::Url::parse(url).ok()?;
+    let ext = Path::new(url.path()).extension()?.to_str()?.to_lowercase();
+    (catter::is_image(&ext) || catter::is_video(&ext)).then_some(ext)
+}
+
 fn get_content_length(response: &Response) -> Option<u64> {
     response
         .headers()
@@ -393,7 +519,7 @@ async fn process_html(
         };
 
         // get response
-        let media_response = match get_response(client, resolved_url.as_str(), options).await {
+        let media_response = match get_response(client, resolved_url.as_str(), url, options).await {
             Ok(v) => v,
             Err(_) => continue,
         };
@@ -452,6 +578,7 @@ async fn process_html(
 async fn get_response(
     client: &Client,
     url: &str,
+    origin: &str,
     options: &MediaScrapeOptions,
 ) -> Result<Response, Box<dyn std::error::Error>> {
     let initial_spinner = if !options.silent {
@@ -466,7 +593,14 @@ async fn get_response(
         None
     };
 
-    let request_future = client.get(url).send();
+    let mut request = client.get(url);
+    for (name, value) in request_headers(options, url, origin) {
+        request = request.header(name, value);
+    }
+    if let Some(timeout) = options.timeout {
+        request = request.timeout(timeout);
+    }
+    let request_future = request.send();
     tokio::pin!(request_future);
 
     let response = tokio::select! {
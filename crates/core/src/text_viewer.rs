@@ -0,0 +1,377 @@
+use std::{
+    io::{self, Write},
+    path::Path,
+};
+
+use crossterm::{
+    cursor::{Hide, MoveTo, Show},
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::Print,
+    terminal::{
+        self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode,
+        enable_raw_mode,
+    },
+};
+
+use regex::{Regex, RegexBuilder};
+use strip_ansi_escapes::strip_str;
+
+use crate::{
+    config::{McatConfig, MdImageRender},
+    markdown_viewer::{
+        self,
+        utils::{escape_len, truncate_ansi},
+    },
+};
+
+/// a full screen, scrollable view of markdown rendered into ansi.
+/// the markdown is wrapped to the terminal, and wrapped again when it's resized
+struct TextViewer<'a> {
+    md: &'a str,
+    config: McatConfig,
+    path: Option<&'a Path>,
+    // the rendered lines, and the same without escapes for searching
+    lines: Vec<String>,
+    plain: Vec<String>,
+    width: u16,
+    height: u16,
+    top: usize,
+    search: Option<Regex>,
+    // the query while it's typed, after `/`
+    typing: Option<String>,
+}
+
+impl<'a> TextViewer<'a> {
+    fn new(md: &'a str, config: &McatConfig, path: Option<&'a Path>) -> Self {
+        // images can't scroll with the text
+        let mut config = config.clone();
+        config.md_image_render = MdImageRender::None;
+        let search = config.grep.clone();
+        TextViewer {
+            md,
+            config,
+            path,
+            lines: Vec::new(),
+            plain: Vec::new(),
+            width: 0,
+            height: 0,
+            top: 0,
+            search,
+            typing: None,
+        }
+    }
+
+    fn render(&mut self) -> io::Result<()> {
+        let (width, height) = terminal::size()?;
+        let old_len = self.lines.len().max(1);
+        let ansi = markdown_viewer::md_to_ansi_with_width(
+            self.md,
+            &self.config,
+            self.path,
+            Some(width as usize),
+        );
+        self.lines = ansi.lines().map(|l| l.to_owned()).collect();
+        self.plain = self.lines.iter().map(strip_str).collect();
+        // stays around the same spot of the document
+        self.top = self.top * self.lines.len() / old_len;
+        self.width = width;
+        self.height = height;
+        self.scroll_to(self.top);
+        Ok(())
+    }
+
+    /// rows available for the text, the last one is the status line
+    fn page(&self) -> usize {
+        (self.height as usize).saturating_sub(1).max(1)
+    }
+
+    fn scroll_to(&mut self, top: usize) {
+        self.top = top.min(self.lines.len().saturating_sub(self.page()));
+    }
+
+    fn scroll_by(&mut self, delta: isize) {
+        self.scroll_to(self.top.saturating_add_signed(delta));
+    }
+
+    /// moves the first line matching the search to the top,
+    /// searching from the line `from` onwards, or backwards from the line before it
+    fn find(&mut self, from: usize, forward: bool) {
+        let Some(re) = &self.search else {
+            return;
+        };
+        let found = match forward {
+            true => (from..self.plain.len()).find(|&i| re.is_match(&self.plain[i])),
+            false => (0..from).rev().find(|&i| re.is_match(&self.plain[i])),
+        };
+        if let Some(i) = found {
+            self.top = i;
+        }
+    }
+
+    fn event_loop(&mut self, out: &mut impl Write) -> io::Result<()> {
+        self.render()?;
+        self.find(0, true);
+        loop {
+            self.draw(out)?;
+            let key = match event::read()? {
+                Event::Key(key) if key.kind != KeyEventKind::Release => key,
+                Event::Resize(..) => {
+                    self.render()?;
+                    continue;
+                }
+                _ => continue,
+            };
+
+            if let Some(typing) = self.typing.as_mut() {
+                match key.code {
+                    KeyCode::Esc => self.typing = None,
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        self.typing = None
+                    }
+                    KeyCode::Enter => {
+                        // plain text, ignoring case
+                        let query = self.typing.take().unwrap_or_default();
+                        self.search = match query.is_empty() {
+                            true => None,
+                            false => RegexBuilder::new(&regex::escape(&query))
+                                .case_insensitive(true)
+                                .build()
+                                .ok(),
+                        };
+                        self.find(self.top, true);
+                    }
+                    KeyCode::Backspace => {
+                        typing.pop();
+                    }
+                    KeyCode::Char(c) => typing.push(c),
+                    _ => {}
+                }
+                continue;
+            }
+
+            let page = self.page() as isize;
+            match key {
+                KeyEvent {
+                    code: KeyCode::Char('c'),
+                    modifiers: KeyModifiers::CONTROL,
+                    ..
+                }
+                | KeyEvent {
+                    code: KeyCode::Char('q') | KeyCode::Esc,
+                    ..
+                } => return Ok(()),
+                KeyEvent {
+                    code: KeyCode::Down | KeyCode::Enter | KeyCode::Char('j'),
+                    ..
+                } => self.scroll_by(1),
+                KeyEvent {
+                    code: KeyCode::Up | KeyCode::Char('k'),
+                    ..
+                } => self.scroll_by(-1),
+                KeyEvent {
+                    code: KeyCode::PageDown | KeyCode::Char(' ') | KeyCode::Char('f'),
+                    ..
+                } => self.scroll_by(page),
+                KeyEvent {
+                    code: KeyCode::PageUp | KeyCode::Char('b'),
+                    ..
+                } => self.scroll_by(-page),
+                KeyEvent {
+                    code: KeyCode::Char('d'),
+                    ..
+                } => self.scroll_by(page / 2),
+                KeyEvent {
+                    code: KeyCode::Char('u'),
+                    ..
+                } => self.scroll_by(-page / 2),
+                KeyEvent {
+                    code: KeyCode::Home | KeyCode::Char('g'),
+                    ..
+                } => self.scroll_to(0),
+                KeyEvent {
+                    code: KeyCode::End | KeyCode::Char('G'),
+                    ..
+                } => self.scroll_to(usize::MAX),
+                KeyEvent {
+                    code: KeyCode::Char('/'),
+                    ..
+                } => self.typing = Some(String::new()),
+                KeyEvent {
+                    code: KeyCode::Char('n'),
+                    ..
+                } => self.find(self.top + 1, true),
+                KeyEvent {
+                    code: KeyCode::Char('N'),
+                    ..
+                } => self.find(self.top, false),
+                _ => {}
+            }
+        }
+    }
+
+    fn draw(&self, out: &mut impl Write) -> io::Result<()> {
+        let width = self.width as usize;
+        for row in 0..self.page() {
+            let line = self.lines.get(self.top + row).map_or("", |l| l.as_str());
+            let line = match &self.search {
+                Some(re) => highlight_matches(line, re),
+                None => line.to_owned(),
+            };
+            queue!(
+                out,
+                MoveTo(0, row as u16),
+                Clear(ClearType::CurrentLine),
+                Print(truncate_ansi(&line, width)),
+                Print("\x1b[0m")
+            )?;
+        }
+
+        let status = match &self.typing {
+            Some(typing) => format!("/{typing}"),
+            None => {
+                let total = self.lines.len();
+                let bottom = (self.top + self.page()).min(total);
+                let percent = match total {
+                    0 => 100,
+                    _ => bottom * 100 / total,
+                };
+                let name = self
+                    .path
+                    .and_then(|p| p.file_name())
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                format!(
+                    "{name} {}-{bottom}/{total} {percent}%  [j/k scroll, g/G top/bottom, / search, n/N next/previous, q quit]",
+                    (self.top + 1).min(total),
+                )
+            }
+        };
+        queue!(
+            out,
+            MoveTo(0, self.height.saturating_sub(1)),
+            Clear(ClearType::CurrentLine),
+            Print(format!("\x1b[7m{}\x1b[0m", truncate_ansi(&status, width)))
+        )?;
+        out.flush()
+    }
+}
+
+/// shows the markdown `md` in a full screen viewer on stdout, until the user quits
+pub fn view(md: &str, config: &McatConfig, path: Option<&Path>) -> io::Result<()> {
+    let mut out = io::stdout();
+    enable_raw_mode()?;
+    // no autowrap, the lines are cut to the screen instead
+    execute!(out, EnterAlternateScreen, Hide, Print("\x1b[?7l"))?;
+    let result = TextViewer::new(md, config, path).event_loop(&mut out);
+    let _ = execute!(out, Print("\x1b[?7h"), Show, LeaveAlternateScreen);
+    let _ = disable_raw_mode();
+    result
+}
+
+/// shows the matches of `re` in `line` in reverse video,
+/// `re` runs over the text without its escape sequences
+pub fn highlight_matches(line: &str, re: &Regex) -> String {
+    let plain = strip_str(line);
+    let ranges: Vec<_> = re
+        .find_iter(&plain)
+        .filter(|m| !m.is_empty())
+        .map(|m| m.range())
+        .collect();
+    if ranges.is_empty() {
+        return line.to_owned();
+    }
+
+    let mut out = String::with_capacity(line.len() + ranges.len() * 10);
+    let mut ranges = ranges.into_iter().peekable();
+    let mut inside = false;
+    // position in the plain text
+    let mut pos = 0;
+    let mut i = 0;
+    while let Some(c) = line[i..].chars().next() {
+        if c == '\x1b' {
+            let len = escape_len(&line[i..]);
+            out.push_str(&line[i..i + len]);
+            i += len;
+            // a reset in the middle of a match shouldn't end it
+            if inside {
+                out.push_str("\x1b[7m");
+            }
+            continue;
+        }
+        if inside && ranges.peek().is_some_and(|r| r.end == pos) {
+            out.push_str("\x1b[27m");
+            inside = false;
+            ranges.next();
+        }
+        if !inside && ranges.peek().is_some_and(|r| r.start == pos) {
+            out.push_str("\x1b[7m");
+            inside = true;
+        }
+        out.push(c);
+        pos += c.len_utf8();
+        i += c.len_utf8();
+    }
+    if inside {
+        out.push_str("\x1b[27m");
+    }
+    out
+}
+
+/// the lines of `text` matching `re` with `context` lines around them, like grep.
+/// lines are matched without their escape sequences, and groups that aren't
+/// next to each other are separated by `--`
+pub fn grep(text: &str, re: &Regex, context: usize, color: bool) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut out = String::new();
+    // the line after the last one printed
+    let mut next = 0;
+    for (m, _) in lines
+        .iter()
+        .enumerate()
+        .filter(|(_, l)| re.is_match(&strip_str(l)))
+    {
+        let start = m.saturating_sub(context).max(next);
+        let end = (m + context + 1).min(lines.len());
+        if start >= end {
+            continue;
+        }
+        if next != 0 && start > next {
+            out.push_str(if color { "\x1b[90m--\x1b[0m\n" } else { "--\n" });
+        }
+        for line in &lines[start..end] {
+            match color {
+                true => out.push_str(&highlight_matches(line, re)),
+                false => out.push_str(line),
+            }
+            out.push('\n');
+        }
+        next = end;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use regex::Regex;
+
+    use crate::text_viewer::{grep, highlight_matches};
+
+    #[test]
+    fn highlights_across_escapes() {
+        let re = Regex::new("bc").unwrap();
+        let line = "a\x1b[31mb\x1b[0mcd";
+        assert_eq!(
+            highlight_matches(line, &re),
+            "a\x1b[31m\x1b[7mb\x1b[0m\x1b[7mc\x1b[27md"
+        );
+    }
+
+    #[test]
+    fn greps_with_context() {
+        let re = Regex::new("^(b|f)$").unwrap();
+        let text = "a\nb\nc\nd\ne\nf\ng";
+        assert_eq!(grep(text, &re, 1, false), "a\nb\nc\n--\ne\nf\ng\n");
+        assert_eq!(grep(text, &re, 2, false), "a\nb\nc\nd\ne\nf\ng\n");
+    }
+}
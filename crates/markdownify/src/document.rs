@@ -0,0 +1,74 @@
+/// what a [`Section`] of a document is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SectionKind {
+    /// a pdf page
+    Page,
+    /// a presentation slide
+    Slide,
+    /// a spreadsheet worksheet
+    Sheet,
+    /// an epub chapter
+    Chapter,
+    /// a file inside an archive
+    Entry,
+    /// formats that aren't split, the whole document
+    Whole,
+}
+
+/// a part of a converted document
+#[derive(Debug, Clone)]
+pub struct Section {
+    pub title: Option<String>,
+    pub kind: SectionKind,
+    pub markdown: String,
+}
+
+impl Section {
+    pub fn new(title: Option<String>, kind: SectionKind, markdown: String) -> Self {
+        Section {
+            title,
+            kind,
+            markdown,
+        }
+    }
+
+    /// the section as it appears inside of [`Document::to_markdown`]
+    pub fn to_markdown(&self) -> String {
+        let title = self.title.as_deref();
+        match (self.kind, title) {
+            (SectionKind::Page | SectionKind::Slide, Some(title)) => {
+                format!("\n\n<!-- S-TITLE: {} -->\n{}", title, self.markdown)
+            }
+            (SectionKind::Sheet | SectionKind::Chapter, Some(title)) => {
+                format!("# {}\n\n{}\n\n", title, self.markdown.trim_end())
+            }
+            (SectionKind::Entry, Some(title)) => format!("# `{}`\n\n{}\n\n", title, self.markdown),
+            (SectionKind::Whole, _) => self.markdown.clone(),
+            (_, None) => format!("{}\n\n", self.markdown),
+        }
+    }
+}
+
+/// a converted document, split into its pages / slides / sheets etc..
+#[derive(Debug, Clone, Default)]
+pub struct Document {
+    pub sections: Vec<Section>,
+}
+
+impl Document {
+    pub fn new(sections: Vec<Section>) -> Self {
+        Document { sections }
+    }
+
+    /// a document made of a single section
+    pub fn whole(markdown: String) -> Self {
+        Document {
+            sections: vec![Section::new(None, SectionKind::Whole, markdown)],
+        }
+    }
+
+    /// joins the sections back into a single markdown string
+    pub fn to_markdown(&self) -> String {
+        self.sections.iter().map(Section::to_markdown).collect()
+    }
+}
@@ -1,8 +1,10 @@
-use crate::get_encoding;
+use crate::{ConvertError, get_encoding};
 
 use super::sheets;
+use base64::{Engine, engine::general_purpose::STANDARD};
 use quick_xml::events::Event;
 use quick_xml::reader::Reader;
+use std::collections::HashMap;
 use std::io::{Cursor, Read};
 use std::path::Path;
 use zip::ZipArchive;
@@ -33,28 +35,145 @@ impl Styles {
     }
 }
 
+fn decode(buf: &[u8]) -> String {
+    let encoding = get_encoding(buf, 4096);
+    let mut decoder = encoding.new_decoder_with_bom_removal();
+    // decode_to_string only writes into the spare capacity
+    let mut text = String::with_capacity(
+        decoder
+            .max_utf8_buffer_length(buf.len())
+            .unwrap_or(buf.len() * 3),
+    );
+    let _ = decoder.decode_to_string(buf, &mut text, true);
+    text
+}
+
 fn get_attr(e: &quick_xml::events::BytesStart, key: &[u8]) -> Option<String> {
     for attr in e.attributes().with_checks(false).flatten() {
         if attr.key.as_ref() == key {
-            let buf = &attr.value;
-            let encoding = get_encoding(buf, 4096);
-            let mut decoder = encoding.new_decoder_with_bom_removal();
-            let mut text = String::default();
-            let _ = decoder.decode_to_string(&buf, &mut text, true);
-            return Some(text);
+            return Some(decode(&attr.value));
         }
     }
     None
 }
 
+/// maps relationship ids (`rId7`) of images to their path inside the archive
+fn read_image_relationships(archive: &mut ZipArchive<Cursor<Vec<u8>>>) -> HashMap<String, String> {
+    let mut rels = HashMap::new();
+    let mut content = String::new();
+    match archive.by_name("word/_rels/document.xml.rels") {
+        Ok(mut file) => {
+            if file.read_to_string(&mut content).is_err() {
+                return rels;
+            }
+        }
+        Err(_) => return rels,
+    }
+
+    let mut reader = Reader::from_str(&content);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Empty(e)) | Ok(Event::Start(e))
+                if e.local_name().as_ref() == b"Relationship" =>
+            {
+                let is_image = get_attr(&e, b"Type").is_some_and(|t| t.ends_with("/image"));
+                let is_external = get_attr(&e, b"TargetMode").is_some_and(|m| m == "External");
+                if let (true, false, Some(id), Some(target)) = (
+                    is_image,
+                    is_external,
+                    get_attr(&e, b"Id"),
+                    get_attr(&e, b"Target"),
+                ) {
+                    rels.insert(id, resolve_target(&target));
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    rels
+}
+
+/// targets are relative to `word/`, unless they start with `/`
+fn resolve_target(target: &str) -> String {
+    let mut parts: Vec<&str> = match target.strip_prefix('/') {
+        Some(_) => Vec::new(),
+        None => vec!["word"],
+    };
+    for part in target.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            _ => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+fn image_mime(name: &str) -> &'static str {
+    let ext = Path::new(name)
+        .extension()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_lowercase();
+    match ext.as_ref() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "bmp" => "image/bmp",
+        "tif" | "tiff" => "image/tiff",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        "emf" => "image/emf",
+        "wmf" => "image/wmf",
+        _ => "application/octet-stream",
+    }
+}
+
+/// writes the image into `extract_images` and returns a reference to it,
+/// or a base64 data uri when there is no directory to extract into.
+fn embed_image(
+    archive: &mut ZipArchive<Cursor<Vec<u8>>>,
+    media_path: &str,
+    extract_images: Option<&Path>,
+) -> Option<String> {
+    let mut file = archive.by_name(media_path).ok()?;
+    let mut data = Vec::new();
+    file.read_to_end(&mut data).ok()?;
+
+    match extract_images {
+        Some(dir) => {
+            let name = Path::new(media_path).file_name()?;
+            std::fs::create_dir_all(dir).ok()?;
+            let out_path = dir.join(name);
+            std::fs::write(&out_path, &data).ok()?;
+            let out_path = out_path.to_string_lossy().replace('\\', "/");
+            if out_path.contains(' ') {
+                Some(format!("<{}>", out_path))
+            } else {
+                Some(out_path)
+            }
+        }
+        None => Some(format!(
+            "data:{};base64,{}",
+            image_mime(media_path),
+            STANDARD.encode(&data)
+        )),
+    }
+}
+
 /// convert docx into markdown
+/// `extract_images` is a directory to write embedded images into, when `None` they're inlined as base64
+///
 /// usuage:
 /// ```rs
 /// let path = Path::new("path/to/file.docx");
-/// let md = docx_convert(&path).unwrap();
+/// let md = docx_convert(&path, None).unwrap();
 /// println!("{}", md);
 /// ```
-pub fn docx_convert(path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+pub fn docx_convert(path: &Path, extract_images: Option<&Path>) -> Result<String, ConvertError> {
     let data = std::fs::read(path)?;
     let cursor = Cursor::new(data);
 
@@ -68,6 +187,9 @@ pub fn docx_convert(path: &Path) -> Result<String, Box<dyn std::error::Error>> {
             break;
         }
     }
+    let image_rels = read_image_relationships(&mut archive);
+    // rId -> markdown reference, so an image used twice is only written once
+    let mut embedded: HashMap<String, String> = HashMap::new();
 
     let mut reader = Reader::from_str(&xml_content);
     let mut buf = Vec::new();
@@ -78,7 +200,35 @@ pub fn docx_convert(path: &Path) -> Result<String, Box<dyn std::error::Error>> {
     let mut styles = Styles::default();
 
     loop {
-        match reader.read_event_into(&mut buf) {
+        let event = reader.read_event_into(&mut buf);
+
+        // <a:blip r:embed="rId7"> (drawingml) or <v:imagedata r:id="rId7"> (vml)
+        if let Ok(Event::Start(e)) | Ok(Event::Empty(e)) = &event {
+            let rid = match e.name().as_ref() {
+                b"a:blip" => get_attr(e, b"r:embed"),
+                b"v:imagedata" => get_attr(e, b"r:id"),
+                _ => None,
+            };
+            if let Some(rid) = rid {
+                let reference = match embedded.get(&rid) {
+                    Some(r) => Some(r.clone()),
+                    None => image_rels
+                        .get(&rid)
+                        .and_then(|media| embed_image(&mut archive, media, extract_images)),
+                };
+                if let Some(reference) = reference {
+                    let image = format!("![]({})", reference);
+                    if styles.table {
+                        current_row.push(image);
+                    } else {
+                        markdown.push_str(&image);
+                    }
+                    embedded.insert(rid, reference);
+                }
+            }
+        }
+
+        match event {
             Ok(Event::Start(e)) => match e.name().as_ref() {
                 b"w:tbl" => styles.table = true,
                 _ => {
@@ -140,10 +290,7 @@ pub fn docx_convert(path: &Path) -> Result<String, Box<dyn std::error::Error>> {
                 _ => {}
             },
             Ok(Event::Text(e)) => {
-                let encoding = get_encoding(&e, 4096);
-                let mut decoder = encoding.new_decoder_with_bom_removal();
-                let mut text = String::default();
-                let _ = decoder.decode_to_string(&e, &mut text, true);
+                let mut text = decode(&e);
                 if styles.bold {
                     text = format!("**{}** ", text.trim());
                     styles.bold = false;
@@ -214,9 +361,11 @@ pub fn docx_convert(path: &Path) -> Result<String, Box<dyn std::error::Error>> {
             },
             Ok(Event::Eof) => break,
             Err(e) => {
-                return Err(
-                    format!("Error at position {}: {:?}", reader.buffer_position(), e).into(),
-                );
+                return Err(ConvertError::parse(format!(
+                    "Error at position {}: {:?}",
+                    reader.buffer_position(),
+                    e
+                )));
             }
             _ => {}
         }
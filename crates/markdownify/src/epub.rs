@@ -0,0 +1,384 @@
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::reader::Reader;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use zip::ZipArchive;
+
+use crate::{ConvertError, Document, Section, SectionKind};
+
+struct ManifestItem {
+    href: String,
+    media_type: String,
+    properties: String,
+}
+
+struct Package {
+    manifest: HashMap<String, ManifestItem>,
+    spine: Vec<String>,
+    ncx_id: Option<String>,
+}
+
+fn get_attr(e: &BytesStart, key: &[u8]) -> Option<String> {
+    e.attributes()
+        .with_checks(false)
+        .flatten()
+        .find(|attr| attr.key.local_name().as_ref() == key)
+        .map(|attr| String::from_utf8_lossy(&attr.value).into_owned())
+}
+
+fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> Option<String> {
+    let mut entry = archive.by_name(name).ok()?;
+    let mut buf = Vec::new();
+    entry.read_to_end(&mut buf).ok()?;
+    Some(String::from_utf8_lossy(&buf).into_owned())
+}
+
+/// resolves `href` relative to the directory `base`, dropping fragments and percent encoding
+fn resolve_href(base: &str, href: &str) -> String {
+    let href = href.split('#').next().unwrap_or_default();
+    let href = percent_decode(href);
+    let mut parts: Vec<&str> = base.split('/').filter(|p| !p.is_empty()).collect();
+    for part in href.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            _ => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && i + 2 < bytes.len()
+            && let Some(v) = std::str::from_utf8(&bytes[i + 1..i + 3])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            out.push(v);
+            i += 3;
+            continue;
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn parent_dir(path: &str) -> &str {
+    path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("")
+}
+
+fn find_rootfile(container: &str) -> Option<String> {
+    let mut reader = Reader::from_str(container);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) if e.local_name().as_ref() == b"rootfile" => {
+                return get_attr(&e, b"full-path");
+            }
+            Ok(Event::Eof) | Err(_) => return None,
+            _ => {}
+        }
+    }
+}
+
+fn parse_package(opf: &str) -> Package {
+    let mut reader = Reader::from_str(opf);
+    let mut package = Package {
+        manifest: HashMap::new(),
+        spine: Vec::new(),
+        ncx_id: None,
+    };
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) => match e.local_name().as_ref() {
+                b"item" => {
+                    if let (Some(id), Some(href)) = (get_attr(&e, b"id"), get_attr(&e, b"href")) {
+                        let item = ManifestItem {
+                            href,
+                            media_type: get_attr(&e, b"media-type").unwrap_or_default(),
+                            properties: get_attr(&e, b"properties").unwrap_or_default(),
+                        };
+                        package.manifest.insert(id, item);
+                    }
+                }
+                b"spine" => package.ncx_id = get_attr(&e, b"toc"),
+                b"itemref" => {
+                    let linear = get_attr(&e, b"linear").unwrap_or_default();
+                    if let Some(idref) = get_attr(&e, b"idref")
+                        && linear != "no"
+                    {
+                        package.spine.push(idref);
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+
+    package
+}
+
+/// collects `href -> title` out of an EPUB3 nav document (`<nav epub:type="toc">`)
+fn parse_nav(nav: &str, nav_dir: &str, titles: &mut HashMap<String, String>) {
+    let mut reader = Reader::from_str(nav);
+    let mut in_toc = false;
+    let mut nav_depth = 0;
+    let mut current: Option<(String, String)> = None;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => match e.local_name().as_ref() {
+                b"nav" => {
+                    nav_depth += 1;
+                    let kind = get_attr(&e, b"type").unwrap_or_default();
+                    if kind.split_whitespace().any(|t| t == "toc") {
+                        in_toc = true;
+                    }
+                }
+                b"a" if in_toc => {
+                    if let Some(href) = get_attr(&e, b"href") {
+                        current = Some((resolve_href(nav_dir, &href), String::new()));
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Text(e)) => {
+                if let Some((_, title)) = current.as_mut() {
+                    title.push_str(&e.decode().unwrap_or_default());
+                }
+            }
+            Ok(Event::GeneralRef(e)) => {
+                if let Some((_, title)) = current.as_mut() {
+                    let name = e.decode().unwrap_or_default();
+                    if let Ok(Some(ch)) = e.resolve_char_ref() {
+                        title.push(ch);
+                    } else if let Some(v) = resolve_predefined_entity(&name) {
+                        title.push_str(v);
+                    }
+                }
+            }
+            Ok(Event::End(e)) => match e.local_name().as_ref() {
+                b"a" => {
+                    if let Some((href, title)) = current.take() {
+                        let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+                        if !title.is_empty() {
+                            titles.entry(href).or_insert(title);
+                        }
+                    }
+                }
+                b"nav" => {
+                    nav_depth -= 1;
+                    if nav_depth == 0 {
+                        in_toc = false;
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+}
+
+/// collects `href -> title` out of an EPUB2 NCX document
+fn parse_ncx(ncx: &str, ncx_dir: &str, titles: &mut HashMap<String, String>) {
+    let mut reader = Reader::from_str(ncx);
+    let mut in_label = false;
+    // titles of the open navPoints, filled when their label is read
+    let mut stack: Vec<String> = Vec::new();
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => match e.local_name().as_ref() {
+                b"navPoint" => stack.push(String::new()),
+                b"text" => in_label = true,
+                _ => {}
+            },
+            Ok(Event::Empty(e)) if e.local_name().as_ref() == b"content" => {
+                if let (Some(src), Some(title)) = (get_attr(&e, b"src"), stack.last()) {
+                    let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+                    if !title.is_empty() {
+                        titles.entry(resolve_href(ncx_dir, &src)).or_insert(title);
+                    }
+                }
+            }
+            Ok(Event::Text(e)) if in_label => {
+                if let Some(title) = stack.last_mut() {
+                    title.push_str(&e.decode().unwrap_or_default());
+                }
+            }
+            Ok(Event::End(e)) => match e.local_name().as_ref() {
+                b"navPoint" => {
+                    stack.pop();
+                }
+                b"text" => in_label = false,
+                _ => {}
+            },
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+}
+
+fn find_ci(haystack: &str, needle: &str, from: usize) -> Option<usize> {
+    haystack[from..]
+        .to_ascii_lowercase()
+        .find(needle)
+        .map(|i| i + from)
+}
+
+/// the inner html of `<body>`, and the content of `<title>`
+fn split_xhtml(xhtml: &str) -> (String, Option<String>) {
+    let title = find_ci(xhtml, "<title", 0).and_then(|start| {
+        let open_end = xhtml[start..].find('>')? + start + 1;
+        let close = find_ci(xhtml, "</title>", open_end)?;
+        let title = xhtml[open_end..close].trim();
+        (!title.is_empty()).then(|| title.to_string())
+    });
+
+    let body = find_ci(xhtml, "<body", 0)
+        .and_then(|start| {
+            let open_end = xhtml[start..].find('>')? + start + 1;
+            let close = find_ci(xhtml, "</body>", open_end).unwrap_or(xhtml.len());
+            Some(&xhtml[open_end..close])
+        })
+        .unwrap_or(xhtml);
+
+    // indented html would end up as markdown code blocks, `<pre>` keeps its whitespace
+    let mut in_pre = false;
+    let mut lines = Vec::new();
+    for line in body.lines() {
+        let lower = line.to_ascii_lowercase();
+        if in_pre {
+            lines.push(line);
+            in_pre = !lower.contains("</pre>");
+            continue;
+        }
+        let opened = lower.rfind("<pre>").or_else(|| lower.rfind("<pre "));
+        if let Some(open) = opened
+            && !lower[open..].contains("</pre>")
+        {
+            // what's after the tag is inside of it already
+            lines.push(line.trim_start());
+            in_pre = true;
+        } else if !line.trim().is_empty() {
+            lines.push(line.trim());
+        }
+    }
+    let body = lines.join("\n");
+
+    (body, title)
+}
+
+/// convert epub into markdown, chapters are emitted in reading (spine) order
+/// usuage:
+/// ```rs
+/// let path = Path::new("path/to/book.epub");
+/// let md = epub_convert(&path).unwrap();
+/// println!("{}", md);
+/// ```
+pub fn epub_convert(path: &Path) -> Result<String, ConvertError> {
+    let sections = epub_sections(path)?;
+    Ok(Document::new(sections).to_markdown())
+}
+
+/// same as [`epub_convert`], with a section per chapter
+pub fn epub_sections(path: &Path) -> Result<Vec<Section>, ConvertError> {
+    let file = File::open(path)?;
+    let mut archive = ZipArchive::new(file)?;
+
+    let container = read_entry(&mut archive, "META-INF/container.xml")
+        .ok_or_else(|| ConvertError::parse("epub is missing container.xml"))?;
+    let opf_path = find_rootfile(&container)
+        .ok_or_else(|| ConvertError::parse("epub container has no rootfile"))?;
+    let opf = read_entry(&mut archive, &opf_path)
+        .ok_or_else(|| ConvertError::parse("epub is missing its package file"))?;
+    let opf_dir = parent_dir(&opf_path);
+    let package = parse_package(&opf);
+
+    // table of contents, EPUB3 nav first then EPUB2 ncx
+    let mut titles = HashMap::new();
+    if let Some(nav) = package
+        .manifest
+        .values()
+        .find(|item| item.properties.split_whitespace().any(|p| p == "nav"))
+    {
+        let nav_path = resolve_href(opf_dir, &nav.href);
+        if let Some(content) = read_entry(&mut archive, &nav_path) {
+            parse_nav(&content, parent_dir(&nav_path), &mut titles);
+        }
+    }
+    let ncx = package
+        .ncx_id
+        .as_ref()
+        .and_then(|id| package.manifest.get(id))
+        .or_else(|| {
+            package
+                .manifest
+                .values()
+                .find(|item| item.media_type == "application/x-dtbncx+xml")
+        });
+    if let Some(ncx) = ncx {
+        let ncx_path = resolve_href(opf_dir, &ncx.href);
+        if let Some(content) = read_entry(&mut archive, &ncx_path) {
+            parse_ncx(&content, parent_dir(&ncx_path), &mut titles);
+        }
+    }
+
+    let mut sections = Vec::new();
+    for idref in &package.spine {
+        let item = match package.manifest.get(idref) {
+            Some(item) => item,
+            None => continue,
+        };
+        // fonts, css, images etc.. aren't chapters
+        if !matches!(
+            item.media_type.as_str(),
+            "application/xhtml+xml" | "text/html"
+        ) {
+            continue;
+        }
+
+        let chapter_path = resolve_href(opf_dir, &item.href);
+        let content = match read_entry(&mut archive, &chapter_path) {
+            Some(c) => c,
+            None => continue,
+        };
+        let (body, doc_title) = split_xhtml(&content);
+        if body.is_empty() {
+            continue;
+        }
+        let title = titles.get(&chapter_path).cloned().or(doc_title);
+        sections.push(Section::new(title, SectionKind::Chapter, body));
+    }
+
+    Ok(sections)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_xhtml;
+
+    #[test]
+    fn pre_keeps_its_whitespace() {
+        let xhtml = "<html><body>\n    <p>intro</p>\n    <pre>  fn main() {\n\n        run();\n    }  </pre>\n    <p>outro</p>\n</body></html>";
+        let (body, _) = split_xhtml(xhtml);
+        assert_eq!(
+            body,
+            "<p>intro</p>\n<pre>  fn main() {\n\n        run();\n    }  </pre>\n<p>outro</p>"
+        );
+    }
+}
@@ -0,0 +1,124 @@
+use std::{error::Error, fmt, io};
+
+use zip::result::ZipError;
+
+/// why a conversion failed
+#[derive(Debug)]
+pub enum ConvertError {
+    /// the file couldn't be read
+    Io(io::Error),
+    /// the file has no converter and isn't text either
+    UnsupportedFormat { ext: String },
+    /// the file is corrupt, or isn't what its extension says
+    Parse { detail: String },
+    /// the zip container (docx, pptx, epub, odt, zip..) couldn't be opened
+    Zip(ZipError),
+    /// the requested pages or sheet don't exist in the document
+    OutOfRange { detail: String },
+    /// an external tool (tesseract, pdftocairo) is missing or failed
+    Tool { detail: String },
+}
+
+impl ConvertError {
+    pub(crate) fn parse(detail: impl Into<String>) -> Self {
+        ConvertError::Parse {
+            detail: detail.into(),
+        }
+    }
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConvertError::Io(err) => write!(f, "{err}"),
+            ConvertError::UnsupportedFormat { ext } => write!(f, "unsupported format: .{ext}"),
+            ConvertError::Parse { detail } => write!(f, "{detail}"),
+            ConvertError::Zip(err) => write!(f, "{err}"),
+            ConvertError::OutOfRange { detail } => write!(f, "{detail}"),
+            ConvertError::Tool { detail } => write!(f, "{detail}"),
+        }
+    }
+}
+
+impl Error for ConvertError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ConvertError::Io(err) => Some(err),
+            ConvertError::Zip(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ConvertError {
+    fn from(err: io::Error) -> Self {
+        ConvertError::Io(err)
+    }
+}
+
+impl From<ZipError> for ConvertError {
+    fn from(err: ZipError) -> Self {
+        match err {
+            ZipError::Io(err) => ConvertError::Io(err),
+            err => ConvertError::Zip(err),
+        }
+    }
+}
+
+impl From<quick_xml::Error> for ConvertError {
+    fn from(err: quick_xml::Error) -> Self {
+        ConvertError::parse(err.to_string())
+    }
+}
+
+impl From<quick_xml::encoding::EncodingError> for ConvertError {
+    fn from(err: quick_xml::encoding::EncodingError) -> Self {
+        ConvertError::parse(err.to_string())
+    }
+}
+
+impl From<serde_json::Error> for ConvertError {
+    fn from(err: serde_json::Error) -> Self {
+        ConvertError::parse(err.to_string())
+    }
+}
+
+impl From<csv::Error> for ConvertError {
+    fn from(err: csv::Error) -> Self {
+        if !err.is_io_error() {
+            return ConvertError::parse(err.to_string());
+        }
+        match err.into_kind() {
+            csv::ErrorKind::Io(err) => ConvertError::Io(err),
+            kind => ConvertError::parse(format!("{kind:?}")),
+        }
+    }
+}
+
+impl From<calamine::Error> for ConvertError {
+    fn from(err: calamine::Error) -> Self {
+        match err {
+            calamine::Error::Io(err) => ConvertError::Io(err),
+            err => ConvertError::parse(err.to_string()),
+        }
+    }
+}
+
+impl From<lopdf::Error> for ConvertError {
+    fn from(err: lopdf::Error) -> Self {
+        match err {
+            lopdf::Error::IO(err) => ConvertError::Io(err),
+            err => ConvertError::parse(err.to_string()),
+        }
+    }
+}
+
+/// the pdf content stream is walked with boxed errors, they're all parse errors
+impl From<Box<dyn Error>> for ConvertError {
+    fn from(err: Box<dyn Error>) -> Self {
+        match err.downcast::<ConvertError>() {
+            Ok(err) => *err,
+            Err(err) => ConvertError::parse(err.to_string()),
+        }
+    }
+}
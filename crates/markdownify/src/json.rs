@@ -0,0 +1,104 @@
+use super::sheets;
+use crate::ConvertError;
+use serde_json::{Map, Value};
+use std::path::Path;
+
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.replace(['\r', '\n'], " "),
+        // nested values are kept compact, on one line, so they don't break the table
+        _ => value.to_string(),
+    }
+}
+
+/// a table out of objects, headers are the union of their keys in order of appearance
+fn objects_to_table(objects: &[&Map<String, Value>]) -> String {
+    let mut headers: Vec<String> = Vec::new();
+    for object in objects {
+        for key in object.keys() {
+            if !headers.contains(key) {
+                headers.push(key.clone());
+            }
+        }
+    }
+    let rows = objects
+        .iter()
+        .map(|object| {
+            headers
+                .iter()
+                .map(|key| object.get(key).map(cell).unwrap_or_default())
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    sheets::to_markdown_table(&headers, &rows)
+}
+
+/// a table when `value` is an array of objects, otherwise pretty printed json
+fn to_markdown(value: &Value) -> Result<String, ConvertError> {
+    let objects = value
+        .as_array()
+        .filter(|values| !values.is_empty())
+        .and_then(|values| {
+            values
+                .iter()
+                .map(|v| v.as_object())
+                .collect::<Option<Vec<_>>>()
+        });
+
+    match objects {
+        Some(objects) => Ok(objects_to_table(&objects)),
+        None => Ok(format!(
+            "```json\n{}\n```",
+            serde_json::to_string_pretty(value)?
+        )),
+    }
+}
+
+/// convert `json` into markdown, an array of objects becomes a table,
+/// anything else is pretty printed inside a json codeblock
+/// # usage:
+/// ```
+/// use std::path::Path;
+/// use markdownify::json::json_convert;
+///
+/// let path = Path::new("path/to/file.json");
+/// match json_convert(&path) {
+///     Ok(md) => println!("{}", md),
+///     Err(e) => eprintln!("Error: {}", e)
+/// }
+/// ```
+pub fn json_convert(path: &Path) -> Result<String, ConvertError> {
+    let content = std::fs::read_to_string(path)?;
+    let value: Value = serde_json::from_str(&content)?;
+
+    to_markdown(&value)
+}
+
+/// convert `jsonl` | `ndjson` (an object per line) into markdown
+/// # usage:
+/// ```
+/// use std::path::Path;
+/// use markdownify::json::jsonl_convert;
+///
+/// let path = Path::new("path/to/file.jsonl");
+/// match jsonl_convert(&path) {
+///     Ok(md) => println!("{}", md),
+///     Err(e) => eprintln!("Error: {}", e)
+/// }
+/// ```
+pub fn jsonl_convert(path: &Path) -> Result<String, ConvertError> {
+    let content = std::fs::read_to_string(path)?;
+    let values = content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str::<Value>(line)
+                .map_err(|e| ConvertError::parse(format!("line {}: {}", i + 1, e)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    to_markdown(&Value::Array(values))
+}
@@ -1,23 +1,45 @@
+mod document;
 pub mod docx;
+pub mod epub;
+pub mod error;
+pub mod json;
+pub mod metadata;
 pub mod opendoc;
 pub mod pdf;
 pub mod pptx;
+pub mod rtf;
 pub mod sheets;
 
+pub use document::{Document, Section, SectionKind};
+pub use error::ConvertError;
+
 use chardetng::EncodingDetector;
 use encoding_rs::Encoding;
+use flate2::read::GzDecoder;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use std::{
     borrow::Cow,
     fs::{self, File},
+    io::{self, Read},
+    ops::RangeInclusive,
     path::{Path, PathBuf},
 };
-use tempfile::Builder;
+use tar::Archive;
+use tempfile::{Builder, NamedTempFile};
 use zip::ZipArchive;
 
 pub struct ConvertOptions<'a> {
     pub path: Cow<'a, Path>,
     pub name_header: Option<&'a str>,
     pub screen_size: Option<(u16, u16)>,
+    pub extract_images: Option<PathBuf>,
+    pub pages: Option<RangeInclusive<usize>>,
+    pub sheet: Option<String>,
+    pub sheet_index: Option<usize>,
+    pub csv_delimiter: Option<u8>,
+    pub include_notes: bool,
+    pub ocr: bool,
+    pub keep_headers: bool,
 }
 impl<'a> ConvertOptions<'a> {
     pub fn new(path: impl Into<ConvertOptions<'a>>) -> Self {
@@ -31,6 +53,38 @@ impl<'a> ConvertOptions<'a> {
         self.screen_size = Some(screen_size);
         self
     }
+    pub fn with_pages(mut self, pages: RangeInclusive<usize>) -> Self {
+        self.pages = Some(pages);
+        self
+    }
+    pub fn with_sheet(mut self, sheet: impl Into<String>) -> Self {
+        self.sheet = Some(sheet.into());
+        self
+    }
+    pub fn with_sheet_index(mut self, sheet_index: usize) -> Self {
+        self.sheet_index = Some(sheet_index);
+        self
+    }
+    pub fn with_csv_delimiter(mut self, delimiter: u8) -> Self {
+        self.csv_delimiter = Some(delimiter);
+        self
+    }
+    pub fn with_notes(mut self, include_notes: bool) -> Self {
+        self.include_notes = include_notes;
+        self
+    }
+    pub fn with_ocr(mut self, ocr: bool) -> Self {
+        self.ocr = ocr;
+        self
+    }
+    pub fn with_keep_headers(mut self, keep_headers: bool) -> Self {
+        self.keep_headers = keep_headers;
+        self
+    }
+    pub fn with_extract_images(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.extract_images = Some(dir.into());
+        self
+    }
 }
 impl<'a> From<&'a str> for ConvertOptions<'a> {
     fn from(value: &'a str) -> Self {
@@ -38,6 +92,14 @@ impl<'a> From<&'a str> for ConvertOptions<'a> {
             path: Cow::Owned(PathBuf::from(value)),
             name_header: None,
             screen_size: None,
+            extract_images: None,
+            pages: None,
+            sheet: None,
+            sheet_index: None,
+            csv_delimiter: None,
+            include_notes: false,
+            ocr: false,
+            keep_headers: false,
         }
     }
 }
@@ -47,6 +109,14 @@ impl<'a> From<&'a Path> for ConvertOptions<'a> {
             path: Cow::Borrowed(value),
             name_header: None,
             screen_size: None,
+            extract_images: None,
+            pages: None,
+            sheet: None,
+            sheet_index: None,
+            csv_delimiter: None,
+            include_notes: false,
+            ocr: false,
+            keep_headers: false,
         }
     }
 }
@@ -56,6 +126,14 @@ impl<'a> From<PathBuf> for ConvertOptions<'a> {
             path: Cow::Owned(value),
             name_header: None,
             screen_size: None,
+            extract_images: None,
+            pages: None,
+            sheet: None,
+            sheet_index: None,
+            csv_delimiter: None,
+            include_notes: false,
+            ocr: false,
+            keep_headers: false,
         }
     }
 }
@@ -73,12 +151,19 @@ pub fn get_encoding(buffer: &[u8], limit: usize) -> &'static Encoding {
 /// Options allow you to customize the conversion process:
 /// - Add a header to the markdown output
 /// - Specify screen size for PDF conversion (affects layout detection quality)
+/// - Only convert a range of pages (PDF, 1-based)
+/// - Only convert a single sheet, by name or by 1-based index (spreadsheets)
+/// - Force the CSV delimiter instead of detecting it
+/// - Include speaker notes (pptx)
+/// - OCR scanned pages with tesseract (PDF)
+/// - Extract embedded images (docx) into a directory instead of inlining them as base64
 ///
 /// # Supported Formats
 /// - **Text/Markup**: `.csv`, `.md`, `.html` (direct conversion)
-/// - **Documents**: `.docx`, `.pdf`, `.pptx`, `.odt`, `.odp`
+/// - **Documents**: `.docx`, `.pdf`, `.pptx`, `.odt`, `.odp`, `.epub`, `.rtf`
+/// - **Data**: `.json`, `.jsonl`, `.ndjson` (arrays of objects become tables)
 /// - **Spreadsheets**: `.xlsx`, `.xls`, `.xlsm`, `.xlsb`, `.xla`, `.xlam`, `.ods`
-/// - **Archives**: `.zip` (extracts and converts contents)
+/// - **Archives**: `.zip`, `.tar`, `.tar.gz`, `.tgz` (extracts and converts contents)
 /// - **Fallback**: Any other text file will be converted with basic formatting
 ///
 /// # Examples
@@ -95,26 +180,91 @@ pub fn get_encoding(buffer: &[u8], limit: usize) -> &'static Encoding {
 /// }
 /// ```
 ///
-/// ## With options for name header and PDF screen size:
+/// ## With options for name header, PDF screen size and page range:
 /// ```
 /// use markdownify::{convert, ConvertOptions};
 ///
 /// let opts = ConvertOptions::new("path/to/file.pdf")
 ///     .with_name_header("My Document")
-///     .with_screen_size((100, 20)); // Better layout detection for PDFs (in cells - width,height)
+///     .with_screen_size((100, 20)) // Better layout detection for PDFs (in cells - width,height)
+///     .with_pages(3..=7); // Only pages 3 to 7
 ///
 /// match convert(opts) {
 ///     Ok(md) => println!("{}", md),
 ///     Err(e) => eprintln!("Error: {}", e)
 /// }
 /// ```
-pub fn convert<'a>(
+pub fn convert<'a>(path_or_opts: impl Into<ConvertOptions<'a>>) -> Result<String, ConvertError> {
+    let path_or_opts = path_or_opts.into();
+    let name_header = path_or_opts.name_header;
+    let result = convert_structured(path_or_opts)?.to_markdown();
+
+    let result = match name_header {
+        Some(name) => format!("<!-- S-TITLE: {name} -->\n{result}\n---"),
+        None => result,
+    };
+
+    Ok(result)
+}
+
+/// checks if files with `ext` have their own converter, anything else is read as plain text
+pub fn has_converter(ext: &str) -> bool {
+    matches!(
+        ext.to_lowercase().as_str(),
+        "epub"
+            | "pdf"
+            | "pptx"
+            | "xlsx"
+            | "xls"
+            | "xlsm"
+            | "xlsb"
+            | "xla"
+            | "xlam"
+            | "ods"
+            | "zip"
+            | "tar"
+            | "tgz"
+            | "gz"
+            | "csv"
+            | "docx"
+            | "json"
+            | "jsonl"
+            | "ndjson"
+            | "rtf"
+            | "odt"
+            | "odp"
+            | "md"
+            | "html"
+    )
+}
+
+/// Convert any document into a [`Document`], split into its pages / slides / sheets / chapters.
+/// formats that have no natural split are a single [`SectionKind::Whole`] section.
+///
+/// takes the same options as [`convert`], except `name_header` which is ignored.
+/// # usage:
+/// ```
+/// use markdownify::{convert_structured, SectionKind};
+///
+/// match convert_structured("path/to/file.pdf") {
+///     Ok(doc) => {
+///         for section in doc.sections.iter().filter(|s| s.kind == SectionKind::Page) {
+///             println!("{:?}\n{}", section.title, section.markdown);
+///         }
+///     }
+///     Err(e) => eprintln!("Error: {}", e)
+/// }
+/// ```
+pub fn convert_structured<'a>(
     path_or_opts: impl Into<ConvertOptions<'a>>,
-) -> Result<String, Box<dyn std::error::Error>> {
+) -> Result<Document, ConvertError> {
     let path_or_opts = path_or_opts.into();
     let path = path_or_opts.path;
     if !path.is_file() {
-        return Err(format!("Unknown path type for {}", path.display()).into());
+        return Err(ConvertError::Io(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Unknown path type for {}", path.display()),
+        )));
     }
 
     let ext = path
@@ -122,32 +272,54 @@ pub fn convert<'a>(
         .unwrap_or_default()
         .to_string_lossy()
         .to_lowercase();
+    let is_tar_gz = ext == "gz"
+        && path
+            .file_stem()
+            .is_some_and(|stem| stem.to_string_lossy().to_lowercase().ends_with(".tar"));
 
-    let result = match ext.as_str() {
-        "csv" => sheets::csv_converter(&path)?,
-        "docx" => docx::docx_convert(&path)?,
-        "pdf" => pdf::pdf_convert(&path, path_or_opts.screen_size)?,
-        "pptx" => pptx::pptx_converter(&path)?,
-        "xlsx" | "xls" | "xlsm" | "xlsb" | "xla" | "xlam" | "ods" => sheets::sheets_convert(&path)?,
-        "zip" => zip_convert(&path)?,
-        "odt" => opendoc::opendoc_convert(&path)?,
-        "odp" => opendoc::opendoc_convert(&path)?,
-        "md" | "html" => {
-            let res = fs::read_to_string(path)?;
-            format!("{}\n\n", res)
-        }
-        _ => {
-            let content = fs::read_to_string(path)?;
-            markitdown_fallback(&content, &ext)
+    let document = match ext.as_str() {
+        "epub" => Document::new(epub::epub_sections(&path)?),
+        "pdf" => Document::new(pdf::pdf_sections(
+            &path,
+            path_or_opts.screen_size,
+            path_or_opts.pages,
+            path_or_opts.ocr,
+            path_or_opts.keep_headers,
+        )?),
+        "pptx" => Document::new(pptx::pptx_sections(&path, path_or_opts.include_notes)?),
+        "xlsx" | "xls" | "xlsm" | "xlsb" | "xla" | "xlam" | "ods" => {
+            Document::new(sheets::sheets_sections(
+                &path,
+                path_or_opts.sheet.as_deref(),
+                path_or_opts.sheet_index,
+            )?)
         }
+        "zip" => Document::new(convert_entries(extract_zip(&path)?)),
+        "tar" | "tgz" => Document::new(convert_entries(extract_tar(&path)?)),
+        "gz" if is_tar_gz => Document::new(convert_entries(extract_tar(&path)?)),
+        _ => Document::whole(match ext.as_str() {
+            "csv" => sheets::csv_converter(&path, path_or_opts.csv_delimiter)?,
+            "docx" => docx::docx_convert(&path, path_or_opts.extract_images.as_deref())?,
+            "json" => json::json_convert(&path)?,
+            "jsonl" | "ndjson" => json::jsonl_convert(&path)?,
+            "rtf" => rtf::rtf_convert(&path)?,
+            "odt" => opendoc::opendoc_convert(&path)?,
+            "odp" => opendoc::opendoc_convert(&path)?,
+            "md" | "html" => {
+                let res = fs::read_to_string(path)?;
+                format!("{}\n\n", res)
+            }
+            _ => match fs::read_to_string(&path) {
+                Ok(content) => markitdown_fallback(&content, &ext),
+                Err(err) if err.kind() == io::ErrorKind::InvalidData => {
+                    return Err(ConvertError::UnsupportedFormat { ext });
+                }
+                Err(err) => return Err(err.into()),
+            },
+        }),
     };
 
-    let result = match path_or_opts.name_header {
-        Some(name) => format!("<!-- S-TITLE: {name} -->\n{result}\n---"),
-        None => result,
-    };
-
-    Ok(result)
+    Ok(document)
 }
 
 /// convert `zip` into markdown
@@ -162,11 +334,19 @@ pub fn convert<'a>(
 ///     Err(e) => eprintln!("Error: {}", e)
 /// }
 /// ```
-pub fn zip_convert(path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+pub fn zip_convert(path: &Path) -> Result<String, ConvertError> {
+    let sections = convert_entries(extract_zip(path)?);
+    Ok(Document::new(sections).to_markdown())
+}
+
+type Entries = Vec<(String, NamedTempFile)>;
+
+fn extract_zip(path: &Path) -> Result<Entries, ConvertError> {
     let file = File::open(path)?;
     let mut archive = ZipArchive::new(file)?;
 
-    let mut output = String::new();
+    // extract first, the archive itself can't be read from multiple threads
+    let mut entries = Vec::new();
     for i in 0..archive.len() {
         let mut entry = archive.by_index(i)?;
         let name = entry.name().to_string();
@@ -183,19 +363,116 @@ pub fn zip_convert(path: &Path) -> Result<String, Box<dyn std::error::Error>> {
             .suffix(&format!(".{}", extension))
             .tempfile()?;
         std::io::copy(&mut entry, &mut temp)?;
-        let temp_path = temp.path().to_path_buf(); // clone path before move
+        entries.push((name, temp));
+    }
+
+    Ok(entries)
+}
+
+/// convert `tar`, `tar.gz` or `tgz` into markdown
+/// # usage:
+/// ```
+/// use std::path::Path;
+/// use markdownify::tar_convert;
+///
+/// let path = Path::new("path/to/archive.tar.gz");
+/// match tar_convert(&path) {
+///     Ok(md) => println!("{}", md),
+///     Err(e) => eprintln!("Error: {}", e)
+/// }
+/// ```
+pub fn tar_convert(path: &Path) -> Result<String, ConvertError> {
+    let sections = convert_entries(extract_tar(path)?);
+    Ok(Document::new(sections).to_markdown())
+}
 
-        // convert using original convert function
-        let md = match convert(temp_path) {
-            Ok(result) => result,
-            Err(err) => format!("**[Failed Reading: {}]**", err),
-        };
-        output += &format!("# `{}`\n\n{}\n\n", name, md);
+fn extract_tar(path: &Path) -> Result<Entries, ConvertError> {
+    let file = File::open(path)?;
+    let name = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_lowercase();
+    let reader: Box<dyn Read> = if name.ends_with(".gz") || name.ends_with(".tgz") {
+        Box::new(GzDecoder::new(file))
+    } else {
+        Box::new(file)
+    };
+    let mut archive = Archive::new(reader);
+
+    let mut entries = Vec::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if entry.header().entry_type().is_dir() {
+            continue;
+        }
+        let name = entry.path()?.to_string_lossy().into_owned();
+        let extension = Path::new(&name)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("");
+
+        let mut temp = Builder::new()
+            .suffix(&format!(".{}", extension))
+            .tempfile()?;
+        std::io::copy(&mut entry, &mut temp)?;
+        entries.push((name, temp));
     }
 
-    Ok(output)
+    Ok(entries)
+}
+
+/// converts extracted archive entries in parallel, keeping their order
+fn convert_entries(entries: Entries) -> Vec<Section> {
+    entries
+        .par_iter()
+        .map(|(name, temp)| {
+            // convert using original convert function
+            let md = match convert(temp.path()) {
+                Ok(result) => result,
+                Err(err) => format!("**[Failed Reading: {}]**", err),
+            };
+            Section::new(Some(name.clone()), SectionKind::Entry, md)
+        })
+        .collect()
 }
 
 fn markitdown_fallback(content: &String, ext: &String) -> String {
     format!("```{}\n{}\n```", ext, content)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{ConvertError, convert};
+    use std::io::Write;
+    use tempfile::Builder;
+
+    fn temp_file(suffix: &str, content: &[u8]) -> tempfile::NamedTempFile {
+        let mut file = Builder::new().suffix(suffix).tempfile().unwrap();
+        file.write_all(content).unwrap();
+        file
+    }
+
+    #[test]
+    fn errors_tell_apart_unsupported_and_corrupt() {
+        assert!(matches!(
+            convert("does/not/exist.docx"),
+            Err(ConvertError::Io(_))
+        ));
+
+        let binary = temp_file(".bin", &[0xff, 0xfe, 0x00, 0x81]);
+        match convert(binary.path()) {
+            Err(ConvertError::UnsupportedFormat { ext }) => assert_eq!(ext, "bin"),
+            other => panic!("expected UnsupportedFormat, got {:?}", other),
+        }
+
+        let corrupt = temp_file(".docx", b"not a zip");
+        assert!(matches!(convert(corrupt.path()), Err(ConvertError::Zip(_))));
+
+        let rtf = temp_file(".rtf", b"plain text");
+        assert!(matches!(
+            convert(rtf.path()),
+            Err(ConvertError::Parse { .. })
+        ));
+    }
+}
@@ -0,0 +1,182 @@
+use calamine::Reader as _;
+use quick_xml::events::Event;
+use quick_xml::reader::Reader;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use crate::ConvertError;
+use zip::ZipArchive;
+
+/// what a document is made of, and who made it. every field is optional,
+/// formats only fill what they have
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DocMeta {
+    pub pages: Option<usize>,
+    pub slides: Option<usize>,
+    pub sheets: Option<usize>,
+    pub paragraphs: Option<usize>,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub created: Option<String>,
+}
+
+/// reads the [`DocMeta`] of pdf, docx, pptx and spreadsheets without converting them
+/// # usage:
+/// ```
+/// use std::path::Path;
+/// use markdownify::metadata::metadata;
+///
+/// let path = Path::new("path/to/file.docx");
+/// match metadata(&path) {
+///     Ok(meta) => println!("{:?} by {:?}", meta.title, meta.author),
+///     Err(e) => eprintln!("Error: {}", e)
+/// }
+/// ```
+pub fn metadata(path: &Path) -> Result<DocMeta, ConvertError> {
+    let ext = path
+        .extension()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_lowercase();
+
+    match ext.as_str() {
+        "pdf" => pdf_metadata(path),
+        "docx" => {
+            let mut archive = ZipArchive::new(File::open(path)?)?;
+            let mut meta = core_properties(&mut archive);
+            meta.paragraphs =
+                read_entry(&mut archive, "word/document.xml").map(|xml| count_paragraphs(&xml));
+            Ok(meta)
+        }
+        "pptx" => {
+            let mut archive = ZipArchive::new(File::open(path)?)?;
+            let mut meta = core_properties(&mut archive);
+            let slides = archive
+                .file_names()
+                .filter(|name| {
+                    name.strip_prefix("ppt/slides/slide")
+                        .is_some_and(|rest| rest.ends_with(".xml"))
+                })
+                .count();
+            meta.slides = Some(slides);
+            Ok(meta)
+        }
+        "xlsx" | "xls" | "xlsm" | "xlsb" | "xla" | "xlam" | "ods" => {
+            let mut meta = match ext.as_str() {
+                "xlsx" | "xlsm" | "xlsb" | "xlam" => ZipArchive::new(File::open(path)?)
+                    .map(|mut archive| core_properties(&mut archive))
+                    .unwrap_or_default(),
+                _ => DocMeta::default(),
+            };
+            let workbook = calamine::open_workbook_auto(path)?;
+            meta.sheets = Some(workbook.sheet_names().len());
+            Ok(meta)
+        }
+        _ => Err(ConvertError::UnsupportedFormat { ext }),
+    }
+}
+
+fn pdf_metadata(path: &Path) -> Result<DocMeta, ConvertError> {
+    let doc = lopdf::Document::load(path)?;
+    let info = doc.trailer.get(b"Info").ok().and_then(|info| match info {
+        lopdf::Object::Reference(id) => doc.get_dictionary(*id).ok(),
+        lopdf::Object::Dictionary(dict) => Some(dict),
+        _ => None,
+    });
+    let field = |key: &[u8]| {
+        let value = info?.get(key).ok()?;
+        let value = match value {
+            lopdf::Object::Reference(id) => doc.get_object(*id).ok()?,
+            value => value,
+        };
+        let text = lopdf::decode_text_string(value).ok()?;
+        let text = text.trim();
+        (!text.is_empty()).then(|| text.to_string())
+    };
+
+    Ok(DocMeta {
+        pages: Some(doc.get_pages().len()),
+        title: field(b"Title"),
+        author: field(b"Author"),
+        created: field(b"CreationDate").map(|d| pdf_date(&d)),
+        ..Default::default()
+    })
+}
+
+/// `D:20240131120000+02'00'` into `2024-01-31 12:00:00`, anything else is kept as is
+fn pdf_date(date: &str) -> String {
+    let digits = date.strip_prefix("D:").unwrap_or(date);
+    let digits: String = digits.chars().take_while(|c| c.is_ascii_digit()).collect();
+    if digits.len() < 8 {
+        return date.to_string();
+    }
+    let mut out = format!("{}-{}-{}", &digits[0..4], &digits[4..6], &digits[6..8]);
+    if digits.len() >= 14 {
+        out.push_str(&format!(
+            " {}:{}:{}",
+            &digits[8..10],
+            &digits[10..12],
+            &digits[12..14]
+        ));
+    }
+    out
+}
+
+fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> Option<String> {
+    let mut entry = archive.by_name(name).ok()?;
+    let mut buf = String::new();
+    entry.read_to_string(&mut buf).ok()?;
+    Some(buf)
+}
+
+/// title, author and creation date out of `docProps/core.xml`, shared by all office formats
+fn core_properties(archive: &mut ZipArchive<File>) -> DocMeta {
+    let mut meta = DocMeta::default();
+    let Some(xml) = read_entry(archive, "docProps/core.xml") else {
+        return meta;
+    };
+
+    let mut reader = Reader::from_str(&xml);
+    let mut current: Option<Vec<u8>> = None;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => current = Some(e.local_name().as_ref().to_vec()),
+            Ok(Event::Text(e)) => {
+                let text = e.decode().unwrap_or_default().trim().to_string();
+                if text.is_empty() {
+                    continue;
+                }
+                match current.as_deref() {
+                    Some(b"title") => meta.title = Some(text),
+                    Some(b"creator") => meta.author = Some(text),
+                    Some(b"created") => meta.created = Some(text),
+                    _ => {}
+                }
+            }
+            Ok(Event::End(_)) => current = None,
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    meta
+}
+
+/// paragraphs of a docx body that have any text in them, like word counts them
+fn count_paragraphs(xml: &str) -> usize {
+    let mut reader = Reader::from_str(xml);
+    let mut count = 0;
+    let mut has_text = false;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) if e.name().as_ref() == b"w:p" => has_text = false,
+            Ok(Event::Text(e)) if !e.decode().unwrap_or_default().trim().is_empty() => {
+                has_text = true
+            }
+            Ok(Event::End(e)) if e.name().as_ref() == b"w:p" && has_text => count += 1,
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    count
+}
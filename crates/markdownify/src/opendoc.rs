@@ -5,6 +5,7 @@ use std::path::Path;
 use zip::ZipArchive;
 
 use super::sheets;
+use crate::ConvertError;
 
 /// convert `odt` and `odp` files into markdown
 /// # usage:
@@ -18,7 +19,7 @@ use super::sheets;
 ///     Err(e) => eprintln!("Error: {}", e)
 /// }
 /// ```
-pub fn opendoc_convert(path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+pub fn opendoc_convert(path: &Path) -> Result<String, ConvertError> {
     let data = std::fs::read(path)?;
     let cursor = Cursor::new(data);
     let mut archive = ZipArchive::new(cursor)?;
@@ -105,9 +106,11 @@ pub fn opendoc_convert(path: &Path) -> Result<String, Box<dyn std::error::Error>
             },
             Ok(Event::Eof) => break,
             Err(e) => {
-                return Err(
-                    format!("Error at position {}: {:?}", reader.buffer_position(), e).into(),
-                );
+                return Err(ConvertError::parse(format!(
+                    "Error at position {}: {:?}",
+                    reader.buffer_position(),
+                    e
+                )));
             }
             _ => {}
         }
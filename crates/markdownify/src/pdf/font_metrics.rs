@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+
+use lopdf::{Dictionary, Document, Object};
+
+// used when the font doesn't say, about the average of a latin font
+const FALLBACK_WIDTH: f32 = 500.0;
+
+/// glyph widths of a font, in thousandths of the font size
+#[derive(Debug, Clone)]
+pub struct FontMetrics {
+    widths: HashMap<u32, f32>,
+    default_width: f32,
+    // composite (Type0) fonts, mostly Identity-H, which use 2 bytes per glyph
+    two_byte: bool,
+}
+
+impl Default for FontMetrics {
+    fn default() -> Self {
+        FontMetrics {
+            widths: HashMap::new(),
+            default_width: FALLBACK_WIDTH,
+            two_byte: false,
+        }
+    }
+}
+
+fn resolve<'a>(doc: &'a Document, obj: &'a Object) -> &'a Object {
+    doc.dereference(obj).map(|(_, obj)| obj).unwrap_or(obj)
+}
+
+fn get<'a>(doc: &'a Document, dict: &'a Dictionary, key: &[u8]) -> Option<&'a Object> {
+    dict.get(key).ok().map(|obj| resolve(doc, obj))
+}
+
+fn number(obj: &Object) -> Option<f32> {
+    obj.as_float().ok()
+}
+
+impl FontMetrics {
+    pub fn from_font(doc: &Document, font: &Dictionary) -> FontMetrics {
+        let is_type0 = get(doc, font, b"Subtype")
+            .and_then(|s| s.as_name().ok())
+            .is_some_and(|s| s == b"Type0");
+        match is_type0 {
+            true => FontMetrics::from_type0(doc, font),
+            false => FontMetrics::from_simple(doc, font),
+        }
+    }
+
+    fn from_simple(doc: &Document, font: &Dictionary) -> FontMetrics {
+        let first_char = get(doc, font, b"FirstChar")
+            .and_then(|c| c.as_i64().ok())
+            .unwrap_or(0)
+            .max(0) as u32;
+        let widths = get(doc, font, b"Widths")
+            .and_then(|w| w.as_array().ok())
+            .map(|w| {
+                w.iter()
+                    .enumerate()
+                    .filter_map(|(i, w)| Some((first_char + i as u32, number(resolve(doc, w))?)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let default_width = get(doc, font, b"FontDescriptor")
+            .and_then(|d| d.as_dict().ok())
+            .and_then(|d| get(doc, d, b"MissingWidth"))
+            .and_then(number)
+            .filter(|w| *w > 0.0)
+            .unwrap_or(FALLBACK_WIDTH);
+
+        FontMetrics {
+            widths,
+            default_width,
+            two_byte: false,
+        }
+    }
+
+    fn from_type0(doc: &Document, font: &Dictionary) -> FontMetrics {
+        let descendant = get(doc, font, b"DescendantFonts")
+            .and_then(|d| d.as_array().ok())
+            .and_then(|d| d.first())
+            .and_then(|d| resolve(doc, d).as_dict().ok());
+        let Some(descendant) = descendant else {
+            return FontMetrics {
+                two_byte: true,
+                ..Default::default()
+            };
+        };
+
+        let default_width = get(doc, descendant, b"DW")
+            .and_then(number)
+            .unwrap_or(1000.0);
+        // `W` is a list of either `first [w1 w2 ...]` or `first last w`
+        let mut widths = HashMap::new();
+        if let Some(w) = get(doc, descendant, b"W").and_then(|w| w.as_array().ok()) {
+            let items: Vec<&Object> = w.iter().map(|o| resolve(doc, o)).collect();
+            let mut i = 0;
+            while i + 1 < items.len() {
+                let Ok(first) = items[i].as_i64() else {
+                    break;
+                };
+                let first = first.max(0) as u32;
+                match items[i + 1] {
+                    Object::Array(list) => {
+                        for (j, w) in list.iter().enumerate() {
+                            if let Some(w) = number(resolve(doc, w)) {
+                                widths.insert(first + j as u32, w);
+                            }
+                        }
+                        i += 2;
+                    }
+                    last => {
+                        let (Ok(last), Some(w)) =
+                            (last.as_i64(), items.get(i + 2).and_then(|w| number(w)))
+                        else {
+                            break;
+                        };
+                        // a broken range shouldn't allocate the whole code space
+                        for code in first..=(last.max(0) as u32).min(first + 0xFFFF) {
+                            widths.insert(code, w);
+                        }
+                        i += 3;
+                    }
+                }
+            }
+        }
+
+        FontMetrics {
+            widths,
+            default_width,
+            two_byte: true,
+        }
+    }
+
+    /// how far `bytes` shown in this font move the text position,
+    /// in text space units before horizontal scaling
+    pub fn advance(
+        &self,
+        bytes: &[u8],
+        font_size: f32,
+        char_spacing: f32,
+        word_spacing: f32,
+    ) -> f32 {
+        let codes: Vec<u32> = match self.two_byte {
+            true => bytes
+                .chunks(2)
+                .map(|c| match c {
+                    [hi, lo] => u32::from(*hi) << 8 | u32::from(*lo),
+                    [b] => u32::from(*b),
+                    _ => 0,
+                })
+                .collect(),
+            false => bytes.iter().map(|b| u32::from(*b)).collect(),
+        };
+        codes
+            .iter()
+            .map(|code| {
+                let width = self.widths.get(code).copied().unwrap_or(self.default_width);
+                // word spacing only applies to the single byte space
+                let word = match !self.two_byte && *code == 32 {
+                    true => word_spacing,
+                    false => 0.0,
+                };
+                width / 1000.0 * font_size + char_spacing + word
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lopdf::{Dictionary, Document, Object};
+
+    use super::FontMetrics;
+
+    fn int(i: i64) -> Object {
+        Object::Integer(i)
+    }
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-4, "{a} != {b}");
+    }
+
+    #[test]
+    fn simple_font_widths() {
+        let mut descriptor = Dictionary::new();
+        descriptor.set("MissingWidth", int(250));
+        let mut font = Dictionary::new();
+        font.set("Subtype", "Type1");
+        font.set("FirstChar", int(65));
+        font.set("Widths", vec![int(600), int(700)]);
+        font.set("FontDescriptor", descriptor);
+
+        let metrics = FontMetrics::from_font(&Document::new(), &font);
+        // "AB" at 10pt, then a missing glyph and a space with word spacing
+        assert_close(metrics.advance(b"AB", 10.0, 0.0, 0.0), 13.0);
+        assert_close(metrics.advance(b"Z", 10.0, 0.0, 0.0), 2.5);
+        assert_close(metrics.advance(b" ", 10.0, 1.0, 2.0), 5.5);
+    }
+
+    #[test]
+    fn type0_w_array() {
+        let mut descendant = Dictionary::new();
+        descendant.set("DW", int(800));
+        // `1 [100 200]` then `10 12 300`
+        descendant.set(
+            "W",
+            vec![
+                int(1),
+                Object::Array(vec![int(100), int(200)]),
+                int(10),
+                int(12),
+                int(300),
+            ],
+        );
+        let mut font = Dictionary::new();
+        font.set("Subtype", "Type0");
+        font.set("DescendantFonts", vec![Object::Dictionary(descendant)]);
+
+        let metrics = FontMetrics::from_font(&Document::new(), &font);
+        assert_close(metrics.advance(&[0, 1], 10.0, 0.0, 0.0), 1.0);
+        assert_close(metrics.advance(&[0, 2], 10.0, 0.0, 0.0), 2.0);
+        assert_close(metrics.advance(&[0, 10, 0, 12], 10.0, 0.0, 0.0), 6.0);
+        assert_close(metrics.advance(&[0, 13], 10.0, 0.0, 0.0), 8.0);
+        // two byte codes don't get word spacing, even for 32
+        assert_close(metrics.advance(&[0, 32], 10.0, 0.0, 5.0), 8.0);
+    }
+}
@@ -1,9 +1,19 @@
-use std::{error::Error, path::Path};
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    mem::take,
+    ops::RangeInclusive,
+    path::Path,
+};
 
-use lopdf::Document;
-use pdf_element::PdfUnit;
+use crate::{ConvertError, Section, SectionKind};
+use pdf_element::{PdfLink, PdfUnit};
 use pdf_page::PdfPage;
 
+pub use pdf_element::PdfText;
+
+mod font_metrics;
+mod ocr;
 mod pdf_element;
 mod pdf_page;
 mod pdf_state;
@@ -11,17 +21,27 @@ mod pdf_state;
 /// convert `pdf` into "markdown"
 /// because its hard to keep the layout of pdf and add markdown symbols, it really is just a pdf to
 /// text function, wrapped inside a pdf codeblock.
+/// short lines set in a larger font than the body text are pulled out of it as headings,
+/// and rows starting with a bullet or a number as (nested) list items.
 ///
 /// `screen_size` is the screen_size in cells. and the function will project that text with that
 /// in consideration. by default it has values that will stop text from overlapping, but if you have a larger
 /// buffer to show the text on, increasing the size will produce better looking result.
+///
+/// `pages` is a 1-based inclusive range of pages to convert, `None` converts all of them.
+///
+/// with `ocr`, pages that have no text but do have images (scanned documents) are rendered
+/// with pdftocairo and read with tesseract, both need to be in PATH.
+///
+/// lines repeated at the top or bottom of most pages (running headers and footers, page numbers)
+/// are dropped unless `keep_headers` is set.
 /// # usage:
 /// ```
 /// use std::path::Path;
 /// use markdownify::pdf::pdf_convert;
 ///
 /// let path = Path::new("path/to/file.pdf");
-/// match pdf_convert(&path, None) {
+/// match pdf_convert(&path, None, Some(3..=7), false, false) {
 ///     Ok(md) => println!("{}", md),
 ///     Err(e) => eprintln!("Error: {}", e)
 /// }
@@ -29,121 +49,821 @@ mod pdf_state;
 pub fn pdf_convert(
     path: &Path,
     screen_size: Option<(u16, u16)>,
-) -> Result<String, Box<dyn std::error::Error>> {
+    pages: Option<RangeInclusive<usize>>,
+    ocr: bool,
+    keep_headers: bool,
+) -> Result<String, ConvertError> {
+    let mut markdown = String::new();
+    stream_sections(
+        path,
+        screen_size,
+        pages,
+        ocr,
+        keep_headers,
+        None,
+        |section| markdown.push_str(&section.to_markdown()),
+    )?;
+    Ok(markdown)
+}
+
+/// same as [`pdf_convert`], but `on_page` is called with the markdown of each page as soon as
+/// it's ready instead of building the whole document in memory, for huge pdfs.
+/// headings and running headers are detected from the first 16 pages only.
+/// # usage:
+/// ```
+/// use std::path::Path;
+/// use markdownify::pdf::pdf_convert_streaming;
+///
+/// let path = Path::new("path/to/file.pdf");
+/// let res = pdf_convert_streaming(&path, None, None, false, false, |page| print!("{}", page));
+/// if let Err(e) = res {
+///     eprintln!("Error: {}", e)
+/// }
+/// ```
+pub fn pdf_convert_streaming(
+    path: &Path,
+    screen_size: Option<(u16, u16)>,
+    pages: Option<RangeInclusive<usize>>,
+    ocr: bool,
+    keep_headers: bool,
+    mut on_page: impl FnMut(&str),
+) -> Result<(), ConvertError> {
+    let lookahead = Some(LOOKAHEAD_PAGES);
+    stream_sections(
+        path,
+        screen_size,
+        pages,
+        ocr,
+        keep_headers,
+        lookahead,
+        |section| on_page(&section.to_markdown()),
+    )
+}
+
+/// the number of pages in the pdf
+pub fn pdf_page_count(path: &Path) -> Result<usize, ConvertError> {
+    Ok(Pdf::new(path)?.page_count())
+}
+
+/// the raw text runs of each page in `pages` (1-based, inclusive, `None` for all of them),
+/// with their position, font and bounding box. for doing your own layout analysis,
+/// like column detection, instead of the grid [`pdf_convert`] projects onto.
+pub fn pdf_text_runs(
+    path: &Path,
+    pages: Option<RangeInclusive<usize>>,
+) -> Result<Vec<Vec<PdfText>>, ConvertError> {
     let pdf = Pdf::new(path)?;
-    let mut result = String::new();
-    let mut i = 0;
+    let pages = pages.unwrap_or(1..=pdf.page_count());
+    pdf.iter_pages(&pages)
+        .map(|(_, page)| {
+            let mut page = page?;
+            let units = page.handle_stream(page.stream.clone())?;
+            Ok(units
+                .into_iter()
+                .filter_map(|unit| match unit {
+                    PdfUnit::Text(text) => Some(text),
+                    PdfUnit::Line(_) => None,
+                })
+                .collect())
+        })
+        .collect()
+}
+
+/// same as [`pdf_convert`], with a section per page
+pub fn pdf_sections(
+    path: &Path,
+    screen_size: Option<(u16, u16)>,
+    pages: Option<RangeInclusive<usize>>,
+    ocr: bool,
+    keep_headers: bool,
+) -> Result<Vec<Section>, ConvertError> {
+    let mut sections = Vec::new();
+    stream_sections(
+        path,
+        screen_size,
+        pages,
+        ocr,
+        keep_headers,
+        None,
+        |section| sections.push(section),
+    )?;
+    Ok(sections)
+}
+
+/// pages [`pdf_convert_streaming`] decodes before the first one is rendered
+const LOOKAHEAD_PAGES: usize = 16;
+
+/// headings and running headers are detected from the first `lookahead` pages, the rest are
+/// decoded and rendered one at a time. `None` decodes the whole document first
+fn stream_sections(
+    path: &Path,
+    screen_size: Option<(u16, u16)>,
+    pages: Option<RangeInclusive<usize>>,
+    ocr: bool,
+    keep_headers: bool,
+    lookahead: Option<usize>,
+    mut on_section: impl FnMut(Section),
+) -> Result<(), ConvertError> {
+    let pdf = Pdf::new(path)?;
+    let page_count = pdf.page_count();
+    // an empty pdf is an empty document, unless pages of it were asked for
+    if page_count == 0 && pages.is_none() {
+        return Ok(());
+    }
+    let pages = pages.unwrap_or(1..=page_count);
+    if *pages.start() == 0 || pages.start() > pages.end() || *pages.end() > page_count {
+        return Err(ConvertError::OutOfRange {
+            detail: format!(
+                "page range {}-{} is out of bounds, the pdf has {} pages",
+                pages.start(),
+                pages.end(),
+                page_count
+            ),
+        });
+    }
+
+    let mut iter = pdf.iter_pages(&pages);
+    let mut decoded = Vec::new();
+    for (i, page) in iter.by_ref().take(lookahead.unwrap_or(usize::MAX)) {
+        decoded.push((i, decode_page(path, i, page?, ocr)?));
+    }
+    let running = (!keep_headers).then(|| RunningLines::new(&decoded));
+    let scale = HeadingScale::new(decoded.iter().flat_map(|(_, content)| match content {
+        PageContent::Units(units, ..) => units.as_slice(),
+        PageContent::Ocr(_) => &[],
+    }));
+
+    let mut emit = |i: usize, mut content: PageContent| {
+        if let Some(running) = &running {
+            running.strip(&mut content);
+        }
+        let markdown = match content {
+            PageContent::Ocr(text) => text,
+            PageContent::Units(units, links, _) => render_page(units, &links, screen_size, &scale),
+        };
+        on_section(Section::new(
+            Some(format!("Page number {}", i)),
+            SectionKind::Page,
+            markdown,
+        ));
+    };
+    for (i, content) in decoded {
+        emit(i, content);
+    }
+    for (i, page) in iter {
+        emit(i, decode_page(path, i, page?, ocr)?);
+    }
+    Ok(())
+}
+
+fn decode_page(
+    path: &Path,
+    i: usize,
+    mut page: PdfPage,
+    ocr: bool,
+) -> Result<PageContent, ConvertError> {
+    let units = page.handle_stream(page.stream.clone())?;
+
+    let has_text = units.iter().any(|u| match u {
+        PdfUnit::Text(t) => !t.text.trim().is_empty(),
+        PdfUnit::Line(_) => false,
+    });
+    if ocr && !has_text && page.has_images() {
+        Ok(PageContent::Ocr(ocr::ocr_page(path, i)?))
+    } else {
+        let links = take(&mut page.links);
+        Ok(PageContent::Units(units, links, page.height))
+    }
+}
+
+enum PageContent {
+    /// the units, links and height of the page
+    Units(Vec<PdfUnit>, Vec<PdfLink>, f32),
+    Ocr(String),
+}
+
+/// the part of the page at the top and bottom running headers and footers are looked for in
+const RUNNING_BAND: f32 = 0.1;
+/// how far apart (in points) the same header can be on different pages
+const RUNNING_Y_TOLERANCE: f32 = 3.0;
+
+/// page numbers change from page to page, so digits are ignored when comparing
+fn running_key(text: &str) -> String {
+    let mut key = String::new();
+    for c in text
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+    {
+        match c.is_ascii_digit() {
+            true if key.ends_with('#') => {}
+            true => key.push('#'),
+            false => key.push(c),
+        }
+    }
+    key
+}
+
+fn in_running_band(text: &PdfText, height: f32) -> bool {
+    !text.text.trim().is_empty()
+        && (text.y > height * (1.0 - RUNNING_BAND) || text.y < height * RUNNING_BAND)
+}
 
-    for page in pdf.iter_pages() {
-        i += 1;
-        result.push_str(&format!("\n\n<!-- S-TITLE: Page number {} -->\n", i));
-        let mut page = page?;
-        let units = page.handle_stream(page.stream.clone())?;
+/// the text near the top or bottom that repeats on most pages
+struct RunningLines {
+    /// the pages and heights each text is on
+    seen: HashMap<String, Vec<(usize, f32)>>,
+    pages: usize,
+}
 
-        // Separate text and lines
-        let mut texts = Vec::new();
-        let mut lines = Vec::new();
+impl RunningLines {
+    fn new(decoded: &[(usize, PageContent)]) -> RunningLines {
+        let mut seen: HashMap<String, Vec<(usize, f32)>> = HashMap::new();
+        let mut pages = 0;
+        for (page, (_, content)) in decoded.iter().enumerate() {
+            let PageContent::Units(units, _, height) = content else {
+                continue;
+            };
+            pages += 1;
+            for unit in units {
+                if let PdfUnit::Text(text) = unit
+                    && in_running_band(text, *height)
+                {
+                    seen.entry(running_key(&text.text))
+                        .or_default()
+                        .push((page, text.y));
+                }
+            }
+        }
+        RunningLines { seen, pages }
+    }
 
+    fn repeated(&self, text: &PdfText) -> bool {
+        let Some(seen) = self.seen.get(&running_key(&text.text)) else {
+            return false;
+        };
+        let mut on: Vec<usize> = seen
+            .iter()
+            .filter(|(_, y)| (y - text.y).abs() <= RUNNING_Y_TOLERANCE)
+            .map(|(page, _)| *page)
+            .collect();
+        on.dedup();
+        on.len() >= 2 && on.len() * 2 > self.pages
+    }
+
+    /// drops the running lines from `content`
+    fn strip(&self, content: &mut PageContent) {
+        if self.pages < 2 {
+            return;
+        }
+        if let PageContent::Units(units, _, height) = content {
+            let height = *height;
+            units.retain(|unit| match unit {
+                PdfUnit::Text(text) => !(in_running_band(text, height) && self.repeated(text)),
+                PdfUnit::Line(_) => true,
+            });
+        }
+    }
+}
+
+/// lines at least this much larger than the body text can be headings
+const HEADING_RATIO: f32 = 1.2;
+/// longer lines are emphasized text, not headings
+const HEADING_MAX_CHARS: usize = 80;
+
+/// font sizes are compared in half points
+fn size_key(size: f32) -> i32 {
+    (size * 2.0).round() as i32
+}
+
+/// maps font sizes into heading levels, the largest size is `#`, the next `##` and so on
+struct HeadingScale {
+    body: i32,
+    sizes: Vec<i32>,
+}
+
+impl HeadingScale {
+    fn new<'a>(units: impl Iterator<Item = &'a PdfUnit>) -> HeadingScale {
+        // the most common size (by characters) is the body text
+        let mut counts: BTreeMap<i32, usize> = BTreeMap::new();
         for unit in units {
-            match unit {
-                PdfUnit::Text(unit) => texts.push(unit),
-                PdfUnit::Line(line) => lines.push(line),
+            if let PdfUnit::Text(t) = unit
+                && t.font_size != 0.0
+            {
+                *counts.entry(size_key(t.font_size.abs())).or_default() +=
+                    t.text.trim().chars().count();
             }
         }
+        let body = counts
+            .iter()
+            .max_by_key(|&(_, count)| *count)
+            .map(|(&size, _)| size)
+            .unwrap_or(0);
+        let sizes = counts
+            .keys()
+            .rev()
+            .copied()
+            .filter(|&size| body > 0 && size as f32 >= body as f32 * HEADING_RATIO)
+            .collect();
 
-        let max_x = 612.0;
-        let max_y = 792.0;
+        HeadingScale { body, sizes }
+    }
 
-        // making each lower will give more space for more "accurate" projection
-        let (cell_width, cell_height) = match screen_size {
-            Some((x, y)) => ((max_x / x as f32).min(4.0), (max_y / y as f32).min(10.0)),
-            None => (4.0, 10.0),
-        };
+    fn level(&self, size: f32) -> Option<usize> {
+        let key = size_key(size.abs());
+        if self.body == 0 || (key as f32) < self.body as f32 * HEADING_RATIO {
+            return None;
+        }
+        let pos = self.sizes.iter().position(|&s| s == key)?;
+        Some((pos + 1).min(3))
+    }
+}
 
-        let cols = (max_x / cell_width).ceil() as usize + 1;
-        let rows = (max_y / cell_height).ceil() as usize + 1;
+/// projects the page onto a grid of cells, short lines in a large font are pulled out as headings.
+/// the grid is a codeblock, so rows with links are pulled out of it as well,
+/// the other links (images, rows with columns) are listed after it
+fn render_page(
+    units: Vec<PdfUnit>,
+    links: &[PdfLink],
+    screen_size: Option<(u16, u16)>,
+    scale: &HeadingScale,
+) -> String {
+    // Separate text and lines
+    let mut texts = Vec::new();
+    let mut lines = Vec::new();
 
-        let mut matrix = vec![vec![' '; cols]; rows];
+    for unit in units {
+        match unit {
+            PdfUnit::Text(unit) => texts.push(unit),
+            PdfUnit::Line(line) => lines.push(line),
+        }
+    }
 
-        // First, draw all lines
-        for line in lines {
-            let x1 = (line.from.0 / cell_width).round() as isize;
-            let mut y1 = (line.from.1 / cell_height).round() as isize;
-            let x2 = (line.to.0 / cell_width).round() as isize;
-            let mut y2 = (line.to.1 / cell_height).round() as isize;
+    let max_x = 612.0;
+    let max_y = 792.0;
 
-            // Flip Y coordinates
-            y1 = rows as isize - 1 - y1;
-            y2 = rows as isize - 1 - y2;
+    // making each lower will give more space for more "accurate" projection
+    let (cell_width, cell_height) = match screen_size {
+        Some((x, y)) => ((max_x / x as f32).min(4.0), (max_y / y as f32).min(10.0)),
+        None => (4.0, 10.0),
+    };
 
-            if y1 == y2 {
-                // Horizontal line
-                for x in x1.min(x2)..=x1.max(x2) {
-                    if (0..rows as isize).contains(&y1) && (0..cols as isize).contains(&x) {
-                        matrix[y1 as usize][x as usize] = '─';
-                    }
+    let cols = (max_x / cell_width).ceil() as usize + 1;
+    let rows = (max_y / cell_height).ceil() as usize + 1;
+
+    let mut matrix = vec![vec![' '; cols]; rows];
+    // index into `links` of the link over each cell
+    let mut link_cells: Vec<Vec<Option<usize>>> = vec![vec![None; cols]; rows];
+
+    // First, draw all lines
+    for line in lines {
+        let x1 = (line.from.0 / cell_width).round() as isize;
+        let mut y1 = (line.from.1 / cell_height).round() as isize;
+        let x2 = (line.to.0 / cell_width).round() as isize;
+        let mut y2 = (line.to.1 / cell_height).round() as isize;
+
+        // Flip Y coordinates
+        y1 = rows as isize - 1 - y1;
+        y2 = rows as isize - 1 - y2;
+
+        if y1 == y2 {
+            // Horizontal line
+            for x in x1.min(x2)..=x1.max(x2) {
+                if (0..rows as isize).contains(&y1) && (0..cols as isize).contains(&x) {
+                    matrix[y1 as usize][x as usize] = '─';
                 }
-            } else if x1 == x2 {
-                // Vertical line
-                for y in y1.min(y2)..=y1.max(y2) {
-                    if (0..rows as isize).contains(&y) && (0..cols as isize).contains(&x1) {
-                        matrix[y as usize][x1 as usize] = '│';
-                    }
+            }
+        } else if x1 == x2 {
+            // Vertical line
+            for y in y1.min(y2)..=y1.max(y2) {
+                if (0..rows as isize).contains(&y) && (0..cols as isize).contains(&x1) {
+                    matrix[y as usize][x1 as usize] = '│';
                 }
-            } else {
-                // Diagonal line, ignore -- too complex
             }
+        } else {
+            // Diagonal line, ignore -- too complex
+        }
+    }
+
+    // heading level of each row, None once any body text lands on it
+    let mut row_levels: Vec<Option<Option<usize>>> = vec![None; rows];
+
+    // column of a glyph drawn in a symbol font, those are list markers
+    let mut marker_cols: Vec<Option<usize>> = vec![None; rows];
+
+    // Then, place all text (this will overwrite lines where they conflict)
+    for text_unit in &texts {
+        let col = (text_unit.x / cell_width).round() as usize;
+        let row = (text_unit.y / cell_height).round() as usize;
+        // Flip Y coordinate
+        let row = rows.saturating_sub(row + 1);
+
+        if row < rows && !text_unit.text.trim().is_empty() {
+            let level = scale.level(text_unit.font_size);
+            row_levels[row] = match (row_levels[row], level) {
+                (None, level) => Some(level),
+                (Some(Some(a)), Some(b)) => Some(Some(a.min(b))),
+                _ => Some(None),
+            };
+        }
+
+        if row < rows && is_marker_font(text_unit) {
+            marker_cols[row] = Some(col);
+        }
+
+        // Place each character of the string
+        let link = links.iter().position(|link| link.covers(text_unit));
+        for (i, ch) in text_unit.text.chars().enumerate() {
+            if col + i < cols && row < rows {
+                matrix[row][col + i] = ch;
+                link_cells[row][col + i] = link;
+            }
+        }
+    }
+
+    let mut parts: Vec<String> = Vec::new();
+    let mut block: Vec<String> = Vec::new();
+    let flush = |block: &mut Vec<String>, parts: &mut Vec<String>| {
+        let first = block.iter().position(|l| !l.trim().is_empty());
+        let last = block.iter().rposition(|l| !l.trim().is_empty());
+        if let (Some(start), Some(end)) = (first, last) {
+            parts.push(format!("```pdf\n{}\n```", block[start..=end].join("\n")));
         }
+        block.clear();
+    };
 
-        // Then, place all text (this will overwrite lines where they conflict)
-        for text_unit in texts {
-            let col = (text_unit.x / cell_width).round() as usize;
-            let row = (text_unit.y / cell_height).round() as usize;
-            // Flip Y coordinate
-            let row = rows.saturating_sub(row + 1);
+    let mut list: Vec<ListItem> = Vec::new();
+    // empty rows since the last list row, a wider gap than a line ends the item
+    let mut gap = 0;
+    let flush_list = |list: &mut Vec<ListItem>, parts: &mut Vec<String>| {
+        if !list.is_empty() {
+            parts.push(render_list(list));
+        }
+        list.clear();
+    };
 
-            // Place each character of the string
-            for (i, ch) in text_unit.text.chars().enumerate() {
-                if col + i < cols && row < rows {
-                    matrix[row][col + i] = ch;
+    // rows with links, one paragraph until a row of the grid
+    let mut prose: Vec<String> = Vec::new();
+    let flush_prose = |prose: &mut Vec<String>, parts: &mut Vec<String>| {
+        if !prose.is_empty() {
+            parts.push(prose.join("\n"));
+        }
+        prose.clear();
+    };
+    let mut inlined = vec![false; links.len()];
+
+    let grid = matrix.iter().zip(row_levels).zip(marker_cols);
+    for (((row, level), marker_col), cells) in grid.zip(&link_cells) {
+        let text = row.iter().collect::<String>();
+        // a link in a row with columns stays in the grid, and in the list under it
+        let linked = cells.iter().any(Option::is_some) && !has_column_gap(text.trim());
+        let line = match linked {
+            true => {
+                for link in cells.iter().flatten() {
+                    inlined[*link] = true;
                 }
+                inline_links(row, cells, links)
+            }
+            false => {
+                if !text.trim().is_empty() {
+                    flush_prose(&mut prose, &mut parts);
+                }
+                text.clone()
+            }
+        };
+        let heading = line.trim();
+        match level {
+            Some(Some(level)) if text.trim().chars().count() < HEADING_MAX_CHARS => {
+                flush(&mut block, &mut parts);
+                flush_list(&mut list, &mut parts);
+                flush_prose(&mut prose, &mut parts);
+                parts.push(format!("{} {}", "#".repeat(level), heading));
+            }
+            _ => {
+                if let Some(item) = ListItem::parse(&line, marker_col) {
+                    flush(&mut block, &mut parts);
+                    flush_prose(&mut prose, &mut parts);
+                    list.push(item);
+                    gap = 0;
+                    continue;
+                }
+                if let Some(item) = list.last_mut() {
+                    // rows between items, or wrapped text of the last one
+                    if heading.is_empty() {
+                        gap += 1;
+                        block.push(line);
+                        continue;
+                    }
+                    let indent = line.chars().take_while(|c| c.is_whitespace()).count();
+                    if gap <= 1 && indent > item.col && !has_column_gap(heading) {
+                        block.clear();
+                        gap = 0;
+                        item.text.push(' ');
+                        item.text.push_str(&collapse_spaces(heading));
+                        continue;
+                    }
+                    flush_list(&mut list, &mut parts);
+                }
+                if linked {
+                    flush(&mut block, &mut parts);
+                    prose.push(collapse_spaces(heading));
+                    continue;
+                }
+                block.push(line);
             }
         }
+    }
+    flush(&mut block, &mut parts);
+    flush_list(&mut list, &mut parts);
+    flush_prose(&mut prose, &mut parts);
+    let rest = links.iter().zip(inlined).filter(|(_, inlined)| !inlined);
+    let links = link_list(&texts, rest.map(|(link, _)| link));
+    if !links.is_empty() {
+        parts.push(links.join("\n"));
+    }
 
-        let first = matrix.iter().position(|row| row.iter().any(|&c| c != ' '));
-        let last = matrix.iter().rposition(|row| row.iter().any(|&c| c != ' '));
-        let text = if let (Some(start), Some(end)) = (first, last) {
-            matrix[start..=end]
+    if parts.is_empty() {
+        // all rows are empty
+        return "```pdf\n\n```".to_string();
+    }
+    parts.join("\n\n")
+}
+
+/// this many empty cells between words is a gap between columns
+const COLUMN_GAP: usize = 6;
+
+/// glyphs that start a bullet list item when followed by a space.
+/// the private use ones are what bullets from the Symbol and Wingdings fonts decode to
+const BULLETS: &[char] = &[
+    '•', '◦', '▪', '▫', '‣', '∙', '·', '●', '○', '■', '□', '►', '➢', '✓', '-', '–', '*',
+    '\u{f0b7}', '\u{f0a7}', '\u{f0d8}', '\u{f076}', '\u{f0fc}',
+];
+
+/// a lone glyph in a symbol font, which is what most bullets are drawn with
+fn is_marker_font(text: &PdfText) -> bool {
+    let font = text.font_name.as_deref().unwrap_or_default().to_lowercase();
+    let symbolic = ["symbol", "dingbat", "wingding"]
+        .iter()
+        .any(|f| font.contains(f));
+    let mut chars = text.text.trim().chars();
+    symbolic && chars.next().is_some_and(|c| !c.is_alphanumeric()) && chars.next().is_none()
+}
+
+/// rows with text in another column can't be pulled out of the grid
+fn has_column_gap(s: &str) -> bool {
+    s.contains(&" ".repeat(COLUMN_GAP))
+}
+
+fn collapse_spaces(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// a row of the page that starts with a list marker
+struct ListItem {
+    /// column of the marker, deeper items are further right
+    col: usize,
+    /// `1.` style number for ordered lists
+    number: Option<String>,
+    text: String,
+}
+
+impl ListItem {
+    /// `marker_col` is where a symbol font glyph was drawn on the row, if any
+    fn parse(line: &str, marker_col: Option<usize>) -> Option<ListItem> {
+        let col = line.chars().take_while(|c| c.is_whitespace()).count();
+        let rest = line.trim();
+        if has_column_gap(rest) {
+            return None;
+        }
+        let mut chars = rest.chars();
+        let first = chars.next()?;
+        let item = |number: Option<String>, text: &str| {
+            let text = collapse_spaces(text);
+            (!text.is_empty()).then_some(ListItem { col, number, text })
+        };
+
+        if marker_col == Some(col) || BULLETS.contains(&first) {
+            let text = chars.as_str();
+            let spaced = text.starts_with(char::is_whitespace);
+            return match marker_col == Some(col) || spaced {
+                true => item(None, text),
+                false => None,
+            };
+        }
+
+        let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+        let after = &rest[digits..];
+        if (1..=3).contains(&digits) && (after.starts_with(". ") || after.starts_with(") ")) {
+            return item(Some(rest[..digits].to_string()), &after[1..]);
+        }
+        None
+    }
+}
+
+/// nests the items by the columns of their markers
+fn render_list(items: &[ListItem]) -> String {
+    // markers a column apart are the same level, rounding puts them on either
+    let mut cols: Vec<usize> = items.iter().map(|item| item.col).collect();
+    cols.sort();
+    cols.dedup_by(|a, b| *a - *b <= 1);
+
+    let mut prev_level = 0;
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let level = cols.iter().filter(|&&col| col + 1 < item.col).count();
+            // a list can't start nested or skip a level
+            let level = match i {
+                0 => 0,
+                _ => level.min(prev_level + 1),
+            };
+            prev_level = level;
+            let marker = match &item.number {
+                Some(number) => format!("{}.", number),
+                None => "-".to_string(),
+            };
+            format!("{}{} {}", "    ".repeat(level), marker, item.text)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// the row with the cells under each link wrapped in `[text](uri)`,
+/// blanks between two cells of the same link are part of it
+fn inline_links(row: &[char], cells: &[Option<usize>], links: &[PdfLink]) -> String {
+    let mut line = String::new();
+    let mut open: Option<usize> = None;
+    for (i, (&ch, &cell)) in row.iter().zip(cells).enumerate() {
+        let link = match ch.is_whitespace() {
+            true => row[i..]
                 .iter()
-                .map(|row| row.iter().collect::<String>())
-                .collect::<Vec<_>>()
-                .join("\n")
-        } else {
-            String::new() // all rows are empty
+                .zip(&cells[i..])
+                .find(|(ch, _)| !ch.is_whitespace())
+                .and_then(|(_, next)| *next)
+                .filter(|next| open == Some(*next)),
+            false => cell,
         };
+        if link != open {
+            if let Some(prev) = open {
+                line.push_str(&format!("]({})", escape_uri(&links[prev].uri)));
+            }
+            if link.is_some() {
+                line.push('[');
+            }
+            open = link;
+        }
+        if open.is_some() && matches!(ch, '[' | ']') {
+            line.push('\\');
+        }
+        line.push(ch);
+    }
+    if let Some(prev) = open {
+        line.push_str(&format!("]({})", escape_uri(&links[prev].uri)));
+    }
+    line
+}
 
-        result.push_str("```pdf\n");
-        result.push_str(&text);
-        result.push_str("\n```");
+fn escape_uri(uri: &str) -> String {
+    uri.replace(' ', "%20")
+        .replace('(', "%28")
+        .replace(')', "%29")
+}
+
+/// a markdown list item per link, `[text](uri)` with the text under it,
+/// or the bare uri when there is none (image links)
+fn link_list<'a>(texts: &[PdfText], links: impl Iterator<Item = &'a PdfLink>) -> Vec<String> {
+    let mut items: Vec<String> = Vec::new();
+    for link in links {
+        let mut runs: Vec<&PdfText> = texts.iter().filter(|t| link.covers(t)).collect();
+        // reading order, top to bottom then left to right
+        runs.sort_by(|a, b| b.y.total_cmp(&a.y).then(a.x.total_cmp(&b.x)));
+
+        let mut text = String::new();
+        let mut prev: Option<&PdfText> = None;
+        for run in runs {
+            // runs of the same word touch, anything further apart is a space
+            if let Some(prev) = prev
+                && (run.bbox.0 - prev.bbox.2 > 1.0 || (run.y - prev.y).abs() > 1.0)
+            {
+                text.push(' ');
+            }
+            text.push_str(&run.text);
+            prev = Some(run);
+        }
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        let item = match text.is_empty() {
+            true => format!("- {}", link.uri),
+            false => {
+                let text = text.replace('[', "\\[").replace(']', "\\]");
+                format!("- [{}]({})", text, escape_uri(&link.uri))
+            }
+        };
+        if !items.contains(&item) {
+            items.push(item);
+        }
     }
-    Ok(result)
+    items
 }
 
 struct Pdf {
-    doc: Document,
+    doc: lopdf::Document,
 }
 
 impl Pdf {
-    pub fn new(path: &Path) -> Result<Pdf, Box<dyn Error>> {
+    pub fn new(path: &Path) -> Result<Pdf, ConvertError> {
         let doc = lopdf::Document::load(path)?;
         let pdf = Pdf { doc };
 
         Ok(pdf)
     }
 
-    pub fn iter_pages(&'_ self) -> impl Iterator<Item = Result<PdfPage<'_>, Box<dyn Error>>> {
+    pub fn page_count(&self) -> usize {
+        self.doc.get_pages().len()
+    }
+
+    /// iterates the pages within `range` (1-based), along with their page number.
+    /// pages outside of it are skipped before their content is decoded
+    pub fn iter_pages(
+        &'_ self,
+        range: &RangeInclusive<usize>,
+    ) -> impl Iterator<Item = (usize, Result<PdfPage<'_>, Box<dyn Error>>)> {
         self.doc
             .page_iter()
-            .map(|id| PdfPage::from_object_id(&self.doc, id))
+            .enumerate()
+            .map(|(i, id)| (i + 1, id))
+            .filter(|(i, _)| range.contains(i))
+            .map(|(i, id)| (i, PdfPage::from_object_id(&self.doc, id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pdf::{
+        HeadingScale, pdf_element::PdfLink, pdf_element::PdfText, pdf_element::PdfUnit, render_page,
+    };
+
+    fn text(x: f32, y: f32, text: &str, font: &str) -> PdfUnit {
+        PdfUnit::Text(PdfText {
+            text: text.to_string(),
+            font_name: Some(font.to_string()),
+            font_size: 11.0,
+            x,
+            y,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn bullets_become_nested_lists() {
+        let units = vec![
+            text(50.0, 740.0, "Notes before the list.", "Helvetica"),
+            text(60.0, 715.0, "\u{f0b7}", "Symbol"),
+            text(78.0, 715.0, "Fruit", "Helvetica"),
+            text(80.0, 701.0, "\u{f0b7}", "Symbol"),
+            text(98.0, 701.0, "Apples, the green ones that", "Helvetica"),
+            text(98.0, 687.0, "are not too sour", "Helvetica"),
+            text(80.0, 673.0, "• Pears", "Helvetica"),
+            text(60.0, 659.0, "• Bread", "Helvetica"),
+            text(60.0, 630.0, "1. Pay", "Helvetica"),
+            text(60.0, 616.0, "2. Leave", "Helvetica"),
+        ];
+        let scale = HeadingScale::new(units.iter());
+        let md = render_page(units, &[], None, &scale);
+
+        assert!(md.contains(
+            "- Fruit\n    - Apples, the green ones that are not too sour\n    - Pears\n- Bread"
+        ));
+        assert!(md.contains("1. Pay\n2. Leave"));
+        assert!(md.contains("Notes before the list."));
+    }
+
+    #[test]
+    fn links_are_inlined_out_of_the_grid() {
+        let units = vec![
+            text(50.0, 740.0, "See", "Helvetica"),
+            text(70.0, 740.0, "example site", "Helvetica"),
+            text(122.0, 740.0, "for more.", "Helvetica"),
+            text(50.0, 700.0, "Plain text", "Helvetica"),
+        ];
+        let links = [
+            PdfLink {
+                uri: "https://example.com".to_string(),
+                rect: (65.0, 735.0, 120.0, 750.0),
+            },
+            // an image, no text under it
+            PdfLink {
+                uri: "https://example.com/logo".to_string(),
+                rect: (300.0, 300.0, 350.0, 350.0),
+            },
+        ];
+        let scale = HeadingScale::new(units.iter());
+        let md = render_page(units, &links, None, &scale);
+
+        assert!(md.starts_with("See [example site](https://example.com) for more.\n\n```pdf\n"));
+        assert!(md.contains("Plain text"));
+        assert!(md.ends_with("```\n\n- https://example.com/logo"));
     }
 }
@@ -0,0 +1,67 @@
+use std::{io::ErrorKind, path::Path, process::Command};
+
+use crate::ConvertError;
+
+fn missing_tesseract() -> ConvertError {
+    ConvertError::Tool {
+        detail:
+            "OCR was requested but tesseract was not found in PATH, install tesseract or drop --ocr"
+                .to_string(),
+    }
+}
+
+/// renders `page` (1-based) of the pdf into a png and runs tesseract over it
+pub fn ocr_page(path: &Path, page: usize) -> Result<String, ConvertError> {
+    match Command::new("tesseract").arg("--version").output() {
+        Err(e) if e.kind() == ErrorKind::NotFound => return Err(missing_tesseract()),
+        Err(e) => return Err(e.into()),
+        Ok(_) => {}
+    }
+
+    let dir = tempfile::tempdir()?;
+    let prefix = dir.path().join("page");
+    let page_str = page.to_string();
+    let args = [
+        "-png",
+        "-singlefile",
+        "-r",
+        "300",
+        "-f",
+        &page_str,
+        "-l",
+        &page_str,
+    ];
+
+    // both take the same arguments
+    let rendered = ["pdftocairo", "pdftoppm"].iter().find_map(|tool| {
+        Command::new(tool)
+            .args(args)
+            .arg(path)
+            .arg(&prefix)
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+    });
+    if rendered.is_none() {
+        return Err(ConvertError::Tool {
+            detail: "OCR needs pdftocairo or pdftoppm in PATH to render the pdf pages".to_string(),
+        });
+    }
+
+    let image = prefix.with_extension("png");
+    let output = Command::new("tesseract")
+        .arg(&image)
+        .arg("stdout")
+        .output()
+        .map_err(|_| missing_tesseract())?;
+    if !output.status.success() {
+        return Err(ConvertError::Tool {
+            detail: format!(
+                "tesseract error: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        });
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
@@ -1,3 +1,4 @@
+/// something drawn on a pdf page
 #[derive(Clone, Debug)]
 pub enum PdfUnit {
     Text(PdfText),
@@ -10,19 +11,45 @@ pub struct PdfLine {
     pub to: (f32, f32),
 }
 
+/// a run of text shown by a single text operator
 #[derive(Default, Clone, Debug)]
 pub struct PdfText {
     pub text: String,
     pub italic: bool,
     pub font_name: Option<String>,
-    pub font_size: Option<f32>,
+    /// the `Tf` size scaled by the text matrix, 0 when nothing set a font yet
+    pub font_size: f32,
     pub x: f32,
     pub y: f32,
     pub underlined: bool,
-    pub color: Option<String>,
+    /// `(x0, y0, x1, y1)` on the page, in points from the bottom left corner.
+    /// spans the advance of the glyphs and the font size above the baseline
+    pub bbox: (f32, f32, f32, f32),
 }
 
-pub fn is_fake_line(lines: &[PdfUnit]) -> bool {
+/// a `/Link` annotation pointing at a uri
+#[derive(Clone, Debug)]
+pub struct PdfLink {
+    pub uri: String,
+    /// `(x0, y0, x1, y1)` of the clickable area, in the same space as [`PdfText::bbox`]
+    pub rect: (f32, f32, f32, f32),
+}
+
+impl PdfLink {
+    /// whether most of `text` is inside the link
+    pub fn covers(&self, text: &PdfText) -> bool {
+        let (x0, y0, x1, y1) = text.bbox;
+        // text without a box (e.g. from `ActualText`) only has its origin
+        let (cx, cy) = match x1 > x0 {
+            true => ((x0 + x1) / 2.0, (y0 + y1) / 2.0),
+            false => (text.x, text.y),
+        };
+        let (lx0, ly0, lx1, ly1) = self.rect;
+        (lx0..=lx1).contains(&cx) && (ly0..=ly1).contains(&cy)
+    }
+}
+
+pub(crate) fn is_fake_line(lines: &[PdfUnit]) -> bool {
     let mut min_x = f32::INFINITY;
     let mut min_y = f32::INFINITY;
     let mut max_x = f32::NEG_INFINITY;
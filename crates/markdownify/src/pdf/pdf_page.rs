@@ -5,12 +5,16 @@ use lopdf::{Dictionary, Document, Encoding, Object, ObjectId};
 use crate::pdf::pdf_element::is_fake_line;
 
 use super::{
-    pdf_element::{PdfLine, PdfText, PdfUnit},
+    font_metrics::FontMetrics,
+    pdf_element::{PdfLine, PdfLink, PdfText, PdfUnit},
     pdf_state::PdfState,
 };
 
 pub struct PdfPage<'a> {
     pub stream: Vec<u8>,
+    pub links: Vec<PdfLink>,
+    /// height of the page as it's displayed, after rotating it
+    pub height: f32,
     document: &'a Document,
     fonts: BTreeMap<Vec<u8>, &'a Dictionary>,
     encodings: BTreeMap<Vec<u8>, Encoding<'a>>,
@@ -18,6 +22,7 @@ pub struct PdfPage<'a> {
     current_font_alias: Vec<u8>,
     current_font_name: Option<String>,
     current_font_size: Option<f32>,
+    current_font_metrics: FontMetrics,
     state: PdfState,
     state_stack: Vec<PdfState>,
     pub child_fonts: Option<BTreeMap<Vec<u8>, &'a Dictionary>>,
@@ -48,22 +53,71 @@ impl<'a> PdfPage<'a> {
             res
         });
 
+        let rotate = page_attribute(doc, id, b"Rotate")
+            .and_then(|r| r.as_i64().ok())
+            .unwrap_or(0);
+        let media_box = page_attribute(doc, id, b"MediaBox")
+            .and_then(|b| b.as_array().ok())
+            .and_then(|b| {
+                let b: Vec<f32> = b.iter().filter_map(|v| v.as_float().ok()).collect();
+                b.try_into().ok()
+            })
+            .unwrap_or([0.0, 0.0, 612.0, 792.0]);
+
+        let height = match rotate.rem_euclid(180) {
+            90 => media_box[2] - media_box[0],
+            _ => media_box[3] - media_box[1],
+        };
+        let state = PdfState::rotated(rotate, media_box);
+        let links = page_links(doc, id, &state);
+
         Ok(PdfPage {
             stream,
+            links,
+            height,
             fonts,
             resource,
             document: doc,
             encodings,
             current_font_alias: Vec::new(),
-            state: PdfState::new(),
+            state,
             state_stack: Vec::new(),
             current_font_name: None,
             current_font_size: None,
+            current_font_metrics: FontMetrics::default(),
             child_fonts: None,
             child_encodings: None,
         })
     }
 
+    /// whether the page draws any image xobjects, scanned pages are just that
+    pub fn has_images(&self) -> bool {
+        let xobjects = self
+            .resource
+            .and_then(|r| r.get(b"XObject").ok())
+            .and_then(|obj| match obj.as_reference() {
+                Ok(id) => self.document.get_object(id).ok()?.as_dict().ok(),
+                Err(_) => obj.as_dict().ok(),
+            });
+        let Some(xobjects) = xobjects else {
+            return false;
+        };
+
+        xobjects.iter().any(|(_, v)| {
+            v.as_reference()
+                .ok()
+                .and_then(|id| self.document.get_object(id).ok())
+                .and_then(|obj| obj.as_stream().ok())
+                .is_some_and(|stream| {
+                    stream
+                        .dict
+                        .get(b"Subtype")
+                        .and_then(Object::as_name)
+                        .is_ok_and(|name| name == b"Image")
+                })
+        })
+    }
+
     pub fn handle_stream(&mut self, stream: Vec<u8>) -> Result<Vec<PdfUnit>, Box<dyn Error>> {
         let mut elements: Vec<PdfUnit> = Vec::new();
         let mut current_element = PdfText::default();
@@ -79,21 +133,34 @@ impl<'a> PdfPage<'a> {
                         // ' is like TJ just with T* before it
                         // " is like as ' just with aw and ac as the first 2 operands
                         let r: &str = op.operator.as_ref();
+                        let mut shown = op.operands.as_slice();
+                        if r == "\"" {
+                            let items = op
+                                .operands
+                                .get(..2)
+                                .ok_or("failed to get spacing for text in pdf")?;
+                            self.state.word_spacing = items[0].as_float()?;
+                            self.state.char_spacing = items[1].as_float()?;
+                            shown = &op.operands[2..];
+                        }
                         if r == "'" || r == "\"" {
                             self.state.t_star();
                         }
                         // currently ignores spacing in between chars. may include joined words
                         // because of weird pdfs design.
-                        let text = self.extract_text_from_objs(&op.operands);
+                        let text = self.extract_text_from_objs(shown);
                         let (x, y) = self.state.current_position();
+                        let font_size = self.current_font_size.unwrap_or_default();
+                        let tx = self.text_advance(shown, font_size);
                         current_element.text = text;
                         current_element.x = x;
                         current_element.y = y;
+                        current_element.bbox = self.state.text_box(tx, font_size);
                         current_element.italic = self.state.tm.c != 0.0;
                         current_element.font_name = self.current_font_name.clone();
-                        current_element.font_size =
-                            self.current_font_size.map(|s| s * self.state.tm.a);
+                        current_element.font_size = font_size * self.state.tm.a;
                         elements.push(PdfUnit::Text(take(&mut current_element)));
+                        self.state.advance(tx);
                         Ok(())
                     }
                     "Do" => {
@@ -176,7 +243,11 @@ impl<'a> PdfPage<'a> {
                         let font_info = self
                             .fonts
                             .get(font_alias)
-                            .ok_or("failed to get fonts for page")?;
+                            .or_else(|| self.child_fonts.as_ref().and_then(|f| f.get(font_alias)));
+                        self.current_font_metrics = font_info
+                            .map(|font| FontMetrics::from_font(self.document, font))
+                            .unwrap_or_default();
+                        let font_info = font_info.ok_or("failed to get fonts for page")?;
                         let font_name = font_info.get(b"BaseFont")?;
                         self.current_font_name =
                             String::from_utf8(font_name.as_name()?.to_vec()).ok();
@@ -184,57 +255,72 @@ impl<'a> PdfPage<'a> {
                     }
                     "cm" => {
                         // current matrix
-                        let items: Vec<f32> = op
+                        let items = op
                             .operands
                             .get(..6)
                             .ok_or("failed to get position for text in pdf")?
                             .iter()
-                            .map(|f| f.as_float().unwrap())
-                            .collect();
+                            .map(|f| f.as_float())
+                            .collect::<Result<Vec<f32>, _>>()?;
                         self.state
                             .cm(items[0], items[1], items[2], items[3], items[4], items[5]);
                         Ok(())
                     }
                     "Tm" => {
                         // text matrix
-                        let items: Vec<f32> = op
+                        let items = op
                             .operands
                             .get(..6)
                             .ok_or("failed to get position for text in pdf")?
                             .iter()
-                            .map(|f| f.as_float().unwrap())
-                            .collect();
+                            .map(|f| f.as_float())
+                            .collect::<Result<Vec<f32>, _>>()?;
                         self.state
                             .tm(items[0], items[1], items[2], items[3], items[4], items[5]);
                         Ok(())
                     }
                     "Td" => {
                         // transforms tm
-                        let items: Vec<f32> = op
+                        let items = op
                             .operands
                             .get(..2)
                             .ok_or("failed to get position for text in pdf")?
                             .iter()
-                            .map(|f| f.as_float().unwrap())
-                            .collect();
+                            .map(|f| f.as_float())
+                            .collect::<Result<Vec<f32>, _>>()?;
                         self.state.td(items[0], items[1]);
                         Ok(())
                     }
                     "TD" => {
                         // just like Td, just sets leading to -ty
-                        let items: Vec<f32> = op
+                        let items = op
                             .operands
                             .get(..2)
                             .ok_or("failed to get position for text in pdf")?
                             .iter()
-                            .map(|f| f.as_float().unwrap())
-                            .collect();
+                            .map(|f| f.as_float())
+                            .collect::<Result<Vec<f32>, _>>()?;
                         self.state.td_capital(items[0], items[1]);
                         Ok(())
                     }
                     "TL" => {
                         // sets leading
-                        self.state.tl(op.operands[0].as_float().unwrap());
+                        let leading = op.operands.first().ok_or("failed to get leading in pdf")?;
+                        self.state.tl(leading.as_float()?);
+                        Ok(())
+                    }
+                    "Tc" | "Tw" | "Tz" => {
+                        // character spacing, word spacing and horizontal scaling
+                        let value = op
+                            .operands
+                            .first()
+                            .ok_or("failed to get text spacing in pdf")?
+                            .as_float()?;
+                        match op.operator.as_ref() {
+                            "Tc" => self.state.char_spacing = value,
+                            "Tw" => self.state.word_spacing = value,
+                            _ => self.state.horizontal_scaling = value / 100.0,
+                        }
                         Ok(())
                     }
                     "T*" => {
@@ -242,23 +328,6 @@ impl<'a> PdfPage<'a> {
                         self.state.t_star();
                         Ok(())
                     }
-                    "sc" | "rg" => {
-                        // sets color
-                        if let Some(items) = op.operands.get(..3) {
-                            let r = items[0].as_float()?;
-                            let g = items[1].as_float()?;
-                            let b = items[2].as_float()?;
-                            if r != 0.0 || g != 0.0 || b != 0.0 {
-                                let color = rgb_to_hex(
-                                    items[0].as_float()?,
-                                    items[1].as_float()?,
-                                    items[2].as_float()?,
-                                );
-                                current_element.color = Some(color);
-                            }
-                        }
-                        Ok(())
-                    }
                     "SC" | "RG" => {
                         // mostly happens after underlined
                         if let Some(last) = elements.last_mut() {
@@ -270,24 +339,24 @@ impl<'a> PdfPage<'a> {
                         Ok(())
                     }
                     "m" => {
-                        let items: Vec<f32> = op
+                        let items = op
                             .operands
                             .get(..2)
                             .ok_or("failed to get position for line in pdf")?
                             .iter()
-                            .map(|f| f.as_float().unwrap())
-                            .collect();
+                            .map(|f| f.as_float())
+                            .collect::<Result<Vec<f32>, _>>()?;
                         self.state.m(items[0], items[1]);
                         Ok(())
                     }
                     "l" => {
-                        let items: Vec<f32> = op
+                        let items = op
                             .operands
                             .get(..2)
                             .ok_or("failed to get position for line in pdf")?
                             .iter()
-                            .map(|f| f.as_float().unwrap())
-                            .collect();
+                            .map(|f| f.as_float())
+                            .collect::<Result<Vec<f32>, _>>()?;
                         let (from, to) = self.state.l((items[0], items[1]));
                         let line = PdfLine { from, to };
                         elements.push(PdfUnit::Line(line));
@@ -304,13 +373,13 @@ impl<'a> PdfPage<'a> {
                         Ok(())
                     }
                     "re" => {
-                        let items: Vec<f32> = op
+                        let items = op
                             .operands
                             .get(..4)
                             .ok_or("failed to get position for line in pdf")?
                             .iter()
-                            .map(|f| f.as_float().unwrap())
-                            .collect();
+                            .map(|f| f.as_float())
+                            .collect::<Result<Vec<f32>, _>>()?;
 
                         let x = items[0];
                         let y = items[1];
@@ -393,6 +462,26 @@ impl<'a> PdfPage<'a> {
         text
     }
 
+    /// how far showing `objs` moves the text position, in unscaled text space units.
+    /// numbers inside `TJ` arrays are kerning in thousandths of the font size
+    fn text_advance(&self, objs: &[Object], font_size: f32) -> f32 {
+        objs.iter()
+            .map(|obj| match obj {
+                Object::String(bytes, _) => self.current_font_metrics.advance(
+                    bytes,
+                    font_size,
+                    self.state.char_spacing,
+                    self.state.word_spacing,
+                ),
+                Object::Array(nested) => self.text_advance(nested, font_size),
+                Object::Integer(_) | Object::Real(_) => {
+                    -obj.as_float().unwrap_or_default() / 1000.0 * font_size
+                }
+                _ => 0.0,
+            })
+            .sum()
+    }
+
     fn extract_bytes_from_obj(obj: &Object) -> Vec<u8> {
         match obj {
             Object::String(bytes, _) | Object::Name(bytes) => bytes.clone(),
@@ -452,9 +541,67 @@ impl<'a> PdfPage<'a> {
     }
 }
 
-fn rgb_to_hex(r: f32, g: f32, b: f32) -> String {
-    let r = (r * 255.0).round() as u8;
-    let g = (g * 255.0).round() as u8;
-    let b = (b * 255.0).round() as u8;
-    format!("#{:02X}{:02X}{:02X}", r, g, b)
+/// an attribute of the page, or inherited from its parents in the page tree
+fn page_attribute<'a>(doc: &'a Document, id: ObjectId, key: &[u8]) -> Option<&'a Object> {
+    let mut dict = doc.get_dictionary(id).ok()?;
+    // the depth limit guards against cycles in broken files
+    for _ in 0..32 {
+        if let Ok(value) = dict.get(key) {
+            return match value {
+                Object::Reference(id) => doc.get_object(*id).ok(),
+                value => Some(value),
+            };
+        }
+        let parent = dict.get(b"Parent").and_then(Object::as_reference).ok()?;
+        dict = doc.get_dictionary(parent).ok()?;
+    }
+    None
+}
+
+/// the uri links of the page, annotations can be inline or references
+fn page_links(doc: &Document, id: ObjectId, state: &PdfState) -> Vec<PdfLink> {
+    fn deref<'a>(doc: &'a Document, obj: &'a Object) -> Option<&'a Object> {
+        match obj {
+            Object::Reference(id) => doc.get_object(*id).ok(),
+            obj => Some(obj),
+        }
+    }
+    let annots = doc
+        .get_dictionary(id)
+        .ok()
+        .and_then(|page| page.get(b"Annots").ok())
+        .and_then(|annots| deref(doc, annots)?.as_array().ok());
+    let Some(annots) = annots else {
+        return Vec::new();
+    };
+
+    annots
+        .iter()
+        .filter_map(|annot| {
+            let annot = deref(doc, annot)?.as_dict().ok()?;
+            if annot.get(b"Subtype").and_then(Object::as_name).ok()? != b"Link" {
+                return None;
+            }
+            let action = deref(doc, annot.get(b"A").ok()?)?.as_dict().ok()?;
+            if action.get(b"S").and_then(Object::as_name).ok()? != b"URI" {
+                return None;
+            }
+            let uri = match deref(doc, action.get(b"URI").ok()?)? {
+                Object::String(bytes, _) => String::from_utf8_lossy(bytes).trim().to_string(),
+                _ => return None,
+            };
+            let rect: [f32; 4] = deref(doc, annot.get(b"Rect").ok()?)?
+                .as_array()
+                .ok()?
+                .iter()
+                .filter_map(|v| v.as_float().ok())
+                .collect::<Vec<_>>()
+                .try_into()
+                .ok()?;
+            (!uri.is_empty()).then(|| PdfLink {
+                uri,
+                rect: state.rect(rect),
+            })
+        })
+        .collect()
 }
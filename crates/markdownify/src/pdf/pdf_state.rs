@@ -50,8 +50,14 @@ impl Matrix3x3 {
 pub struct PdfState {
     ctm: Matrix3x3,
     pub tm: Matrix3x3,
+    // start of the current line, glyphs advance the tm but not this
+    tlm: Matrix3x3,
     leading: f32,
     pub m: (f32, f32),
+    pub char_spacing: f32,
+    pub word_spacing: f32,
+    /// `Tz`, 1.0 for 100%
+    pub horizontal_scaling: f32,
 }
 
 impl Default for PdfState {
@@ -64,13 +70,36 @@ impl PdfState {
         Self {
             tm: Matrix3x3::identity(),
             ctm: Matrix3x3::identity(),
+            tlm: Matrix3x3::identity(),
             leading: 0.0,
             m: (0.0, 0.0),
+            char_spacing: 0.0,
+            word_spacing: 0.0,
+            horizontal_scaling: 1.0,
+        }
+    }
+
+    /// a state whose device space is the page as it's displayed,
+    /// `rotate` is the page `/Rotate` (clockwise) and `media_box` its `[x0, y0, x1, y1]`
+    pub fn rotated(rotate: i64, media_box: [f32; 4]) -> Self {
+        let [x0, y0, x1, y1] = media_box;
+        let (w, h) = (x1 - x0, y1 - y0);
+        let rotation = match rotate.rem_euclid(360) {
+            90 => Matrix3x3::from_components(0.0, -1.0, 1.0, 0.0, 0.0, w),
+            180 => Matrix3x3::from_components(-1.0, 0.0, 0.0, -1.0, w, h),
+            270 => Matrix3x3::from_components(0.0, 1.0, -1.0, 0.0, h, 0.0),
+            _ => Matrix3x3::identity(),
+        };
+        let origin = Matrix3x3::from_components(1.0, 0.0, 0.0, 1.0, -x0, -y0);
+        Self {
+            ctm: rotation.multiply(&origin),
+            ..Self::new()
         }
     }
 
     pub fn bt(&mut self) {
         self.tm = Matrix3x3::identity();
+        self.tlm = Matrix3x3::identity();
     }
 
     pub fn et(&mut self) {
@@ -83,7 +112,8 @@ impl PdfState {
 
     pub fn td(&mut self, tx: f32, ty: f32) {
         let translation = Matrix3x3::from_components(1.0, 0.0, 0.0, 1.0, tx, ty);
-        self.tm = self.tm.multiply(&translation);
+        self.tlm = self.tlm.multiply(&translation);
+        self.tm = self.tlm;
     }
 
     pub fn td_capital(&mut self, tx: f32, ty: f32) {
@@ -94,6 +124,37 @@ impl PdfState {
     pub fn tm(&mut self, a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) {
         let m = Matrix3x3::from_components(a, b, c, d, e, f);
         self.tm = m;
+        self.tlm = m;
+    }
+
+    /// moves past shown text, `tx` in unscaled text space units
+    pub fn advance(&mut self, tx: f32) {
+        let translation =
+            Matrix3x3::from_components(1.0, 0.0, 0.0, 1.0, tx * self.horizontal_scaling, 0.0);
+        self.tm = self.tm.multiply(&translation);
+    }
+
+    /// the `(x0, y0, x1, y1)` box on the page of text starting at the current position,
+    /// `tx` wide (like [`PdfState::advance`]) and `font_size` high from the baseline
+    pub fn text_box(&self, tx: f32, font_size: f32) -> (f32, f32, f32, f32) {
+        let combined = self.ctm.multiply(&self.tm);
+        let width = tx * self.horizontal_scaling;
+        let corners = [
+            (0.0, 0.0),
+            (width, 0.0),
+            (0.0, font_size),
+            (width, font_size),
+        ]
+        .map(|(x, y)| combined.apply_to_point(x, y));
+        corners.iter().fold(
+            (
+                f32::INFINITY,
+                f32::INFINITY,
+                f32::NEG_INFINITY,
+                f32::NEG_INFINITY,
+            ),
+            |(x0, y0, x1, y1), &(x, y)| (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+        )
     }
 
     pub fn t_star(&mut self) {
@@ -116,6 +177,13 @@ impl PdfState {
         f
     }
 
+    /// `rect` (`[x0, y0, x1, y1]` in user space) as a `(x0, y0, x1, y1)` box on the page
+    pub fn rect(&self, rect: [f32; 4]) -> (f32, f32, f32, f32) {
+        let (ax, ay) = self.ctm.apply_to_point(rect[0], rect[1]);
+        let (bx, by) = self.ctm.apply_to_point(rect[2], rect[3]);
+        (ax.min(bx), ay.min(by), ax.max(bx), ay.max(by))
+    }
+
     pub fn l(&self, to: (f32, f32)) -> ((f32, f32), (f32, f32)) {
         let from = self.ctm.apply_to_point(self.m.0, self.m.1);
         let to = self.ctm.apply_to_point(to.0, to.1);
@@ -128,3 +196,27 @@ impl PdfState {
         (from, to)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::PdfState;
+
+    #[test]
+    fn text_box_follows_the_text_matrix() {
+        let mut state = PdfState::new();
+        state.tm(1.0, 0.0, 0.0, 1.0, 100.0, 700.0);
+        state.horizontal_scaling = 0.5;
+        assert_eq!(state.text_box(40.0, 12.0), (100.0, 700.0, 120.0, 712.0));
+
+        state.advance(40.0);
+        assert_eq!(state.current_position(), (120.0, 700.0));
+    }
+
+    #[test]
+    fn text_box_on_a_rotated_page() {
+        // a 90° page turns the 612x792 media box into a 792x612 one
+        let mut state = PdfState::rotated(90, [0.0, 0.0, 612.0, 792.0]);
+        state.tm(1.0, 0.0, 0.0, 1.0, 100.0, 700.0);
+        assert_eq!(state.text_box(50.0, 10.0), (700.0, 462.0, 710.0, 512.0));
+    }
+}
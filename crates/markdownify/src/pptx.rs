@@ -8,24 +8,113 @@ use quick_xml::events::Event;
 use zip::ZipArchive;
 
 use super::sheets;
+use crate::{ConvertError, Document, Section, SectionKind};
+
+fn get_attr(e: &quick_xml::events::BytesStart, key: &[u8]) -> Option<String> {
+    e.attributes()
+        .with_checks(false)
+        .flatten()
+        .find(|attr| attr.key.as_ref() == key)
+        .map(|attr| String::from_utf8_lossy(&attr.value).into_owned())
+}
+
+fn read_entry(archive: &mut ZipArchive<Cursor<Vec<u8>>>, name: &str) -> Option<String> {
+    let mut file = archive.by_name(name).ok()?;
+    let mut content = String::new();
+    file.read_to_string(&mut content).ok()?;
+    Some(content)
+}
+
+/// finds the notes slide of `slide_path` through its relationships,
+/// so gaps in the slide numbering can't pair a slide with the wrong notes
+fn notes_path(archive: &mut ZipArchive<Cursor<Vec<u8>>>, slide_path: &str) -> Option<String> {
+    let (dir, name) = slide_path.rsplit_once('/')?;
+    let rels = read_entry(archive, &format!("{}/_rels/{}.rels", dir, name))?;
+    let mut reader = quick_xml::Reader::from_str(&rels);
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Empty(e)) | Ok(Event::Start(e))
+                if e.local_name().as_ref() == b"Relationship" =>
+            {
+                let is_notes = get_attr(&e, b"Type").is_some_and(|t| t.ends_with("/notesSlide"));
+                if let (true, Some(target)) = (is_notes, get_attr(&e, b"Target")) {
+                    let mut parts: Vec<&str> = dir.split('/').collect();
+                    for part in target.split('/') {
+                        match part {
+                            "" | "." => {}
+                            ".." => {
+                                parts.pop();
+                            }
+                            _ => parts.push(part),
+                        }
+                    }
+                    return Some(parts.join("/"));
+                }
+            }
+            Ok(Event::Eof) | Err(_) => return None,
+            _ => {}
+        }
+    }
+}
+
+/// the text of the notes placeholder, a line per paragraph
+fn read_notes(archive: &mut ZipArchive<Cursor<Vec<u8>>>, slide_path: &str) -> Option<String> {
+    let path = notes_path(archive, slide_path)?;
+    let content = read_entry(archive, &path)?;
+    let mut reader = quick_xml::Reader::from_str(&content);
+    let mut lines: Vec<String> = Vec::new();
+    let mut line = String::new();
+    // notes slides also hold the slide image and number, only the body has the notes
+    let mut in_body = false;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) if e.name().as_ref() == b"p:sp" => in_body = false,
+            Ok(Event::Empty(e)) | Ok(Event::Start(e)) if e.name().as_ref() == b"p:ph" => {
+                in_body = get_attr(&e, b"type").as_deref() == Some("body");
+            }
+            Ok(Event::Text(e)) if in_body => {
+                line.push_str(&e.decode().unwrap_or_default());
+            }
+            Ok(Event::End(e)) if in_body && e.name().as_ref() == b"a:p" => {
+                lines.push(line.trim().to_string());
+                line.clear();
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+
+    let notes = lines.join("\n").trim().to_string();
+    (!notes.is_empty()).then_some(notes)
+}
 
 /// convert `pptx` files into markdown
+///
+/// with `include_notes` each slide's speaker notes are added beneath it as a blockquote.
 /// # usage:
 /// ```
 /// use std::path::Path;
 /// use markdownify::pptx::pptx_converter;
 ///
 /// let path = Path::new("path/to/file.pptx");
-/// match pptx_converter(&path) {
+/// match pptx_converter(&path, true) {
 ///     Ok(md) => println!("{}", md),
 ///     Err(e) => eprintln!("Error: {}", e)
 /// }
 /// ```
-pub fn pptx_converter(path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+pub fn pptx_converter(path: &Path, include_notes: bool) -> Result<String, ConvertError> {
+    let sections = pptx_sections(path, include_notes)?;
+    Ok(Document::new(sections).to_markdown().trim().to_string())
+}
+
+/// same as [`pptx_converter`], with a section per slide
+pub fn pptx_sections(path: &Path, include_notes: bool) -> Result<Vec<Section>, ConvertError> {
     let data = fs::read(path)?;
     let cursor = Cursor::new(data);
     let mut archive = ZipArchive::new(cursor)?;
-    let mut markdown = String::new();
+    let mut sections = Vec::new();
     let mut slide_num = 1;
 
     for i in 0..archive.len() {
@@ -33,14 +122,13 @@ pub fn pptx_converter(path: &Path) -> Result<String, Box<dyn std::error::Error>>
         let file_name = file.name().to_string();
 
         if file_name.starts_with("ppt/slides/") && file_name.ends_with(".xml") {
-            markdown.push_str(&format!(
-                "\n\n<!-- S-TITLE: Slide number {} -->\n",
-                slide_num
-            ));
+            let title = format!("Slide number {}", slide_num);
             slide_num += 1;
+            let mut markdown = String::new();
 
             let mut content = String::new();
             file.read_to_string(&mut content)?;
+            drop(file);
             let mut reader = quick_xml::Reader::from_str(&content);
             let mut buf = Vec::new();
             let mut table_rows: Vec<Vec<String>> = Vec::new();
@@ -137,13 +225,24 @@ pub fn pptx_converter(path: &Path) -> Result<String, Box<dyn std::error::Error>>
                         _ => {}
                     },
                     Ok(Event::Eof) => break,
-                    Err(e) => return Err(Box::new(e)),
+                    Err(e) => return Err(e.into()),
                     _ => {}
                 }
                 buf.clear();
             }
+
+            if include_notes && let Some(notes) = read_notes(&mut archive, &file_name) {
+                markdown.push_str("\n> Notes:\n");
+                for line in notes.lines() {
+                    match line.is_empty() {
+                        true => markdown.push_str(">\n"),
+                        false => markdown.push_str(&format!("> {}\n", line)),
+                    }
+                }
+            }
+            sections.push(Section::new(Some(title), SectionKind::Slide, markdown));
         }
     }
 
-    Ok(markdown.trim().to_string())
+    Ok(sections)
 }
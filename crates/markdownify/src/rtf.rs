@@ -0,0 +1,413 @@
+use encoding_rs::{Encoding, WINDOWS_1252};
+use std::path::Path;
+
+use crate::ConvertError;
+
+#[derive(Clone, Copy, PartialEq, Default)]
+struct Style {
+    bold: bool,
+    italics: bool,
+    underline: bool,
+}
+
+#[derive(Clone, Copy)]
+struct GroupState {
+    style: Style,
+    skip: bool,
+    // amount of fallback chars after \uN
+    uc: usize,
+}
+
+impl Default for GroupState {
+    fn default() -> Self {
+        GroupState {
+            style: Style::default(),
+            skip: false,
+            uc: 1,
+        }
+    }
+}
+
+/// builds the markdown, moving whitespace outside of the style markers so `**bold **` doesn't happen
+struct Writer {
+    output: String,
+    open: Style,
+    pending_space: String,
+}
+
+impl Writer {
+    fn new() -> Self {
+        Writer {
+            output: String::new(),
+            open: Style::default(),
+            pending_space: String::new(),
+        }
+    }
+
+    fn close(&mut self) {
+        if self.open.underline {
+            self.output.push_str("</u>");
+        }
+        if self.open.italics {
+            self.output.push('*');
+        }
+        if self.open.bold {
+            self.output.push_str("**");
+        }
+        self.open = Style::default();
+    }
+
+    fn push(&mut self, c: char, style: Style) {
+        if c.is_whitespace() {
+            self.pending_space.push(c);
+            return;
+        }
+        if style != self.open {
+            self.close();
+        }
+        self.output.push_str(&self.pending_space);
+        self.pending_space.clear();
+        if style != self.open {
+            if style.bold {
+                self.output.push_str("**");
+            }
+            if style.italics {
+                self.output.push('*');
+            }
+            if style.underline {
+                self.output.push_str("<u>");
+            }
+            self.open = style;
+        }
+        self.output.push(c);
+    }
+
+    fn line_break(&mut self, paragraph: bool) {
+        self.close();
+        self.pending_space.clear();
+        let output = self.output.trim_end_matches([' ', '\t']).len();
+        self.output.truncate(output);
+        if paragraph && (self.output.is_empty() || self.output.ends_with("\n\n")) {
+            return;
+        }
+        self.output
+            .push_str(if paragraph { "\n\n" } else { "  \n" });
+    }
+
+    fn finish(mut self) -> String {
+        self.close();
+        self.output
+    }
+}
+
+// destinations that hold metadata rather than document text
+const SKIPPED_DESTINATIONS: &[&str] = &[
+    "fonttbl",
+    "colortbl",
+    "stylesheet",
+    "info",
+    "pict",
+    "header",
+    "headerl",
+    "headerr",
+    "headerf",
+    "footer",
+    "footerl",
+    "footerr",
+    "footerf",
+    "listtable",
+    "listoverridetable",
+    "rsidtbl",
+    "generator",
+    "xmlnstbl",
+    "themedata",
+    "colorschememapping",
+    "datastore",
+    "latentstyles",
+    "filetbl",
+    "revtbl",
+    "object",
+    "fldinst",
+    "pgdsctbl",
+    "mmathPr",
+];
+
+fn codepage_encoding(codepage: i32) -> Option<&'static Encoding> {
+    match codepage {
+        65001 => Some(encoding_rs::UTF_8),
+        932 => Some(encoding_rs::SHIFT_JIS),
+        936 => Some(encoding_rs::GBK),
+        949 => Some(encoding_rs::EUC_KR),
+        950 => Some(encoding_rs::BIG5),
+        _ => Encoding::for_label(format!("windows-{codepage}").as_bytes()),
+    }
+}
+
+/// convert rtf into markdown
+/// usuage:
+/// ```rs
+/// let path = Path::new("path/to/file.rtf");
+/// let md = rtf_convert(&path).unwrap();
+/// println!("{}", md);
+/// ```
+pub fn rtf_convert(path: &Path) -> Result<String, ConvertError> {
+    let data = std::fs::read(path)?;
+    if !data.starts_with(b"{\\rtf") {
+        return Err(ConvertError::parse("not a valid rtf file"));
+    }
+
+    let mut writer = Writer::new();
+    let mut stack: Vec<GroupState> = Vec::new();
+    let mut state = GroupState::default();
+    let mut encoding = WINDOWS_1252;
+    // raw \'hh bytes, decoded together so multibyte codepages work
+    let mut bytes: Vec<u8> = Vec::new();
+    // fallback chars still to be skipped after a \uN
+    let mut skip_chars = 0;
+
+    let flush_bytes =
+        |bytes: &mut Vec<u8>, writer: &mut Writer, state: &GroupState, enc: &'static Encoding| {
+            if bytes.is_empty() {
+                return;
+            }
+            let (text, _) = enc.decode_without_bom_handling(bytes);
+            if !state.skip {
+                for c in text.chars() {
+                    writer.push(c, state.style);
+                }
+            }
+            bytes.clear();
+        };
+
+    let mut i = 0;
+    while i < data.len() {
+        let b = data[i];
+        let is_raw_byte = b >= 0x80 || (b == b'\\' && data.get(i + 1) == Some(&b'\''));
+        if !is_raw_byte {
+            flush_bytes(&mut bytes, &mut writer, &state, encoding);
+        }
+        match b {
+            b'{' => {
+                stack.push(state);
+                skip_chars = 0;
+                i += 1;
+            }
+            b'}' => {
+                state = stack.pop().unwrap_or_default();
+                skip_chars = 0;
+                i += 1;
+            }
+            b'\r' | b'\n' => i += 1,
+            b'\\' => {
+                i += 1;
+                let Some(&next) = data.get(i) else { break };
+
+                // control symbols
+                if !next.is_ascii_alphabetic() {
+                    i += 1;
+                    let symbol = match next {
+                        b'\'' => {
+                            let hex = data.get(i..i + 2).and_then(|h| std::str::from_utf8(h).ok());
+                            i += 2;
+                            if let Some(v) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                                if skip_chars > 0 {
+                                    skip_chars -= 1;
+                                } else {
+                                    bytes.push(v);
+                                }
+                            }
+                            continue;
+                        }
+                        b'*' => {
+                            // unknown destinations are ignorable by definition
+                            state.skip = true;
+                            continue;
+                        }
+                        b'~' => '\u{a0}',
+                        b'_' => '\u{2011}',
+                        b'-' => continue,
+                        b'\\' | b'{' | b'}' => next as char,
+                        b'\r' | b'\n' => {
+                            if !state.skip {
+                                writer.line_break(true);
+                            }
+                            continue;
+                        }
+                        _ => continue,
+                    };
+                    if !state.skip {
+                        writer.push(symbol, state.style);
+                    }
+                    continue;
+                }
+
+                // control words: \word[-N][ ]
+                let start = i;
+                while i < data.len() && data[i].is_ascii_alphabetic() {
+                    i += 1;
+                }
+                let word = String::from_utf8_lossy(&data[start..i]).into_owned();
+                let num_start = i;
+                if i < data.len() && data[i] == b'-' {
+                    i += 1;
+                }
+                while i < data.len() && data[i].is_ascii_digit() {
+                    i += 1;
+                }
+                let param: Option<i32> = std::str::from_utf8(&data[num_start..i])
+                    .ok()
+                    .and_then(|n| n.parse().ok());
+                if i < data.len() && data[i] == b' ' {
+                    i += 1;
+                }
+
+                let on = param.unwrap_or(1) != 0;
+                let text: Option<&str> = match word.as_str() {
+                    "b" => {
+                        state.style.bold = on;
+                        None
+                    }
+                    "i" => {
+                        state.style.italics = on;
+                        None
+                    }
+                    "ul" | "uld" | "uldb" | "ulw" | "ulth" => {
+                        state.style.underline = on;
+                        None
+                    }
+                    "ulnone" => {
+                        state.style.underline = false;
+                        None
+                    }
+                    "plain" | "pard" => {
+                        state.style = Style::default();
+                        None
+                    }
+                    "ansicpg" => {
+                        if let Some(enc) = param.and_then(codepage_encoding) {
+                            encoding = enc;
+                        }
+                        None
+                    }
+                    "uc" => {
+                        state.uc = param.unwrap_or(1).max(0) as usize;
+                        None
+                    }
+                    "u" => {
+                        if let Some(n) = param {
+                            let n = if n < 0 { n + 65536 } else { n } as u32;
+                            if let Some(c) = char::from_u32(n)
+                                && !state.skip
+                            {
+                                writer.push(c, state.style);
+                            }
+                            skip_chars = state.uc;
+                        }
+                        None
+                    }
+                    "par" | "sect" | "page" => {
+                        if !state.skip {
+                            writer.line_break(true);
+                        }
+                        None
+                    }
+                    "line" => {
+                        if !state.skip {
+                            writer.line_break(false);
+                        }
+                        None
+                    }
+                    "tab" => Some("\t"),
+                    "emdash" => Some("—"),
+                    "endash" => Some("–"),
+                    "bullet" => Some("•"),
+                    "lquote" => Some("‘"),
+                    "rquote" => Some("’"),
+                    "ldblquote" => Some("“"),
+                    "rdblquote" => Some("”"),
+                    "emspace" | "enspace" | "qmspace" => Some(" "),
+                    w if SKIPPED_DESTINATIONS.contains(&w) => {
+                        state.skip = true;
+                        None
+                    }
+                    _ => None,
+                };
+                if let Some(text) = text {
+                    if skip_chars > 0 {
+                        skip_chars -= 1;
+                    } else if !state.skip {
+                        for c in text.chars() {
+                            writer.push(c, state.style);
+                        }
+                    }
+                }
+            }
+            _ => {
+                if skip_chars > 0 {
+                    skip_chars -= 1;
+                } else if b >= 0x80 {
+                    // rtf should be 7bit, but some writers embed raw codepage bytes
+                    bytes.push(b);
+                } else if !state.skip {
+                    writer.push(b as char, state.style);
+                }
+                i += 1;
+            }
+        }
+    }
+    flush_bytes(&mut bytes, &mut writer, &state, encoding);
+
+    let markdown = writer.finish();
+    Ok(format!("{}\n", markdown.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::rtf_convert;
+    use std::io::Write;
+    use tempfile::Builder;
+
+    fn convert(rtf: &str) -> String {
+        let mut file = Builder::new().suffix(".rtf").tempfile().unwrap();
+        file.write_all(rtf.as_bytes()).unwrap();
+        rtf_convert(file.path()).unwrap()
+    }
+
+    #[test]
+    fn style_toggles_are_scoped_to_their_group() {
+        let md = convert(r"{\rtf1\ansi plain {\b bold} \i it\i0  \ul under\ulnone  end}");
+        assert_eq!(md, "plain **bold** *it* <u>under</u> end\n");
+    }
+
+    #[test]
+    fn unicode_skips_its_fallback_chars() {
+        // \uc2 only holds inside its group, \u252 after it skips a single `?` again
+        let md = convert(r"{\rtf1 caf\u233?s {\uc2 \u8364\'80\'80 euro} \u252?ber}");
+        assert_eq!(md, "cafés € euro über\n");
+    }
+
+    #[test]
+    fn hex_bytes_use_the_codepage() {
+        assert_eq!(convert(r"{\rtf1\ansi na\'efve}"), "naïve\n");
+        assert_eq!(
+            convert(r"{\rtf1\ansi\ansicpg1251 \'cf\'f0\'e8\'e2\'e5\'f2}"),
+            "Привет\n"
+        );
+        // multibyte codepages decode the bytes together
+        assert_eq!(convert(r"{\rtf1\ansi\ansicpg932 \'82\'a0}"), "あ\n");
+    }
+
+    #[test]
+    fn par_breaks_paragraphs_and_pard_resets_the_style() {
+        let md = convert(r"{\rtf1\pard first\par \pard\b second\par\par\pard third\line fourth}");
+        assert_eq!(md, "first\n\n**second**\n\nthird  \nfourth\n");
+    }
+
+    #[test]
+    fn metadata_destinations_are_skipped() {
+        let md = convert(
+            r"{\rtf1{\fonttbl{\f0 Arial;}}{\colortbl;\red255\green0\blue0;}{\*\generator Riched20;}{\*\unknownthing hidden}shown}",
+        );
+        assert_eq!(md, "shown\n");
+    }
+}
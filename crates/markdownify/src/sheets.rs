@@ -6,12 +6,40 @@ use std::{
 
 use calamine::Reader;
 
-fn detect_delimiter(line: &str) -> u8 {
+use crate::{ConvertError, Document, Section, SectionKind};
+
+/// counts `c` in `line`, ignoring quoted fields
+fn count_unquoted(line: &str, c: char) -> usize {
+    let mut in_quotes = false;
+    let mut count = 0;
+    for ch in line.chars() {
+        if ch == '"' {
+            in_quotes = !in_quotes;
+        } else if ch == c && !in_quotes {
+            count += 1;
+        }
+    }
+    count
+}
+
+/// picks the candidate that shows up the same amount of times on every line,
+/// falling back to the one that shows up the most
+fn detect_delimiter(lines: &[String]) -> u8 {
     let candidates = [',', ';', '\t', '|'];
     candidates
         .iter()
-        .map(|&c| (c, line.matches(c).count()))
-        .max_by_key(|&(_, count)| count)
+        .map(|&c| {
+            let counts = lines
+                .iter()
+                .map(|line| count_unquoted(line, c))
+                .collect::<Vec<_>>();
+            let first = counts.first().copied().unwrap_or(0);
+            let consistent = first > 0 && counts.iter().all(|&n| n == first);
+            let total: usize = counts.iter().sum();
+            (c, (consistent, first, total))
+        })
+        .filter(|&(_, (_, _, total))| total > 0)
+        .max_by_key(|&(_, score)| score)
         .map(|(c, _)| c as u8)
         .unwrap_or(b',') // fallback to comma
 }
@@ -42,22 +70,82 @@ pub fn to_markdown_table(headers: &[String], rows: &[Vec<String>]) -> String {
 }
 
 /// convert `xlsx` | `xls` | `xlsm` | `xlsb` | `xla` | `xlam` | `ods` files into markdown
+///
+/// `sheet` selects a worksheet by name, `sheet_index` by position (1-based).
+/// with both, the name is tried first and the position is the fallback.
+/// when neither is given all the sheets are converted.
 /// # usage:
 /// ```
 /// use std::path::Path;
 /// use markdownify::sheets::sheets_convert;
 ///
 /// let path = Path::new("path/to/file.xlsx");
-/// match sheets_convert(&path) {
+/// match sheets_convert(&path, Some("Sheet1"), None) {
 ///     Ok(md) => println!("{}", md),
 ///     Err(e) => eprintln!("Error: {}", e)
 /// }
 /// ```
-pub fn sheets_convert(path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+pub fn sheets_convert(
+    path: &Path,
+    sheet: Option<&str>,
+    sheet_index: Option<usize>,
+) -> Result<String, ConvertError> {
+    let sections = sheets_sections(path, sheet, sheet_index)?;
+    Ok(Document::new(sections).to_markdown())
+}
+
+/// same as [`sheets_convert`], with a section per worksheet
+pub fn sheets_sections(
+    path: &Path,
+    sheet: Option<&str>,
+    sheet_index: Option<usize>,
+) -> Result<Vec<Section>, ConvertError> {
     let mut workbook = calamine::open_workbook_auto(path)?;
-    let mut output = String::new();
+    let mut sections = Vec::new();
+
+    let sheet_names = workbook.sheet_names();
+    let by_position = |index: usize| {
+        index
+            .checked_sub(1)
+            .and_then(|i| sheet_names.get(i))
+            .cloned()
+    };
+    let selected = match (sheet, sheet_index) {
+        // a sheet named like a number ("2024") wins over the position
+        (Some(name), index) => match sheet_names
+            .iter()
+            .find(|s| s.as_str() == name)
+            .cloned()
+            .or_else(|| index.and_then(by_position))
+        {
+            Some(name) => vec![name],
+            None => {
+                return Err(ConvertError::OutOfRange {
+                    detail: format!(
+                        "sheet '{}' not found, available sheets: {}",
+                        name,
+                        sheet_names.join(", ")
+                    ),
+                });
+            }
+        },
+        (None, Some(index)) => match by_position(index) {
+            Some(name) => vec![name],
+            None => {
+                return Err(ConvertError::OutOfRange {
+                    detail: format!(
+                        "sheet {} is out of bounds, the workbook has {} sheets: {}",
+                        index,
+                        sheet_names.len(),
+                        sheet_names.join(", ")
+                    ),
+                });
+            }
+        },
+        (None, None) => sheet_names,
+    };
 
-    for sheet_name in workbook.sheet_names() {
+    for sheet_name in selected {
         if let Ok(range) = workbook.worksheet_range(&sheet_name) {
             let mut rows = range.rows();
             if let Some(header_row) = rows.next() {
@@ -69,38 +157,48 @@ pub fn sheets_convert(path: &Path) -> Result<String, Box<dyn std::error::Error>>
                     .map(|r| r.iter().map(|cell| cell.to_string()).collect::<Vec<_>>())
                     .collect::<Vec<_>>();
 
-                output += &format!("# {}\n\n", sheet_name);
-                output += &to_markdown_table(&headers, &body);
-                output += "\n";
+                sections.push(Section::new(
+                    Some(sheet_name),
+                    SectionKind::Sheet,
+                    to_markdown_table(&headers, &body),
+                ));
             }
         }
     }
 
-    if output.is_empty() {
-        Err("No readable sheets found.".into())
+    if sections.is_empty() {
+        Err(ConvertError::parse("No readable sheets found."))
     } else {
-        Ok(output)
+        Ok(sections)
     }
 }
 
 /// convert `csv` into markdown
+///
+/// the delimiter is sniffed from the first few lines unless `delimiter` is given.
 /// # usage:
 /// ```
 /// use std::path::Path;
 /// use markdownify::sheets::csv_converter;
 ///
 /// let path = Path::new("path/to/file.csv");
-/// match csv_converter(&path) {
+/// match csv_converter(&path, Some(b';')) {
 ///     Ok(md) => println!("{}", md),
 ///     Err(e) => eprintln!("Error: {}", e)
 /// }
 /// ```
-pub fn csv_converter(path: &Path) -> Result<String, Box<dyn std::error::Error>> {
-    let mut file = File::open(path)?;
-    let mut first_line = String::new();
-    let _ = io::BufReader::new(&mut file).read_line(&mut first_line)?;
-
-    let delimiter = detect_delimiter(&first_line);
+pub fn csv_converter(path: &Path, delimiter: Option<u8>) -> Result<String, ConvertError> {
+    let delimiter = match delimiter {
+        Some(d) => d,
+        None => {
+            let file = File::open(path)?;
+            let lines = io::BufReader::new(file)
+                .lines()
+                .take(10)
+                .collect::<Result<Vec<_>, _>>()?;
+            detect_delimiter(&lines)
+        }
+    };
     let mut reader = csv::ReaderBuilder::new()
         .delimiter(delimiter)
         .from_path(path)?;
@@ -113,7 +211,14 @@ pub fn csv_converter(path: &Path) -> Result<String, Box<dyn std::error::Error>>
 
     let rows = reader
         .records()
-        .map(|r| r.map(|rec| rec.iter().map(|s| s.to_string()).collect::<Vec<_>>()))
+        .map(|r| {
+            // quoted fields may span lines, which would break the table
+            r.map(|rec| {
+                rec.iter()
+                    .map(|s| s.replace(['\r', '\n'], " "))
+                    .collect::<Vec<_>>()
+            })
+        })
         .collect::<Result<Vec<_>, _>>()?;
 
     Ok(to_markdown_table(&headers, &rows))
@@ -1,5 +1,5 @@
 use crate::{
-    Frame,
+    Frame, FramePacer,
     term_misc::{self, ensure_space},
 };
 use std::{
@@ -227,13 +227,29 @@ fn visual_weight(r: u8, g: u8, b: u8, a: u8) -> f32 {
 /// rasteroid::ascii_encoder::encode_frames(&mut ascii_frames, out, center, false).unwrap();
 /// ```
 pub fn encode_frames(
+    frames: &mut dyn Iterator<Item = impl Frame>,
+    out: impl Write,
+    center: bool,
+    cycle: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    play_frames(frames, out, center, cycle, true, |data, buf, offset| {
+        encode_image(data, buf, offset, None)
+    })
+}
+
+/// plays frames for the encoders that print text,
+/// `in_cells` is whether the frames width is in cells (for centering) or pixels
+pub(crate) fn play_frames(
     frames: &mut dyn Iterator<Item = impl Frame>,
     mut out: impl Write,
     center: bool,
     cycle: bool,
+    in_cells: bool,
+    encode: impl Fn(&[u8], &mut Vec<u8>, Option<u16>) -> Result<(), Box<dyn std::error::Error>>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let mut last_timestamp = None;
-    let mut frame_outputs = Vec::new();
+    let mut pacer = FramePacer::default();
+    // each frame, and how long it's held for
+    let mut frame_outputs: Vec<(Vec<u8>, Duration)> = Vec::new();
     let mut start = true;
 
     for frame in frames {
@@ -242,35 +258,31 @@ pub fn encode_frames(
             continue;
         }
         let img = image::load_from_memory(data)?;
-        let offset = term_misc::center_image(img.width() as u16, true);
-
-        let target_delay = match (frame.timestamp(), last_timestamp) {
-            (ts, Some(last)) if ts > last => Duration::from_secs_f32(ts - last),
-            _ => Duration::from_millis(33), // default ~30fps
-        };
-        last_timestamp = Some(frame.timestamp());
+        let offset = term_misc::center_image(img.width() as u16, in_cells);
 
         let mut buffer = Vec::new();
 
-        encode_image(
-            data,
-            &mut buffer,
-            if center { Some(offset) } else { None },
-            None,
-        )?;
+        encode(data, &mut buffer, if center { Some(offset) } else { None })?;
+
+        let hold = pacer.hold(&frame);
+        std::thread::sleep(hold);
+        if let Some(last) = frame_outputs.last_mut() {
+            last.1 = hold;
+        }
 
         clear_write_frame(&mut out, &buffer, start)?;
         start = false;
 
         out.flush()?;
 
-        frame_outputs.push((buffer, target_delay));
-        std::thread::sleep(target_delay);
+        frame_outputs.push((buffer, Duration::ZERO));
     }
 
-    if frame_outputs.is_empty() {
+    let Some(last) = frame_outputs.last_mut() else {
         return Ok(());
-    }
+    };
+    last.1 = pacer.last_hold();
+    std::thread::sleep(last.1);
 
     if cycle {
         loop {
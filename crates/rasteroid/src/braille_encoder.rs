@@ -0,0 +1,139 @@
+use crate::{
+    Frame, ascii_encoder,
+    term_misc::{self, SizeDirection},
+};
+use image::{DynamicImage, RgbaImage, imageops::FilterType};
+use std::io::Write;
+
+// Luminance threshold: a dot is raised when its pixel is brighter than this
+const LUM_THRESHOLD: f32 = 35.0;
+
+// bit of each dot inside a 2x4 block, indexed [row][column]
+const DOT_BITS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// Renders an image as Unicode Braille in the terminal.
+///
+/// Each terminal cell holds a 2x4 block of dots (U+2800 - U+28FF), so it reaches roughly
+/// four times the resolution of the half-block ASCII output on the same grid.
+/// The image is expected in pixels (like for the other protocols), it's resampled
+/// to the amount of cells it covers. Each cell is colored with the average color of its raised dots.
+///
+/// # Arguments
+/// - `img`: Image byte slice (any format supported by `image` crate, e.g., PNG, JPEG)
+/// - `out`: A writer to send output to (e.g., `std::io::stdout`)
+/// - `offset`: Optional horizontal offset in terminal columns (used for centering)
+/// - `print_at`: Optional locaiton the image should be printed at
+///
+/// # Example
+/// ```
+/// use std::path::Path;
+/// use std::io::{self, Write};
+/// use rasteroid::braille_encoder::encode_image;
+///
+/// let path = Path::new("image.png");
+/// let bytes = match std::fs::read(path) {
+///     Ok(bytes) => bytes,
+///     Err(_) => return,
+/// };
+///
+/// let mut stdout = io::stdout();
+/// encode_image(&bytes, &mut stdout, Some(10), None).unwrap();
+/// stdout.flush().unwrap();
+/// ```
+pub fn encode_image(
+    img: &[u8],
+    out: &mut impl Write,
+    offset: Option<u16>,
+    print_at: Option<(u16, u16)>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let image = image::load_from_memory(img)?;
+    let rgba_image = to_dots(&image)?;
+
+    let w = rgba_image.width();
+    let h = rgba_image.height();
+
+    for (row, y) in (0..h).step_by(4).enumerate() {
+        if let Some(at) = print_at {
+            let loc = term_misc::loc_to_terminal(Some((at.0, at.1 + row as u16)));
+            out.write_all(loc.as_ref())?;
+        }
+        if let Some(off) = offset {
+            let center = term_misc::offset_to_terminal(Some(off));
+            out.write_all(center.as_ref())?;
+        }
+
+        for x in (0..w).step_by(2) {
+            let mut bits = 0;
+            let mut sum = [0u32; 3];
+            let mut lit = 0;
+            for (dy, row_bits) in DOT_BITS.iter().enumerate() {
+                for (dx, bit) in row_bits.iter().enumerate() {
+                    let (px, py) = (x + dx as u32, y + dy as u32);
+                    if px >= w || py >= h {
+                        continue;
+                    }
+                    let p = rgba_image.get_pixel(px, py);
+                    if visual_weight(p[0], p[1], p[2], p[3]) > LUM_THRESHOLD {
+                        bits |= bit;
+                        sum[0] += p[0] as u32;
+                        sum[1] += p[1] as u32;
+                        sum[2] += p[2] as u32;
+                        lit += 1;
+                    }
+                }
+            }
+
+            if lit == 0 {
+                out.write_all(b" ")?;
+                continue;
+            }
+            let c = char::from_u32(0x2800 + bits).unwrap_or(' ');
+            let (r, g, b) = (sum[0] / lit, sum[1] / lit, sum[2] / lit);
+            write!(out, "\x1b[38;2;{};{};{}m{}\x1b[0m", r, g, b, c)?;
+        }
+
+        out.write_all(b"\n")?;
+    }
+
+    out.write_all(b"\x1b[0m")?;
+    Ok(())
+}
+
+/// resamples the image so each of its pixels is a single dot (2x4 per cell)
+fn to_dots(image: &DynamicImage) -> Result<RgbaImage, Box<dyn std::error::Error>> {
+    let cells_w = term_misc::dim_to_cells(&format!("{}px", image.width()), SizeDirection::Width)?;
+    let cells_h = term_misc::dim_to_cells(&format!("{}px", image.height()), SizeDirection::Height)?;
+
+    Ok(image
+        .resize_exact(cells_w.max(1) * 2, cells_h.max(1) * 4, FilterType::Triangle)
+        .to_rgba8())
+}
+
+fn visual_weight(r: u8, g: u8, b: u8, a: u8) -> f32 {
+    if a == 0 {
+        0.0
+    } else {
+        (0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32) * (a as f32 / 255.0)
+    }
+}
+
+/// Streams a sequence of video frames to the terminal as Braille.
+///
+/// Works the same as [`ascii_encoder::encode_frames`], but the frames are expected in pixels
+/// (see [`encode_image`]).
+///
+/// # Arguments
+/// - `frames`: A mutable iterator over items implementing the `Frame` trait.
+/// - `out`: A writer to send output to (e.g., `std::io::stdout()`).
+/// - `center`: If `true`, horizontally centers each frame in the terminal.
+/// - `cycle`: If `true`, will loop over the animation until interrupted.
+pub fn encode_frames(
+    frames: &mut dyn Iterator<Item = impl Frame>,
+    out: impl Write,
+    center: bool,
+    cycle: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    ascii_encoder::play_frames(frames, out, center, cycle, false, |data, buf, offset| {
+        encode_image(data, buf, offset, None)
+    })
+}
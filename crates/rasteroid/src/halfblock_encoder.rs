@@ -0,0 +1,118 @@
+use crate::{
+    Frame, ascii_encoder,
+    term_misc::{self, EnvIdentifiers},
+};
+use std::io::Write;
+
+// pixels under this alpha are left empty, their color is usually meaningless
+const MIN_ALPHA: u8 = 64;
+
+/// Renders an image with truecolor upper half-blocks (`▀`).
+///
+/// Each cell holds two vertical pixels, the top one as the foreground color and the
+/// bottom one as the background color. Unlike the ASCII encoder nothing is filtered out,
+/// only mostly transparent pixels are left empty.
+///
+/// # Arguments
+/// - `img`: Image byte slice (any format supported by `image` crate, e.g., PNG, JPEG),
+///   its width is in cells and every 2 pixels of its height are a single row
+/// - `out`: A writer to send output to (e.g., `std::io::stdout`)
+/// - `offset`: Optional horizontal offset in terminal columns (used for centering)
+/// - `print_at`: Optional locaiton the image should be printed at
+///
+/// # Example
+/// ```
+/// use std::path::Path;
+/// use std::io::{self, Write};
+/// use rasteroid::halfblock_encoder::encode_image;
+///
+/// let path = Path::new("image.png");
+/// let bytes = match std::fs::read(path) {
+///     Ok(bytes) => bytes,
+///     Err(_) => return,
+/// };
+///
+/// let mut stdout = io::stdout();
+/// encode_image(&bytes, &mut stdout, Some(10), None).unwrap();
+/// stdout.flush().unwrap();
+/// ```
+pub fn encode_image(
+    img: &[u8],
+    out: &mut impl Write,
+    offset: Option<u16>,
+    print_at: Option<(u16, u16)>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let image = image::load_from_memory(img)?;
+    let rgba_image = image.to_rgba8();
+
+    let w = rgba_image.width();
+    let h = rgba_image.height();
+
+    for (row, y) in (0..h).step_by(2).enumerate() {
+        if let Some(at) = print_at {
+            let loc = term_misc::loc_to_terminal(Some((at.0, at.1 + row as u16)));
+            out.write_all(loc.as_ref())?;
+        }
+        if let Some(off) = offset {
+            let center = term_misc::offset_to_terminal(Some(off));
+            out.write_all(center.as_ref())?;
+        }
+
+        for x in 0..w {
+            let top = rgba_image.get_pixel(x, y);
+            // odd heights have a transparent last half
+            let bottom = match y + 1 < h {
+                true => *rgba_image.get_pixel(x, y + 1),
+                false => image::Rgba([0, 0, 0, 0]),
+            };
+
+            match (top[3] >= MIN_ALPHA, bottom[3] >= MIN_ALPHA) {
+                (false, false) => out.write_all(b" ")?,
+                (true, false) => {
+                    write!(out, "\x1b[38;2;{};{};{}m▀\x1b[0m", top[0], top[1], top[2])?
+                }
+                (false, true) => write!(
+                    out,
+                    "\x1b[38;2;{};{};{}m▄\x1b[0m",
+                    bottom[0], bottom[1], bottom[2]
+                )?,
+                (true, true) => write!(
+                    out,
+                    "\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m▀\x1b[0m",
+                    top[0], top[1], top[2], bottom[0], bottom[1], bottom[2]
+                )?,
+            }
+        }
+
+        out.write_all(b"\n")?;
+    }
+
+    out.write_all(b"\x1b[0m")?;
+    Ok(())
+}
+
+/// checks if the terminal reports 24bit colors through `COLORTERM`
+pub fn is_truecolor_capable(env: &mut EnvIdentifiers) -> bool {
+    env.contains("COLORTERM", "truecolor") || env.contains("COLORTERM", "24bit")
+}
+
+/// Streams a sequence of video frames to the terminal with half-blocks.
+///
+/// Works the same as [`ascii_encoder::encode_frames`], the frames are expected in cells
+/// (see [`encode_image`]).
+///
+/// # Arguments
+/// - `frames`: A mutable iterator over items implementing the `Frame` trait.
+/// - `out`: A writer to send output to (e.g., `std::io::stdout()`).
+/// - `center`: If `true`, horizontally centers each frame in the terminal.
+/// - `cycle`: If `true`, will loop over the animation until interrupted.
+pub fn encode_frames(
+    frames: &mut dyn Iterator<Item = impl Frame>,
+    out: impl Write,
+    center: bool,
+    cycle: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    ascii_encoder::play_frames(frames, out, center, cycle, true, |data, buf, offset| {
+        encode_image(data, buf, offset, None)
+    })
+}
@@ -1,12 +1,67 @@
-use std::{error, io::Cursor};
+use std::{
+    error,
+    io::Cursor,
+    sync::atomic::{AtomicU64, Ordering},
+};
 
 use fast_image_resize::{IntoImageView, Resizer, images::Image};
-use image::{DynamicImage, GenericImage, GenericImageView, ImageEncoder, codecs::png::PngEncoder};
+use image::{
+    DynamicImage, GenericImage, GenericImageView, ImageDecoder, ImageEncoder, ImageReader,
+    ImageResult, codecs::png::PngEncoder, metadata::Orientation,
+};
 
 use crate::term_misc::dim_to_cells;
 
 use super::term_misc::{self, dim_to_px};
 
+/// the default [`set_image_budget`], a 4k screen fits in it
+pub const DEFAULT_MAX_IMAGE_PIXELS: u64 = 12_000_000;
+pub const DEFAULT_MAX_IMAGE_BYTES: u64 = 8_000_000;
+
+static MAX_IMAGE_PIXELS: AtomicU64 = AtomicU64::new(DEFAULT_MAX_IMAGE_PIXELS);
+static MAX_IMAGE_BYTES: AtomicU64 = AtomicU64::new(DEFAULT_MAX_IMAGE_BYTES);
+
+/// caps what [`InlineImage::resize_plus_max`] encodes, so a huge photo doesn't flood a slow terminal.
+/// images over `max_pixels`, or encoding into more than `max_bytes`, are downsampled until they fit.
+/// 0 turns a limit off
+pub fn set_image_budget(max_pixels: u64, max_bytes: u64) {
+    MAX_IMAGE_PIXELS.store(max_pixels, Ordering::Relaxed);
+    MAX_IMAGE_BYTES.store(max_bytes, Ordering::Relaxed);
+}
+
+/// the current `(max_pixels, max_bytes)`, see [`set_image_budget`]
+pub fn image_budget() -> (u64, u64) {
+    (
+        MAX_IMAGE_PIXELS.load(Ordering::Relaxed),
+        MAX_IMAGE_BYTES.load(Ordering::Relaxed),
+    )
+}
+
+/// decodes an image like `image::load_from_memory`,
+/// and with `auto_orient` turns it upright using its EXIF orientation.
+/// # example:
+/// ```no_run
+/// use rasteroid::image_extended::load_image;
+///
+/// let bytes = std::fs::read("photo.jpg").unwrap();
+/// let dyn_img = load_image(&bytes, true).unwrap();
+/// ```
+pub fn load_image(bytes: &[u8], auto_orient: bool) -> ImageResult<DynamicImage> {
+    let mut decoder = ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()?
+        .into_decoder()?;
+    let orientation = match auto_orient {
+        true => decoder.orientation().unwrap_or(Orientation::NoTransforms),
+        false => Orientation::NoTransforms,
+    };
+    let mut img = DynamicImage::from_decoder(decoder)?;
+    img.apply_orientation(orientation);
+    Ok(img)
+}
+
+/// (png data, center offset, width, height)
+pub type ResizeResult = Result<(Vec<u8>, u16, u32, u32), Box<dyn error::Error>>;
+
 pub trait InlineImage {
     /// fast image resizer, that takes logic units.
     /// # example:
@@ -33,17 +88,146 @@ pub trait InlineImage {
         height: Option<&str>,
         resize_for_ascii: bool,
         pad: bool,
-    ) -> Result<(Vec<u8>, u16, u32, u32), Box<dyn error::Error>>;
+    ) -> ResizeResult;
+
+    /// same as `resize_plus`, but caps the encoded raster to `max_px` (width, height) pixels.
+    /// useful for slow encoders (sixel) on huge terminals.
+    /// # example:
+    /// ```
+    /// use std::path::Path;
+    /// use rasteroid::image_extended::InlineImage;
+    ///
+    /// let path = Path::new("image.png");
+    /// let buf = match std::fs::read(path) {
+    ///     Ok(buf) => buf,
+    ///     Err(e) => return,
+    /// };
+    /// let dyn_img = image::load_from_memory(&buf).unwrap();
+    /// let (img_data, offset, width, height) = dyn_img.resize_plus_max(Some("80%"), Some("80%"), false, false, Some((2000, 2000))).unwrap();
+    /// ```
+    /// * the cap is ignored when resizing for ascii, since it works in cells
+    fn resize_plus_max(
+        &self,
+        width: Option<&str>,
+        height: Option<&str>,
+        resize_for_ascii: bool,
+        pad: bool,
+        max_px: Option<(u32, u32)>,
+    ) -> ResizeResult;
+
+    /// cuts out the `x, y, width, height` rectangle of the image.
+    /// # example:
+    /// ```
+    /// use rasteroid::image_extended::InlineImage;
+    ///
+    /// let dyn_img = image::DynamicImage::new_rgba8(400, 200);
+    /// let cropped = dyn_img.crop_plus("10%", "0", "50%", "100px").unwrap();
+    /// assert_eq!((cropped.width(), cropped.height()), (200, 100));
+    /// ```
+    /// * it accepts either `%` (of the image) / `c` (cells) / `px` / just a number
+    /// * a rectangle going out of the image is clamped to it
+    fn crop_plus(
+        &self,
+        x: &str,
+        y: &str,
+        width: &str,
+        height: &str,
+    ) -> Result<DynamicImage, String>;
+
+    /// rotates the image clockwise and then flips it.
+    /// # example:
+    /// ```
+    /// use rasteroid::image_extended::{Flip, InlineImage};
+    ///
+    /// let dyn_img = image::DynamicImage::new_rgba8(400, 200);
+    /// let rotated = dyn_img.rotate_flip(90, Some(Flip::Horizontal));
+    /// assert_eq!((rotated.width(), rotated.height()), (200, 400));
+    /// ```
+    /// * rotations other than 90, 180 or 270 leave the image as is
+    fn rotate_flip(&self, rotate: u32, flip: Option<Flip>) -> DynamicImage;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Flip {
+    Horizontal,
+    Vertical,
+}
+
+impl Flip {
+    /// accepts `h` / `horizontal` and `v` / `vertical`
+    pub fn parse(s: &str) -> Option<Flip> {
+        match s.to_ascii_lowercase().as_str() {
+            "h" | "horizontal" => Some(Flip::Horizontal),
+            "v" | "vertical" => Some(Flip::Vertical),
+            _ => None,
+        }
+    }
+}
+
+fn crop_dim(dim: &str, full: u32, direction: term_misc::SizeDirection) -> Result<u32, String> {
+    match dim.strip_suffix('%') {
+        Some(percent) => percent
+            .parse::<f32>()
+            .map(|p| (full as f32 * p.max(0.0) / 100.0).round() as u32)
+            .map_err(|_| format!("Invalid dimension format: {}", dim)),
+        None => dim_to_px(dim, direction),
+    }
 }
 
 impl InlineImage for DynamicImage {
+    fn crop_plus(
+        &self,
+        x: &str,
+        y: &str,
+        width: &str,
+        height: &str,
+    ) -> Result<DynamicImage, String> {
+        use term_misc::SizeDirection::{Height, Width};
+
+        let (src_width, src_height) = self.dimensions();
+        if src_width == 0 || src_height == 0 {
+            return Ok(self.clone());
+        }
+        let x = crop_dim(x, src_width, Width)?.min(src_width - 1);
+        let y = crop_dim(y, src_height, Height)?.min(src_height - 1);
+        let width = crop_dim(width, src_width, Width)?.clamp(1, src_width - x);
+        let height = crop_dim(height, src_height, Height)?.clamp(1, src_height - y);
+
+        Ok(self.crop_imm(x, y, width, height))
+    }
+
+    fn rotate_flip(&self, rotate: u32, flip: Option<Flip>) -> DynamicImage {
+        let img = match rotate % 360 {
+            90 => self.rotate90(),
+            180 => self.rotate180(),
+            270 => self.rotate270(),
+            _ => self.clone(),
+        };
+        match flip {
+            Some(Flip::Horizontal) => img.fliph(),
+            Some(Flip::Vertical) => img.flipv(),
+            None => img,
+        }
+    }
+
     fn resize_plus(
         &self,
         width: Option<&str>,
         height: Option<&str>,
         resize_for_ascii: bool,
         pad: bool,
-    ) -> Result<(Vec<u8>, u16, u32, u32), Box<dyn error::Error>> {
+    ) -> ResizeResult {
+        self.resize_plus_max(width, height, resize_for_ascii, pad, None)
+    }
+
+    fn resize_plus_max(
+        &self,
+        width: Option<&str>,
+        height: Option<&str>,
+        resize_for_ascii: bool,
+        pad: bool,
+        max_px: Option<(u32, u32)>,
+    ) -> ResizeResult {
         let (src_width, src_height) = self.dimensions();
         let width = match width {
             Some(w) => match resize_for_ascii {
@@ -59,27 +243,54 @@ impl InlineImage for DynamicImage {
             },
             None => src_height,
         };
+        let (mut width, mut height) = match max_px {
+            Some((max_width, max_height)) if !resize_for_ascii => {
+                (width.min(max_width), height.min(max_height))
+            }
+            _ => (width, height),
+        };
 
-        let (new_width, new_height) = calc_fit(src_width, src_height, width, height);
-        let center = term_misc::center_image(new_width as u16, resize_for_ascii);
+        let (max_pixels, max_bytes) = match resize_for_ascii {
+            true => (0, 0),
+            false => image_budget(),
+        };
+        let (fit_width, fit_height) = calc_fit(src_width, src_height, width, height);
+        let pixels = fit_width as u64 * fit_height as u64;
+        if max_pixels != 0 && pixels > max_pixels {
+            (width, height) = scale_box(width, height, max_pixels as f64 / pixels as f64);
+        }
 
-        let mut dst_image = Image::new(
-            new_width,
-            new_height,
-            self.pixel_type().ok_or("image is invalid")?,
-        );
-        let mut resizer = Resizer::new();
-        resizer.resize(self, &mut dst_image, None)?;
-
-        let mut buffer = Vec::new();
-        let mut cursor = Cursor::new(&mut buffer);
-        let encoder = PngEncoder::new(&mut cursor);
-        encoder.write_image(
-            dst_image.buffer(),
-            dst_image.width(),
-            dst_image.height(),
-            self.color().into(),
-        )?;
+        // the encoded size is only known after encoding, a few tries get it under the budget
+        let mut tries = 0;
+        let (buffer, new_width, new_height) = loop {
+            let (new_width, new_height) = calc_fit(src_width, src_height, width, height);
+            let mut dst_image = Image::new(
+                new_width.max(1),
+                new_height.max(1),
+                self.pixel_type().ok_or("image is invalid")?,
+            );
+            let mut resizer = Resizer::new();
+            resizer.resize(self, &mut dst_image, None)?;
+
+            let mut buffer = Vec::new();
+            let mut cursor = Cursor::new(&mut buffer);
+            let encoder = PngEncoder::new(&mut cursor);
+            encoder.write_image(
+                dst_image.buffer(),
+                dst_image.width(),
+                dst_image.height(),
+                self.color().into(),
+            )?;
+
+            let len = buffer.len() as u64;
+            tries += 1;
+            if max_bytes == 0 || len <= max_bytes || tries > 4 || new_width.min(new_height) <= 1 {
+                break (buffer, new_width.max(1), new_height.max(1));
+            }
+            // a bit under, png sizes don't scale exactly with the area
+            (width, height) = scale_box(width, height, max_bytes as f64 / len as f64 * 0.9);
+        };
+        let center = term_misc::center_image(new_width as u16, resize_for_ascii);
 
         if pad && (new_width != width || new_height != height) {
             let img = image::load_from_memory(&buffer)?;
@@ -363,3 +574,12 @@ pub fn calc_fit(src_width: u32, src_height: u32, dst_width: u32, dst_height: u32
         (scaled_width, dst_height)
     }
 }
+
+/// shrinks `width` x `height` so its area is `area_ratio` of what it was
+fn scale_box(width: u32, height: u32, area_ratio: f64) -> (u32, u32) {
+    let factor = area_ratio.sqrt();
+    (
+        ((width as f64 * factor) as u32).max(1),
+        ((height as f64 * factor) as u32).max(1),
+    )
+}
@@ -59,7 +59,7 @@ pub fn encode_image(
 /// ```
 pub fn is_iterm_capable(env: &mut EnvIdentifiers) -> bool {
     env.term_contains("mintty")
-        || env.term_contains("wezterm")
+        || env.is_wezterm()
         || env.term_contains("iterm2")
         || env.term_contains("rio")
         || (env.term_contains("warp") && !env.contains("OS", "windows"))
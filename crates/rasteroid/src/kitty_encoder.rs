@@ -45,13 +45,23 @@ fn transmit_shm(
     Ok(())
 }
 
+// the most base64 kitty takes in a single escape sequence
+const CHUNK_SIZE: usize = 4096;
+// how often the progress is reported, in bytes of base64
+const PROGRESS_STEP: usize = 64 * 1024;
+
+/// `before_last` is written right before the last chunk, where the image is placed.
+/// outside of tmux `progress` is called between chunks with `(sent, total)` bytes, after a flush,
+/// so whatever it draws doesn't end up inside an escape sequence.
+/// `sent == total` comes right before the last chunk, anything drawn should be cleared by then
 fn chunk_base64(
     base64: &str,
     out: &mut impl Write,
-    size: usize,
     first_opts: HashMap<String, String>,
     sub_opts: HashMap<String, String>,
     tmux: bool,
+    before_last: &str,
+    mut progress: Option<&mut dyn FnMut(usize, usize)>,
 ) -> Result<(), std::io::Error> {
     // first block
     let mut first_opts_string = String::with_capacity(first_opts.len() * 8);
@@ -87,12 +97,31 @@ fn chunk_base64(
 
     let total_bytes = base64.len();
     let mut start = 0;
+    let mut reported = 0;
 
     while start < total_bytes {
-        let end = min(start + size, total_bytes);
+        let end = min(start + CHUNK_SIZE, total_bytes);
         let chunk_data = &base64[start..end];
         let more_chunks = (end != total_bytes) as u8;
 
+        // reported as done right before the last chunk, which shows the image
+        let report = match more_chunks {
+            0 => Some(total_bytes),
+            _ if start - reported >= PROGRESS_STEP => Some(start),
+            _ => None,
+        };
+        if let Some(progress) = progress.as_mut()
+            && let Some(sent) = report
+            && !tmux
+        {
+            out.flush()?;
+            progress(sent, total_bytes);
+            reported = start;
+        }
+        if more_chunks == 0 {
+            out.write_all(before_last.as_bytes())?;
+        }
+
         let opts = if start == 0 {
             &first_opts_string
         } else {
@@ -134,12 +163,37 @@ fn chunk_base64(
 /// ```
 /// the option offset just offsets the image to the right by the amount of cells you specify
 /// the print at is the same just absolute position
+/// returns the id the image was given, for [`delete_image`]
 pub fn encode_image(
     img: &[u8],
     out: &mut impl Write,
     offset: Option<u16>,
     print_at: Option<(u16, u16)>,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<u32, Box<dyn std::error::Error>> {
+    encode(img, out, offset, print_at, None)
+}
+
+/// same as [`encode_image`], calling `progress` with `(sent, total)` bytes while the image is sent.
+/// it's called between complete escape sequences, so it may draw on the terminal (on stderr),
+/// as long as it's cleared with the cursor back in place once `sent == total`.
+/// never called in tmux, the whole transfer is a single passthrough sequence there
+pub fn encode_image_with_progress(
+    img: &[u8],
+    out: &mut impl Write,
+    offset: Option<u16>,
+    print_at: Option<(u16, u16)>,
+    progress: &mut dyn FnMut(usize, usize),
+) -> Result<u32, Box<dyn std::error::Error>> {
+    encode(img, out, offset, print_at, Some(progress))
+}
+
+fn encode(
+    img: &[u8],
+    out: &mut impl Write,
+    offset: Option<u16>,
+    print_at: Option<(u16, u16)>,
+    progress: Option<&mut dyn FnMut(usize, usize)>,
+) -> Result<u32, Box<dyn std::error::Error>> {
     let id = rand::random::<u32>();
     let mut opts = HashMap::from([
         ("f".to_string(), "100".to_string()),
@@ -162,20 +216,37 @@ pub fn encode_image(
         opts.insert("r".to_string(), rows.to_string());
         opts.insert("c".to_string(), cols.to_string());
         let base64 = image_to_base64(img);
-        chunk_base64(&base64, out, 4096, opts, HashMap::new(), tmux)?;
+        chunk_base64(&base64, out, opts, HashMap::new(), tmux, "", progress)?;
 
         let placement = create_unicode_placeholder(cols, rows, id, offset, print_at)?;
         out.write_all(placement.as_bytes())?;
     } else {
-        let center_string = offset_to_terminal(offset);
-        let print_at_string = loc_to_terminal(print_at);
-        out.write_all(print_at_string.as_ref())?;
-        out.write_all(center_string.as_ref())?;
+        // the image lands where the cursor is at the last chunk, in tmux that's inside the passthrough
+        let position = format!(
+            "{}{}",
+            loc_to_terminal(print_at),
+            offset_to_terminal(offset)
+        );
+        let before_last = match tmux {
+            true => {
+                out.write_all(position.as_bytes())?;
+                ""
+            }
+            false => &position,
+        };
         let base64 = image_to_base64(img);
-        chunk_base64(&base64, out, 4096, opts, HashMap::new(), tmux)?;
+        chunk_base64(
+            &base64,
+            out,
+            opts,
+            HashMap::new(),
+            tmux,
+            before_last,
+            progress,
+        )?;
     }
 
-    Ok(())
+    Ok(id)
 }
 
 const DIACRITICS: &[&str] = &[
@@ -280,10 +351,11 @@ fn process_frame(
         chunk_base64(
             &base64,
             out,
-            4096,
             first_opts,
             sub_opts.unwrap_or_default(),
             tmux,
+            "",
+            None,
         )?;
     }
     Ok(())
@@ -345,14 +417,15 @@ fn process_frame(
 /// // should be used carefully, leaks memory with lifetime over the app itself. if kitty doesn't
 /// // consume the animation, will just sit in /dev/shm or corresponding place for other os and
 /// // take alot of memory
-/// unsafe { encode_frames_fast(&mut kitty_frames, &mut out, true) };
+/// unsafe { encode_frames_fast(&mut kitty_frames, &mut out, true, false) };
 /// ```
 pub unsafe fn encode_frames_fast(
     frames: &mut dyn Iterator<Item = impl Frame>,
     out: &mut impl Write,
     center: bool,
+    cycle: bool,
 ) -> Result<(), Box<dyn Error>> {
-    encode_frames_sep(frames, out, center, true)
+    encode_frames_sep(frames, out, center, cycle, true)
 }
 
 /// encode a video into inline video.
@@ -405,20 +478,23 @@ pub unsafe fn encode_frames_fast(
 ///             }
 ///         });
 /// let id = rand::random::<u32>();
-/// encode_frames(&mut kitty_frames, &mut out, true);
+/// encode_frames(&mut kitty_frames, &mut out, true, false);
 /// ```
 pub fn encode_frames(
     frames: &mut dyn Iterator<Item = impl Frame>,
     out: &mut impl Write,
     center: bool,
+    cycle: bool,
 ) -> Result<(), Box<dyn Error>> {
-    encode_frames_sep(frames, out, center, false)
+    encode_frames_sep(frames, out, center, cycle, false)
 }
 
+/// `cycle` loops the animation until the image is deleted, otherwise it plays once
 fn encode_frames_sep(
     frames: &mut dyn Iterator<Item = impl Frame>,
     out: &mut impl Write,
     center: bool,
+    cycle: bool,
     use_shm: bool,
 ) -> Result<(), Box<dyn Error>> {
     // getting the first frame
@@ -485,7 +561,8 @@ fn encode_frames_sep(
     )?;
 
     // starting the animation
-    let z = 100;
+    // the gap after the root frame
+    let z = first.duration().map_or(100, |d| (d * 1000.0) as u32);
     write!(out, "{prefix}a=a,s=2,v=1,r=1,i={id},z={z}{suffix}")?;
 
     let shutdown = term_misc::setup_signal_handler();
@@ -498,7 +575,10 @@ fn encode_frames_sep(
         let v = frame.height().to_string();
         let i = id.to_string();
         let f = "24".to_string();
-        let z = ((frame.timestamp() - pre_timestamp) * 1000.0) as u32;
+        let z = (frame
+            .duration()
+            .unwrap_or(frame.timestamp() - pre_timestamp)
+            * 1000.0) as u32;
         pre_timestamp = frame.timestamp();
 
         let first_opts = HashMap::from([
@@ -531,7 +611,9 @@ fn encode_frames_sep(
         let placement = create_unicode_placeholder(cols, rows, id, offset, None)?;
         out.write_all(placement.as_bytes())?;
     }
-    write!(out, "{prefix}a=a,s=3,v=1,r=1,i={id},z={z}{suffix}")?;
+    // v is the loop count, 1 is forever and n loops n - 1 times
+    let loops = if cycle { 1 } else { 2 };
+    write!(out, "{prefix}a=a,s=3,v={loops},r=1,i={id},z={z}{suffix}")?;
     Ok(())
 }
 
@@ -543,15 +625,26 @@ pub fn delete_all_images(out: &mut impl Write) -> Result<(), std::io::Error> {
         out.write_all(b"\x1b_Ga=d,d=r,x=0,y=4294967295\x1b\\")
     }
 }
-pub fn delete_single_image(id: u32, out: &mut impl Write) -> Result<(), std::io::Error> {
+
+/// deletes the image with `id` and all of its placements
+/// # example:
+/// ```
+/// use rasteroid::kitty_encoder::{delete_image, encode_image};
+///
+/// let bytes = match std::fs::read("image.png") {
+///     Ok(bytes) => bytes,
+///     Err(_) => return,
+/// };
+/// let mut stdout = std::io::stdout();
+/// let id = encode_image(&bytes, &mut stdout, None, None).unwrap();
+/// delete_image(id, &mut stdout).unwrap();
+/// ```
+pub fn delete_image(id: u32, out: &mut impl Write) -> Result<(), std::io::Error> {
     let tmux = term_misc::get_wininfo().is_tmux;
     if tmux {
-        write!(
-            out,
-            "\x1bPtmux;\x1b\x1b_Ga=d,d=i,i={id},p={id}\x1b\x1b\\\x1b\\"
-        )
+        write!(out, "\x1bPtmux;\x1b\x1b_Ga=d,d=i,i={id}\x1b\x1b\\\x1b\\")
     } else {
-        write!(out, "\x1b_Ga=d,d=i,i={id},p={id}\x1b\\")
+        write!(out, "\x1b_Ga=d,d=i,i={id}\x1b\\")
     }
 }
 
@@ -565,5 +658,5 @@ pub fn delete_single_image(id: u32, out: &mut impl Write) -> Result<(), std::io:
 /// println!("Kitty: {}", is_capable);
 /// ```
 pub fn is_kitty_capable(env: &mut EnvIdentifiers) -> bool {
-    env.term_contains("kitty") || env.term_contains("ghostty")
+    env.term_contains("kitty") || env.is_ghostty()
 }
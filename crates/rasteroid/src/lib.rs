@@ -1,19 +1,24 @@
 use std::{
-    io::{self, Write},
+    io::{self, Cursor, Write},
     process::Command,
+    sync::{Mutex, OnceLock},
+    time::Duration,
 };
 
-use image::load_from_memory;
-use term_misc::{EnvIdentifiers, ensure_space};
+use image::ImageReader;
+use term_misc::{EnvIdentifiers, SizeDirection, ensure_space};
 
 pub mod ascii_encoder;
+pub mod braille_encoder;
+pub mod halfblock_encoder;
 pub mod image_extended;
 pub mod iterm_encoder;
 pub mod kitty_encoder;
 pub mod sixel_encoder;
 pub mod term_misc;
 
-/// encode an image bytes into inline image using the given encoder
+/// encode an image bytes into inline image using the given encoder,
+/// returns the (width, height) in cells the image took, and its id with kitty (see [`kitty_encoder::delete_image`])
 /// # example:
 /// ```
 /// use std::path::Path;
@@ -29,8 +34,8 @@ pub mod term_misc;
 /// };
 /// let mut stdout = std::io::stdout();
 /// let mut env = EnvIdentifiers::new();
-/// let encoder = InlineEncoder::auto_detect(true, false, false, false, &mut env); // force kitty as fallback
-/// inline_an_image(&bytes, &mut stdout, None, None, &encoder).unwrap();
+/// let encoder = InlineEncoder::auto_detect(true, false, false, false, false, false, &mut env); // force kitty as fallback
+/// let (width, height, _) = inline_an_image(&bytes, &mut stdout, None, None, &encoder).unwrap();
 /// stdout.flush().unwrap();
 /// ```
 /// MENTION: it should work for Iterm Gifs too.
@@ -40,30 +45,59 @@ pub fn inline_an_image(
     offset: Option<u16>,
     print_at: Option<(u16, u16)>,
     inline_encoder: &InlineEncoder,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<(u16, u16, Option<u32>), Box<dyn std::error::Error>> {
     let is_tmux = term_misc::get_wininfo().is_tmux;
     let self_handle = match inline_encoder {
         InlineEncoder::Iterm | InlineEncoder::Sixel => true,
-        InlineEncoder::Kitty | InlineEncoder::Ascii => false,
+        InlineEncoder::Kitty
+        | InlineEncoder::Ascii
+        | InlineEncoder::HalfBlock
+        | InlineEncoder::Braille => false,
     } && is_tmux;
-    let mut img_cells = 0;
+    let (width, height) = image_cells(img, inline_encoder)?;
     if self_handle {
-        let img_px = load_from_memory(img)?.height();
-        img_cells =
-            term_misc::dim_to_cells(&format!("{img_px}px"), term_misc::SizeDirection::Height)?;
-        ensure_space(out, img_cells as u16)?;
+        ensure_space(out, height)?;
     }
+    let mut id = None;
     match inline_encoder {
-        InlineEncoder::Kitty => kitty_encoder::encode_image(img, out, offset, print_at),
-        InlineEncoder::Iterm => iterm_encoder::encode_image(img, out, offset, print_at),
-        InlineEncoder::Sixel => sixel_encoder::encode_image(img, out, offset, print_at),
-        InlineEncoder::Ascii => ascii_encoder::encode_image(img, out, offset, print_at),
-    }?;
+        InlineEncoder::Kitty => {
+            id = Some(kitty_encoder::encode_image(img, out, offset, print_at)?);
+        }
+        InlineEncoder::Iterm => iterm_encoder::encode_image(img, out, offset, print_at)?,
+        InlineEncoder::Sixel => sixel_encoder::encode_image(img, out, offset, print_at)?,
+        InlineEncoder::Ascii => ascii_encoder::encode_image(img, out, offset, print_at)?,
+        InlineEncoder::HalfBlock => halfblock_encoder::encode_image(img, out, offset, print_at)?,
+        InlineEncoder::Braille => braille_encoder::encode_image(img, out, offset, print_at)?,
+    }
     if self_handle {
-        write!(out, "\x1B[{img_cells}B")?;
+        write!(out, "\x1B[{height}B")?;
     }
 
-    Ok(())
+    Ok((width, height, id))
+}
+
+/// the (width, height) in cells an image takes when encoded with `inline_encoder`,
+/// only the image header is read.
+pub fn image_cells(
+    img: &[u8],
+    inline_encoder: &InlineEncoder,
+) -> Result<(u16, u16), Box<dyn std::error::Error>> {
+    let (width, height) = ImageReader::new(Cursor::new(img))
+        .with_guessed_format()?
+        .into_dimensions()?;
+    let (width, height) = match inline_encoder {
+        // already in cells, 2 pixels per row
+        InlineEncoder::Ascii | InlineEncoder::HalfBlock => (width, height.div_ceil(2)),
+        InlineEncoder::Kitty
+        | InlineEncoder::Iterm
+        | InlineEncoder::Sixel
+        | InlineEncoder::Braille => (
+            term_misc::dim_to_cells(&format!("{width}px"), SizeDirection::Width)?,
+            term_misc::dim_to_cells(&format!("{height}px"), SizeDirection::Height)?,
+        ),
+    };
+
+    Ok((width as u16, height as u16))
 }
 
 #[derive(Clone, Copy, PartialEq)]
@@ -72,6 +106,8 @@ pub enum InlineEncoder {
     Iterm,
     Sixel,
     Ascii,
+    HalfBlock,
+    Braille,
 }
 impl InlineEncoder {
     /// auto detect which Encoder works for the current terminal
@@ -81,6 +117,8 @@ impl InlineEncoder {
         force_iterm: bool,
         force_sixel: bool,
         force_ascii: bool,
+        force_halfblock: bool,
+        force_braille: bool,
         env: &mut EnvIdentifiers,
     ) -> Self {
         if force_kitty {
@@ -95,21 +133,65 @@ impl InlineEncoder {
         if force_ascii {
             return Self::Ascii;
         }
+        if force_halfblock {
+            return Self::HalfBlock;
+        }
+        if force_braille {
+            return Self::Braille;
+        }
 
-        if kitty_encoder::is_kitty_capable(env) {
+        let caps = Capabilities::detect(env);
+        if caps.kitty {
             return Self::Kitty;
         }
-        if iterm_encoder::is_iterm_capable(env) {
+        if caps.iterm {
             return Self::Iterm;
         }
-        if sixel_encoder::is_sixel_capable(env) {
+        if caps.sixel {
             return Self::Sixel;
         }
+        if caps.truecolor {
+            return Self::HalfBlock;
+        }
 
         Self::Ascii
     }
 }
 
+static CAPABILITIES: OnceLock<Mutex<Option<Capabilities>>> = OnceLock::new();
+
+/// what the current terminal supports
+#[derive(Clone, Copy, Debug)]
+pub struct Capabilities {
+    pub kitty: bool,
+    pub iterm: bool,
+    pub sixel: bool,
+    pub truecolor: bool,
+}
+impl Capabilities {
+    /// runs every `is_*_capable` check once per process, later calls return the same result.
+    /// (forced encoders in [`InlineEncoder::auto_detect`] never get here)
+    pub fn detect(env: &mut EnvIdentifiers) -> Self {
+        let mut cached = CAPABILITIES
+            .get_or_init(|| Mutex::new(None))
+            .lock()
+            .unwrap();
+        *cached.get_or_insert_with(|| Capabilities {
+            kitty: kitty_encoder::is_kitty_capable(env),
+            iterm: iterm_encoder::is_iterm_capable(env),
+            sixel: sixel_encoder::is_sixel_capable(env),
+            truecolor: halfblock_encoder::is_truecolor_capable(env),
+        })
+    }
+}
+
+/// forgets the detected [`Capabilities`], so the next detection probes the terminal again
+pub fn reset_detection() {
+    if let Some(cached) = CAPABILITIES.get() {
+        *cached.lock().unwrap() = None;
+    }
+}
+
 pub fn set_tmux_passthrough(enabled: bool) {
     let status = if enabled { "on" } else { "off" };
     let _ = Command::new("tmux")
@@ -144,4 +226,49 @@ pub trait Frame {
     fn data(&self) -> &[u8];
     fn width(&self) -> u16;
     fn height(&self) -> u16;
+    /// how long the frame stays on screen in seconds, when the source says so (e.g. gif delays).
+    /// without it the players use the gap between timestamps
+    fn duration(&self) -> Option<f32> {
+        None
+    }
+}
+
+/// used when a frame has no duration, and its timestamp doesn't tell either (~30fps)
+const DEFAULT_FRAME_DELAY: Duration = Duration::from_millis(33);
+
+/// paces the frame players: a frame is held for its own duration,
+/// or until the timestamp of the frame after it
+#[derive(Default)]
+pub(crate) struct FramePacer {
+    /// timestamp and duration of the frame on screen
+    last: Option<(f32, Option<f32>)>,
+    gap: Option<Duration>,
+}
+
+impl FramePacer {
+    /// how long the frame on screen stays there, before `next` replaces it
+    pub(crate) fn hold(&mut self, next: &impl Frame) -> Duration {
+        let Some((timestamp, duration)) = self.last else {
+            self.last = Some((next.timestamp(), next.duration()));
+            return Duration::ZERO;
+        };
+        let hold = match duration {
+            Some(duration) => Duration::from_secs_f32(duration.max(0.0)),
+            None if next.timestamp() > timestamp => {
+                Duration::from_secs_f32(next.timestamp() - timestamp)
+            }
+            None => DEFAULT_FRAME_DELAY,
+        };
+        self.gap = Some(hold);
+        self.last = Some((next.timestamp(), next.duration()));
+        hold
+    }
+
+    /// how long the last frame stays on screen, it has no frame after it to go by
+    pub(crate) fn last_hold(&self) -> Duration {
+        match self.last {
+            Some((_, Some(duration))) => Duration::from_secs_f32(duration.max(0.0)),
+            _ => self.gap.unwrap_or(DEFAULT_FRAME_DELAY),
+        }
+    }
 }
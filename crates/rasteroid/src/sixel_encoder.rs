@@ -1,9 +1,13 @@
-use crate::term_misc::{self, EnvIdentifiers, loc_to_terminal, offset_to_terminal};
+use crate::{
+    Frame, FramePacer,
+    term_misc::{self, EnvIdentifiers, ensure_space, loc_to_terminal, offset_to_terminal},
+};
 use color_quant::NeuQuant;
 use image::{ImageBuffer, Rgb};
 use std::{
     error::Error,
     io::{self, Write},
+    time::{Duration, Instant},
 };
 
 const SIXEL_MIN: u8 = 0x3f; // '?'
@@ -45,6 +49,117 @@ pub fn encode_image(
     Ok(())
 }
 
+/// plays a sequence of frames (an animation or a video) as sixel images.
+/// each frame is drawn over the last one by restoring the cursor, on a timer that follows the
+/// frames timestamps. with `cycle` it loops over them until interrupted.
+///
+/// # Notes
+/// Each frame is expected to contain encoded image bytes (e.g., PNG, JPEG),
+/// already sized for the terminal.
+///
+/// # Example
+/// ```rust,no_run
+/// use rasteroid::Frame;
+/// use rasteroid::sixel_encoder::encode_frames;
+///
+/// struct PngFrame {
+///     timestamp: f32,
+///     png: Vec<u8>,
+/// }
+/// impl Frame for PngFrame {
+///     fn timestamp(&self) -> f32 {
+///         self.timestamp
+///     }
+///     fn data(&self) -> &[u8] {
+///         &self.png
+///     }
+///     // doesn't matter here
+///     fn width(&self) -> u16 {
+///         0
+///     }
+///     // doesn't matter here
+///     fn height(&self) -> u16 {
+///         0
+///     }
+/// }
+///
+/// let frames = vec![
+///     PngFrame { timestamp: 0.0, png: std::fs::read("frame1.png").unwrap() },
+///     PngFrame { timestamp: 0.5, png: std::fs::read("frame2.png").unwrap() },
+/// ];
+/// let mut out = std::io::stdout();
+/// encode_frames(&mut frames.into_iter(), &mut out, true, false).unwrap();
+/// ```
+pub fn encode_frames(
+    frames: &mut dyn Iterator<Item = impl Frame>,
+    out: &mut impl Write,
+    center: bool,
+    cycle: bool,
+) -> Result<(), Box<dyn Error>> {
+    let mut pacer = FramePacer::default();
+    let mut start = true;
+    let mut shown = Instant::now();
+    // each frame, and how long it's held for
+    let mut frame_outputs: Vec<(Vec<u8>, Duration)> = Vec::new();
+
+    for frame in frames {
+        let data = frame.data();
+        if data.is_empty() {
+            continue;
+        }
+        let img = image::load_from_memory(data)?;
+        let rgb_img = img.to_rgb8();
+
+        let mut buffer = Vec::new();
+        if start {
+            let cells = term_misc::dim_to_cells(
+                &format!("{}px", img.height()),
+                term_misc::SizeDirection::Height,
+            )?;
+            ensure_space(&mut buffer, cells as u16)?;
+            buffer.extend_from_slice(b"\x1b[s");
+            start = false;
+        } else {
+            buffer.extend_from_slice(b"\x1b[u\x1b[s");
+        }
+        // what's replayed by `cycle`, the cursor is restored on its own
+        let drawn = buffer.len();
+        if center {
+            let offset = term_misc::center_image(img.width() as u16, false);
+            buffer.extend_from_slice(offset_to_terminal(Some(offset)).as_bytes());
+        }
+        encode_sixel(&rgb_img, &mut buffer)?;
+
+        // encoding the frame already took part of the hold
+        let hold = pacer.hold(&frame);
+        std::thread::sleep(hold.saturating_sub(shown.elapsed()));
+        if let Some(last) = frame_outputs.last_mut() {
+            last.1 = hold;
+        }
+
+        out.write_all(&buffer)?;
+        out.flush()?;
+        shown = Instant::now();
+        if cycle {
+            frame_outputs.push((buffer[drawn..].to_vec(), Duration::ZERO));
+        }
+    }
+
+    std::thread::sleep(pacer.last_hold());
+    let Some(last) = frame_outputs.last_mut() else {
+        return Ok(());
+    };
+    last.1 = pacer.last_hold();
+    loop {
+        for (output, delay) in &frame_outputs {
+            out.write_all(b"\x1b[u\x1b[s")?;
+            out.write_all(output)?;
+            out.flush()?;
+            std::thread::sleep(*delay);
+        }
+    }
+}
+
 /// checks if the current terminal supports Sixel's graphic protocol
 /// # example:
 /// ```
@@ -59,6 +174,7 @@ pub fn is_sixel_capable(env: &mut EnvIdentifiers) -> bool {
     env.term_contains("foot") 
         || env.has_key("WT_PROFILE_ID") // windows-terminal
         || env.term_contains("sixel-tmux")
+        || env.is_wezterm()
 }
 
 fn encode_sixel(
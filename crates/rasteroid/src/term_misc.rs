@@ -15,10 +15,13 @@ use crate::get_tmux_terminal_name;
 pub struct Wininfo {
     pub sc_width: u16,
     pub sc_height: u16,
-    pub spx_width: u16,
-    pub spx_height: u16,
     pub is_tmux: bool,
     pub needs_inline: bool,
+    spx: OnceLock<(u16, u16)>,
+    spx_ioctl: (u16, u16),
+    spx_fallback: Size,
+    sc_unscaled: (u16, u16),
+    scale: f32,
 }
 
 /// converts image bytse into base64
@@ -69,26 +72,24 @@ impl Wininfo {
         is_tmux: bool,
         needs_inline: bool,
     ) -> Self {
-        let mut spx_width = 0;
-        let mut spx_height = 0;
+        let mut spx_ioctl = (0, 0);
         if let Ok(res) = window_size() {
             // ioctl for unix
-            spx_width = res.width;
-            spx_height = res.height;
+            spx_ioctl = (res.width, res.height);
         } else {
             // do windows api here
             #[cfg(windows)]
             if let Some(size) = get_size_windows() {
-                spx_width = size.0;
-                spx_height = size.1;
+                spx_ioctl = size;
             }
         }
+        // ioctl on the controlling tty, so it works when piped too
         let (mut sc_width, mut sc_height) = size().unwrap_or((0, 0));
-
-        // fallback or forcing
-        if spx_fallback.force || spx_width == 0 || spx_height == 0 {
-            spx_width = spx_fallback.width;
-            spx_height = spx_fallback.height;
+        if sc_width == 0 {
+            sc_width = env_dim("COLUMNS");
+        }
+        if sc_height == 0 {
+            sc_height = env_dim("LINES");
         }
         if sc_fallback.force || sc_width == 0 || sc_height == 0 {
             sc_width = sc_fallback.width;
@@ -100,12 +101,56 @@ impl Wininfo {
         Wininfo {
             sc_height,
             sc_width: (sc_width as f32 * scale) as u16,
-            spx_height,
-            spx_width: (spx_width as f32 * scale) as u16,
             is_tmux,
             needs_inline,
+            spx: OnceLock::new(),
+            spx_ioctl,
+            spx_fallback: spx_fallback.clone(),
+            sc_unscaled: (sc_width, sc_height),
+            scale,
         }
     }
+
+    /// width of the terminal in pixels
+    pub fn spx_width(&self) -> u16 {
+        self.spx().0
+    }
+
+    /// height of the terminal in pixels
+    pub fn spx_height(&self) -> u16 {
+        self.spx().1
+    }
+
+    // resolved on first use, so the terminal is only queried when something needs pixels
+    fn spx(&self) -> (u16, u16) {
+        *self.spx.get_or_init(|| {
+            let fallback = &self.spx_fallback;
+            let (mut spx_width, mut spx_height) = self.spx_ioctl;
+            // the ioctl is often left empty (e.g. in splits and over ssh), asking the terminal itself
+            if !fallback.force
+                && (spx_width == 0 || spx_height == 0)
+                && let Some(size) =
+                    query_pixel_size(self.sc_unscaled, std::time::Duration::from_millis(200))
+            {
+                (spx_width, spx_height) = size;
+            }
+
+            // fallback or forcing
+            if fallback.force || spx_width == 0 || spx_height == 0 {
+                spx_width = fallback.width;
+                spx_height = fallback.height;
+            }
+
+            ((spx_width as f32 * self.scale) as u16, spx_height)
+        })
+    }
+}
+
+fn env_dim(key: &str) -> u16 {
+    env::var(key)
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(0)
 }
 
 /// setting a fallback for when fails to query spx and sc.
@@ -212,13 +257,18 @@ pub fn center_image(image_width: u16, is_ascii: bool) -> u16 {
     let offset = if is_ascii {
         (winsize.sc_width as f32 - image_width as f32) / 2.0
     } else {
-        let offset_x = (winsize.spx_width as f32 - image_width as f32) / 2.0;
-        offset_x / (winsize.spx_width as f32 / winsize.sc_width as f32)
+        let offset_x = (winsize.spx_width() as f32 - image_width as f32) / 2.0;
+        offset_x / (winsize.spx_width() as f32 / winsize.sc_width as f32)
     };
 
     offset.round() as u16
 }
 
+/// the blank rows above an image `image_rows` tall, that put it in the middle of the terminal
+pub fn vcenter_image(image_rows: u16) -> u16 {
+    get_wininfo().sc_height.saturating_sub(image_rows) / 2
+}
+
 /// convert any format of width / height into pixels.
 /// for instance 80% would be converted to the size of screen in the direction specified * 0.8.
 /// accepted formats are % (percent) / c (cells) / px (pixels) / or just number
@@ -232,8 +282,8 @@ pub fn dim_to_px(dim: &str, direction: SizeDirection) -> Result<u32, String> {
     let (spx, sc) = if not_px {
         let winsize = get_wininfo();
         match direction {
-            SizeDirection::Width => (winsize.spx_width, winsize.sc_width),
-            SizeDirection::Height => (winsize.spx_height, winsize.sc_height),
+            SizeDirection::Width => (winsize.spx_width(), winsize.sc_width),
+            SizeDirection::Height => (winsize.spx_height(), winsize.sc_height),
         }
     } else {
         (1, 1)
@@ -271,8 +321,8 @@ pub fn dim_to_cells(dim: &str, direction: SizeDirection) -> Result<u32, String>
     let (spx, sc) = if needs_calc {
         let winsize = get_wininfo();
         match direction {
-            SizeDirection::Width => (winsize.spx_width, winsize.sc_width),
-            SizeDirection::Height => (winsize.spx_height, winsize.sc_height),
+            SizeDirection::Width => (winsize.spx_width(), winsize.sc_width),
+            SizeDirection::Height => (winsize.spx_height(), winsize.sc_height),
         }
     } else {
         (1, 1) // dummy values, won’t be used
@@ -361,6 +411,9 @@ impl EnvIdentifiers {
             "KONSOLE_VERSION",
             "WT_PROFILE_ID",
             "TMUX",
+            "COLORTERM",
+            "GHOSTTY_RESOURCES_DIR",
+            "WEZTERM_PANE",
         ];
         let mut result = HashMap::new();
 
@@ -414,6 +467,16 @@ impl EnvIdentifiers {
         .any(|key| self.contains(key, term))
     }
 
+    /// checks if the current terminal is ghostty, the env var survives inside of tmux
+    pub fn is_ghostty(&mut self) -> bool {
+        self.term_contains("ghostty") || self.has_key("GHOSTTY_RESOURCES_DIR")
+    }
+
+    /// checks if the current terminal is wezterm, the env var survives inside of tmux
+    pub fn is_wezterm(&mut self) -> bool {
+        self.term_contains("wezterm") || self.has_key("WEZTERM_PANE")
+    }
+
     /// checks if the current terminal is a tmux terminal
     pub fn is_tmux(&mut self) -> bool {
         self.term_contains("tmux") || self.has_key("TMUX")
@@ -459,3 +522,201 @@ pub fn setup_signal_handler() -> Arc<AtomicBool> {
 
     shutdown
 }
+
+pub type Rgb = (u8, u8, u8);
+
+/// the colors the terminal itself uses
+#[derive(Debug, Clone, Default)]
+pub struct TerminalColors {
+    /// the 16 ansi colors
+    pub palette: [Option<Rgb>; 16],
+    pub foreground: Option<Rgb>,
+    pub background: Option<Rgb>,
+}
+
+/// writes `query` to the terminal followed by a device attributes query, which every terminal
+/// answers, and returns everything read back until that answer.
+/// `None` when there's no terminal or it didn't answer within `timeout`.
+#[cfg(unix)]
+fn query_tty(query: &str, timeout: std::time::Duration) -> Option<String> {
+    use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
+    use std::{io::Read, os::fd::AsRawFd, time::Instant};
+
+    let mut tty = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .ok()?;
+
+    enable_raw_mode().ok()?;
+    if tty
+        .write_all(query.as_bytes())
+        .and_then(|_| tty.write_all(b"\x1b[c"))
+        .and_then(|_| tty.flush())
+        .is_err()
+    {
+        let _ = disable_raw_mode();
+        return None;
+    }
+
+    // only read what poll says is there, so nothing is left blocked on the tty
+    // (and eating keystrokes) once we give up
+    let deadline = Instant::now() + timeout;
+    let mut response = Vec::new();
+    let mut buf = [0u8; 256];
+    let answered = loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        let mut fds = libc::pollfd {
+            fd: tty.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let left_ms = left.as_millis().min(libc::c_int::MAX as u128) as libc::c_int;
+        // SAFETY: `fds` is a single valid pollfd that outlives the call
+        if unsafe { libc::poll(&mut fds, 1, left_ms) } <= 0 {
+            break false;
+        }
+        match tty.read(&mut buf) {
+            Ok(0) | Err(_) => break false,
+            Ok(n) => {
+                response.extend_from_slice(&buf[..n]);
+                if ends_with_device_attributes(&response) {
+                    break true;
+                }
+            }
+        }
+    };
+    let _ = disable_raw_mode();
+
+    answered.then(|| String::from_utf8_lossy(&response).into_owned())
+}
+
+/// asks the terminal for its colors (OSC 4 / 10 / 11).
+/// returns `None` when there's no terminal, or it didn't answer within `timeout`.
+/// the terminal is only asked once, later calls get the same answer.
+#[cfg(unix)]
+pub fn query_terminal_colors(timeout: std::time::Duration) -> Option<TerminalColors> {
+    static COLORS: OnceLock<Option<TerminalColors>> = OnceLock::new();
+    COLORS.get_or_init(|| ask_terminal_colors(timeout)).clone()
+}
+
+#[cfg(unix)]
+fn ask_terminal_colors(timeout: std::time::Duration) -> Option<TerminalColors> {
+    let mut query = String::new();
+    for i in 0..16 {
+        query.push_str(&format!("\x1b]4;{i};?\x1b\\"));
+    }
+    query.push_str("\x1b]10;?\x1b\\\x1b]11;?\x1b\\");
+
+    let response = query_tty(&query, timeout)?;
+    let colors = parse_color_responses(&response);
+    let has_any = colors.foreground.is_some()
+        || colors.background.is_some()
+        || colors.palette.iter().any(Option::is_some);
+    has_any.then_some(colors)
+}
+
+#[cfg(not(unix))]
+pub fn query_terminal_colors(_timeout: std::time::Duration) -> Option<TerminalColors> {
+    None
+}
+
+/// asks the terminal for the size of its text area in pixels (`CSI 14 t`),
+/// and the size of a cell (`CSI 16 t`) for terminals that only answer that one.
+/// `sc` is the size in cells, used to turn the cell size into the window size.
+/// returns `None` when there's no terminal, or it didn't answer within `timeout`.
+#[cfg(unix)]
+pub fn query_pixel_size(sc: (u16, u16), timeout: std::time::Duration) -> Option<(u16, u16)> {
+    let response = query_tty("\x1b[14t\x1b[16t", timeout)?;
+    parse_pixel_size(&response, sc)
+}
+
+#[cfg(not(unix))]
+pub fn query_pixel_size(_sc: (u16, u16), _timeout: std::time::Duration) -> Option<(u16, u16)> {
+    None
+}
+
+/// parses the `ESC [ 4 ; H ; W t` (window) and `ESC [ 6 ; H ; W t` (cell) replies
+fn parse_pixel_size(response: &str, sc: (u16, u16)) -> Option<(u16, u16)> {
+    let mut window = None;
+    let mut cell = None;
+    for reply in response.split("\x1b[").skip(1) {
+        let Some(reply) = reply.split_once('t').map(|(r, _)| r) else {
+            continue;
+        };
+        let mut parts = reply.split(';').map(|p| p.parse::<u16>().ok());
+        let (Some(Some(kind)), Some(Some(h)), Some(Some(w)), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        if w == 0 || h == 0 {
+            continue;
+        }
+        match kind {
+            4 => window = Some((w, h)),
+            6 => cell = Some((w, h)),
+            _ => {}
+        }
+    }
+
+    window.or_else(|| {
+        let (w, h) = cell?;
+        (sc.0 != 0 && sc.1 != 0).then(|| (w.saturating_mul(sc.0), h.saturating_mul(sc.1)))
+    })
+}
+
+// `ESC [ ? ... c`
+#[cfg(unix)]
+fn ends_with_device_attributes(buf: &[u8]) -> bool {
+    let Some(start) = buf.windows(3).rposition(|w| w == b"\x1b[?") else {
+        return false;
+    };
+    buf[start + 3..]
+        .iter()
+        .position(|&b| b == b'c')
+        .is_some_and(|end| {
+            buf[start + 3..start + 3 + end]
+                .iter()
+                .all(|b| b.is_ascii_digit() || *b == b';')
+        })
+}
+
+/// parses the `ESC ] 4 ; N ; rgb:RRRR/GGGG/BBBB` (and 10 / 11) replies
+fn parse_color_responses(response: &str) -> TerminalColors {
+    let mut colors = TerminalColors::default();
+    for reply in response.split("\x1b]").skip(1) {
+        let reply = reply.split(['\x07', '\x1b']).next().unwrap_or_default();
+        let mut parts = reply.split(';');
+        let (target, value) = match (parts.next(), parts.next(), parts.next()) {
+            (Some("4"), Some(index), Some(value)) => (index.parse::<usize>().ok(), value),
+            (Some("10"), Some(value), None) => (Some(16), value),
+            (Some("11"), Some(value), None) => (Some(17), value),
+            _ => continue,
+        };
+        let Some(rgb) = parse_rgb(value) else {
+            continue;
+        };
+        match target {
+            Some(i @ 0..16) => colors.palette[i] = Some(rgb),
+            Some(16) => colors.foreground = Some(rgb),
+            Some(17) => colors.background = Some(rgb),
+            _ => {}
+        }
+    }
+    colors
+}
+
+// `rgb:R/G/B`, each of 1-4 hex digits
+fn parse_rgb(value: &str) -> Option<Rgb> {
+    let value = value.strip_prefix("rgb:")?;
+    let mut channels = value.split('/').map(|c| {
+        if !(1..=4).contains(&c.len()) {
+            return None;
+        }
+        let v = u32::from_str_radix(c, 16).ok()?;
+        let max = 16u32.pow(c.len() as u32) - 1;
+        Some((v * 255 / max) as u8)
+    });
+    Some((channels.next()??, channels.next()??, channels.next()??))
+}